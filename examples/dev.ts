@@ -1,4 +1,7 @@
-import { Renderer, Camera, Vector3, Matrix4, NSrgba } from '../index.js';
+import {
+  Renderer, Camera, Vector3, Matrix4, NSrgba, CpuMesh, Mesh, Gm, PhysicalMaterial,
+  DirectionalLight, AmbientLight,
+} from '../index.js';
 
 async function main() {
   console.log('🚀 Initializing Three-D NAPI example...');
@@ -43,15 +46,14 @@ async function main() {
     const orange = new NSrgba(1.0, 0.5, 0.0, 1.0);
     console.log('🎨 Sample Color (Orange):', `rgba(${orange.r * 255}, ${orange.g * 255}, ${orange.b * 255}, ${orange.a})`);
 
-    // 6. Simulate rendering a frame
-    const frameSuccess = renderer.renderFrame();
-    console.log({frameSuccess});
-    const timeout = new Promise((resolve) => {
-      setTimeout(() => {
-        resolve(true);
-      }, 10000);
-    });
-    await timeout;
+    // 6. Render a lit cube and save it
+    const ctx = renderer.context;
+    const cube = new Gm(new Mesh(ctx, CpuMesh.cube()), new PhysicalMaterial(orange, 0.0, 0.6));
+    const sun = new DirectionalLight(ctx, 2.0, new NSrgba(1, 1, 1, 1), [-1, -1, -1]);
+    const ambient = new AmbientLight(ctx, 0.2, new NSrgba(1, 1, 1, 1));
+    renderer.render([cube], camera, [sun, ambient]);
+    renderer.saveScreenshot('frame.png');
+    console.log('🖼️ Saved frame.png');
   } catch (error) {
     console.error('❌ Error in example:', error);
   }
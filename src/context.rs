@@ -1,4 +1,9 @@
+use crate::core::compressed::supports_compression;
+use crate::enums::CompressionTextureType;
+use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::rc::Rc;
+use std::sync::Arc;
 
 #[napi]
 pub struct ActiveAttribute {}
@@ -9,8 +14,133 @@ pub struct ActiveTransformFeedback {}
 #[napi]
 pub struct ActiveUniform {}
 
-#[napi]
-pub struct Context {}
+/// `glGetQueryObjectui64v`, which glow only exposes taking a buffer offset.
+pub(crate) type GetQueryObjectUi64v = unsafe extern "system" fn(u32, u32, *mut u64);
+
+/// Headless OpenGL context shared by every GPU resource created from it.
+/// Created on an EGL device without a window or surface.
+#[napi]
+#[derive(Clone)]
+pub struct Context {
+    pub(crate) inner: three_d::Context,
+    /// Reads 64-bit query results, if the driver has the function.
+    pub(crate) get_query_object_u64: Option<GetQueryObjectUi64v>,
+    // Keeps the GL context alive (and current) for as long as `inner` is used.
+    _gl: Rc<glutin::context::PossiblyCurrentContext>,
+}
+
+#[napi]
+impl Context {
+    /// Creates a new headless context on the first available EGL device.
+    #[napi(constructor)]
+    pub fn new() -> Result<Self> {
+        let (gl, get_query_object_u64, gl_context) = create_headless_gl()?;
+        let inner = three_d::Context::from_gl_context(Arc::new(gl))
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        Ok(Context {
+            inner,
+            get_query_object_u64,
+            _gl: Rc::new(gl_context),
+        })
+    }
+
+    /// Returns whether the context reports no pending GL errors.
+    /// The default framebuffer is not checked since a headless context has none.
+    #[napi]
+    pub fn is_valid(&self) -> bool {
+        use three_d::context::HasContext;
+        // SAFETY: the context is current on this thread for its whole lifetime.
+        unsafe { self.inner.get_error() == three_d::context::NO_ERROR }
+    }
+
+    /// Returns a short description of the context.
+    #[napi]
+    pub fn get_info(&self) -> String {
+        use three_d::context::HasContext;
+        // SAFETY: the context is current on this thread for its whole lifetime.
+        let (renderer, version) = unsafe {
+            (
+                self.inner.get_parameter_string(three_d::context::RENDERER),
+                self.inner.get_parameter_string(three_d::context::VERSION),
+            )
+        };
+        format!("Context {{ renderer: {renderer}, version: {version} }}")
+    }
+
+    /// Returns whether textures of the given compression type can be uploaded
+    /// without decoding them on the CPU.
+    #[napi]
+    pub fn supports_compression(&self, compression: CompressionTextureType) -> bool {
+        supports_compression(&self.inner, &compression)
+    }
+}
+
+#[cfg(not(target_vendor = "apple"))]
+fn create_headless_gl() -> Result<(
+    three_d::context::Context,
+    Option<GetQueryObjectUi64v>,
+    glutin::context::PossiblyCurrentContext,
+)> {
+    use glutin::api::egl::{device::Device, display::Display};
+    use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+    use glutin::context::{ContextApi, ContextAttributesBuilder, Version as GlVersion};
+    use glutin::display::GlDisplay;
+    use std::ffi::CString;
+
+    let no_context = |e: glutin::error::Error| Error::new(Status::GenericFailure, e.to_string());
+    let device = Device::query_devices()
+        .map_err(no_context)?
+        .next()
+        .ok_or_else(|| Error::new(Status::GenericFailure, "No EGL device found"))?;
+    // SAFETY: no native display is passed, EGL picks the device's own display.
+    let display = unsafe { Display::with_device(&device, None) }.map_err(no_context)?;
+    let template = ConfigTemplateBuilder::new()
+        .with_surface_type(ConfigSurfaceTypes::empty())
+        .build();
+    // SAFETY: the template does not reference a native window.
+    let config = unsafe { display.find_configs(template) }
+        .map_err(no_context)?
+        .next()
+        .ok_or_else(|| Error::new(Status::GenericFailure, "No suitable EGL config found"))?;
+    let attributes = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::OpenGl(Some(GlVersion::new(3, 3))))
+        .build(None);
+    // SAFETY: the config was created by this display.
+    let gl_context = unsafe { display.create_context(&config, &attributes) }
+        .map_err(no_context)?
+        .make_current_surfaceless()
+        .map_err(no_context)?;
+    // SAFETY: the context was made current above, so the loaded pointers are valid.
+    let gl = unsafe {
+        three_d::context::Context::from_loader_function(|name| {
+            let name = CString::new(name).unwrap();
+            display.get_proc_address(name.as_c_str())
+        })
+    };
+    let get_query_object_u64 = display.get_proc_address(c"glGetQueryObjectui64v");
+    // SAFETY: a non-null pointer returned for this name is the GL function,
+    // whose signature `GetQueryObjectUi64v` matches.
+    let get_query_object_u64 = (!get_query_object_u64.is_null()).then(|| unsafe {
+        std::mem::transmute::<*const std::ffi::c_void, GetQueryObjectUi64v>(get_query_object_u64)
+    });
+    Ok((
+        gl,
+        get_query_object_u64,
+        glutin::context::PossiblyCurrentContext::Egl(gl_context),
+    ))
+}
+
+#[cfg(target_vendor = "apple")]
+fn create_headless_gl() -> Result<(
+    three_d::context::Context,
+    Option<GetQueryObjectUi64v>,
+    glutin::context::PossiblyCurrentContext,
+)> {
+    Err(Error::new(
+        Status::GenericFailure,
+        "Headless contexts are not supported on this platform",
+    ))
+}
 
 #[napi]
 pub struct DebugMessageLogEntry {}
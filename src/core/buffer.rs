@@ -1,4 +1,14 @@
+use crate::context::Context;
+use crate::prelude::NF16;
+use napi::bindgen_prelude::{Either3, Float32Array, Uint16Array};
+use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::sync::Arc;
+use three_d::f16;
+
+/// Vertex attribute values: a `Float32Array`, or half floats as a
+/// `Uint16Array` of IEEE bit patterns or an array of `NF16`.
+pub type VertexData = Either3<Float32Array, Uint16Array, Vec<&'static NF16>>;
 
 #[napi]
 pub struct ElementBuffer {}
@@ -9,5 +19,214 @@ pub struct InstanceBuffer {}
 #[napi]
 pub struct UniformBuffer {}
 
+/// Typed GPU storage of a [`VertexBuffer`].
+pub(crate) enum VertexAttribute {
+    F32(three_d::VertexBuffer<f32>),
+    F32x2(three_d::VertexBuffer<[f32; 2]>),
+    F32x3(three_d::VertexBuffer<[f32; 3]>),
+    F32x4(three_d::VertexBuffer<[f32; 4]>),
+    F16(three_d::VertexBuffer<f16>),
+    F16x2(three_d::VertexBuffer<[f16; 2]>),
+    F16x3(three_d::VertexBuffer<[f16; 3]>),
+    F16x4(three_d::VertexBuffer<[f16; 4]>),
+}
+
+/// Values of a vertex attribute converted to the precision of the buffer.
+enum Values {
+    F32(Vec<f32>),
+    F16(Vec<f16>),
+}
+
+impl Values {
+    fn new(data: &VertexData, half: bool) -> Self {
+        match (data, half) {
+            (Either3::A(data), false) => Values::F32(data.to_vec()),
+            (Either3::A(data), true) => {
+                Values::F16(data.iter().map(|&v| f16::from_f32(v)).collect())
+            }
+            (Either3::B(data), false) => {
+                Values::F32(data.iter().map(|&b| f16::from_bits(b).to_f32()).collect())
+            }
+            (Either3::B(data), true) => {
+                Values::F16(data.iter().map(|&b| f16::from_bits(b)).collect())
+            }
+            (Either3::C(data), false) => {
+                Values::F32(data.iter().map(|&v| f16::from(v).to_f32()).collect())
+            }
+            (Either3::C(data), true) => Values::F16(data.iter().map(|&v| v.into()).collect()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Values::F32(data) => data.len(),
+            Values::F16(data) => data.len(),
+        }
+    }
+}
+
+fn vectors<T: Copy + Default, const N: usize>(data: &[T]) -> Vec<[T; N]> {
+    data.chunks_exact(N)
+        .map(|chunk| {
+            let mut vector = [T::default(); N];
+            vector.copy_from_slice(chunk);
+            vector
+        })
+        .collect()
+}
+
+impl VertexAttribute {
+    fn new(context: &three_d::Context, components: u32, values: &Values) -> Self {
+        match (values, components) {
+            (Values::F32(data), 1) => {
+                VertexAttribute::F32(three_d::VertexBuffer::new_with_data(context, data))
+            }
+            (Values::F32(data), 2) => VertexAttribute::F32x2(three_d::VertexBuffer::new_with_data(
+                context,
+                &vectors(data),
+            )),
+            (Values::F32(data), 3) => VertexAttribute::F32x3(three_d::VertexBuffer::new_with_data(
+                context,
+                &vectors(data),
+            )),
+            (Values::F32(data), _) => VertexAttribute::F32x4(three_d::VertexBuffer::new_with_data(
+                context,
+                &vectors(data),
+            )),
+            (Values::F16(data), 1) => {
+                VertexAttribute::F16(three_d::VertexBuffer::new_with_data(context, data))
+            }
+            (Values::F16(data), 2) => VertexAttribute::F16x2(three_d::VertexBuffer::new_with_data(
+                context,
+                &vectors(data),
+            )),
+            (Values::F16(data), 3) => VertexAttribute::F16x3(three_d::VertexBuffer::new_with_data(
+                context,
+                &vectors(data),
+            )),
+            (Values::F16(data), _) => VertexAttribute::F16x4(three_d::VertexBuffer::new_with_data(
+                context,
+                &vectors(data),
+            )),
+        }
+    }
+
+    fn fill(&mut self, values: &Values) {
+        match (self, values) {
+            (VertexAttribute::F32(buffer), Values::F32(data)) => buffer.fill(data),
+            (VertexAttribute::F32x2(buffer), Values::F32(data)) => buffer.fill(&vectors(data)),
+            (VertexAttribute::F32x3(buffer), Values::F32(data)) => buffer.fill(&vectors(data)),
+            (VertexAttribute::F32x4(buffer), Values::F32(data)) => buffer.fill(&vectors(data)),
+            (VertexAttribute::F16(buffer), Values::F16(data)) => buffer.fill(data),
+            (VertexAttribute::F16x2(buffer), Values::F16(data)) => buffer.fill(&vectors(data)),
+            (VertexAttribute::F16x3(buffer), Values::F16(data)) => buffer.fill(&vectors(data)),
+            (VertexAttribute::F16x4(buffer), Values::F16(data)) => buffer.fill(&vectors(data)),
+            _ => unreachable!("values are converted to the precision of the buffer"),
+        }
+    }
+
+    fn vertex_count(&self) -> u32 {
+        match self {
+            VertexAttribute::F32(buffer) => buffer.vertex_count(),
+            VertexAttribute::F32x2(buffer) => buffer.vertex_count(),
+            VertexAttribute::F32x3(buffer) => buffer.vertex_count(),
+            VertexAttribute::F32x4(buffer) => buffer.vertex_count(),
+            VertexAttribute::F16(buffer) => buffer.vertex_count(),
+            VertexAttribute::F16x2(buffer) => buffer.vertex_count(),
+            VertexAttribute::F16x3(buffer) => buffer.vertex_count(),
+            VertexAttribute::F16x4(buffer) => buffer.vertex_count(),
+        }
+    }
+}
+
+/// A vertex attribute stored on the GPU with one to four float components
+/// per vertex, in full or half precision. Half precision halves the memory
+/// of attributes such as normals that do not need 32 bits.
 #[napi]
-pub struct VertexBuffer {}
+pub struct VertexBuffer {
+    pub(crate) inner: Arc<VertexAttribute>,
+    components: u32,
+    half: bool,
+}
+
+/// Checks that `len` values make whole vertices of `components` components.
+fn check_layout(len: usize, components: u32) -> Result<()> {
+    if !(1..=4).contains(&components) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("Vertex attributes have 1 to 4 components, got {components}"),
+        ));
+    }
+    if len == 0 || !len.is_multiple_of(components as usize) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("Expected a non-zero multiple of {components} values, got {len}"),
+        ));
+    }
+    Ok(())
+}
+
+#[napi]
+impl VertexBuffer {
+    /// Uploads attribute data with `components` values per vertex. `half`
+    /// picks the precision on the GPU and defaults to that of the data:
+    /// floats are rounded to half floats with `half: true`, and half data is
+    /// widened to floats with `half: false`.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        data: VertexData,
+        components: u32,
+        half: Option<bool>,
+    ) -> Result<Self> {
+        let half = half.unwrap_or(!matches!(data, Either3::A(_)));
+        let values = Values::new(&data, half);
+        check_layout(values.len(), components)?;
+        Ok(VertexBuffer {
+            inner: Arc::new(VertexAttribute::new(&context.inner, components, &values)),
+            components,
+            half,
+        })
+    }
+
+    /// Replaces the content of the buffer. The data is converted to the
+    /// precision of the buffer and may hold a different number of vertices.
+    /// Fails if the buffer is currently shared with a geometry.
+    #[napi]
+    pub fn fill(&mut self, data: VertexData) -> Result<()> {
+        let values = Values::new(&data, self.half);
+        check_layout(values.len(), self.components)?;
+        let buffer = Arc::get_mut(&mut self.inner).ok_or_else(|| {
+            Error::new(
+                Status::GenericFailure,
+                "VertexBuffer is in use and cannot be modified",
+            )
+        })?;
+        buffer.fill(&values);
+        Ok(())
+    }
+
+    /// Number of components per vertex.
+    #[napi(getter)]
+    pub fn components(&self) -> u32 {
+        self.components
+    }
+
+    /// Whether the values are stored as half floats.
+    #[napi(getter)]
+    pub fn is_half(&self) -> bool {
+        self.half
+    }
+
+    #[napi(getter)]
+    pub fn vertex_count(&self) -> u32 {
+        self.inner.vertex_count()
+    }
+
+    /// Size of the data on the GPU in bytes.
+    #[napi(getter)]
+    pub fn size_bytes(&self) -> u32 {
+        let component_bytes = if self.half { 2 } else { 4 };
+        self.vertex_count() * self.components * component_bytes
+    }
+}
@@ -0,0 +1,530 @@
+use crate::context::Context;
+use crate::core::texture::{
+    bound_texture, pixel_data, Component, CpuTexture, PixelData, TextureOptions,
+};
+use crate::enums::CompressionTextureType;
+use crate::types::{TextureHeight, TextureWidth};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::Arc;
+use three_d::context::HasContext;
+use three_d::{Interpolation, Wrapping};
+
+/// A block-compressed pixel format, as stored in KTX2 and DDS files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlockFormat {
+    Bc1 { alpha: bool, srgb: bool },
+    Bc2 { srgb: bool },
+    Bc3 { srgb: bool },
+    Bc4 { signed: bool },
+    Bc5 { signed: bool },
+    Bc6h { signed: bool },
+    Bc7 { srgb: bool },
+    Etc2 { srgb: bool },
+    Etc2A1 { srgb: bool },
+    Etc2Eac { srgb: bool },
+    EacR11 { signed: bool },
+    EacRg11 { signed: bool },
+    Astc { width: u32, height: u32, srgb: bool },
+}
+
+/// ASTC block footprints in the order of their GL and Vulkan format enums.
+pub(crate) const ASTC_BLOCKS: [(u32, u32); 14] = [
+    (4, 4),
+    (5, 4),
+    (5, 5),
+    (6, 5),
+    (6, 6),
+    (8, 5),
+    (8, 6),
+    (8, 8),
+    (10, 5),
+    (10, 6),
+    (10, 8),
+    (10, 10),
+    (12, 10),
+    (12, 12),
+];
+
+impl BlockFormat {
+    /// Width and height of a block in texels.
+    pub fn block_size(&self) -> (u32, u32) {
+        match *self {
+            BlockFormat::Astc { width, height, .. } => (width, height),
+            _ => (4, 4),
+        }
+    }
+
+    /// Size of a block in bytes.
+    pub fn block_bytes(&self) -> usize {
+        match self {
+            BlockFormat::Bc1 { .. }
+            | BlockFormat::Bc4 { .. }
+            | BlockFormat::Etc2 { .. }
+            | BlockFormat::Etc2A1 { .. }
+            | BlockFormat::EacR11 { .. } => 8,
+            _ => 16,
+        }
+    }
+
+    /// Size in bytes of a mip level with the given dimensions, or `None` if
+    /// it does not fit in memory.
+    pub fn level_size(&self, width: u32, height: u32) -> Option<usize> {
+        let (block_width, block_height) = self.block_size();
+        (width.div_ceil(block_width) as usize)
+            .checked_mul(height.div_ceil(block_height) as usize)?
+            .checked_mul(self.block_bytes())
+    }
+
+    pub fn compression(&self) -> CompressionTextureType {
+        match self {
+            BlockFormat::Bc1 { srgb: true, .. }
+            | BlockFormat::Bc2 { srgb: true }
+            | BlockFormat::Bc3 { srgb: true } => CompressionTextureType::S3TCSRGB,
+            BlockFormat::Bc1 { .. } | BlockFormat::Bc2 { .. } | BlockFormat::Bc3 { .. } => {
+                CompressionTextureType::S3TC
+            }
+            BlockFormat::Bc4 { .. } | BlockFormat::Bc5 { .. } => CompressionTextureType::RGTC,
+            BlockFormat::Bc6h { .. } | BlockFormat::Bc7 { .. } => CompressionTextureType::BPTC,
+            BlockFormat::Etc2 { .. } | BlockFormat::Etc2A1 { .. } => CompressionTextureType::ETC2,
+            BlockFormat::Etc2Eac { .. }
+            | BlockFormat::EacR11 { .. }
+            | BlockFormat::EacRg11 { .. } => CompressionTextureType::ETC2EAC,
+            BlockFormat::Astc { .. } => CompressionTextureType::ASTC,
+        }
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        match *self {
+            BlockFormat::Bc1 { srgb, .. }
+            | BlockFormat::Bc2 { srgb }
+            | BlockFormat::Bc3 { srgb }
+            | BlockFormat::Bc7 { srgb }
+            | BlockFormat::Etc2 { srgb }
+            | BlockFormat::Etc2A1 { srgb }
+            | BlockFormat::Etc2Eac { srgb }
+            | BlockFormat::Astc { srgb, .. } => srgb,
+            _ => false,
+        }
+    }
+
+    /// Component type of the decoded texels: float for HDR and signed formats.
+    pub fn component(&self) -> Component {
+        match self {
+            BlockFormat::Bc4 { signed: true }
+            | BlockFormat::Bc5 { signed: true }
+            | BlockFormat::Bc6h { .. }
+            | BlockFormat::EacR11 { signed: true }
+            | BlockFormat::EacRg11 { signed: true } => Component::F32,
+            _ => Component::U8,
+        }
+    }
+
+    /// The GL internal format.
+    pub fn gl_format(&self) -> u32 {
+        let srgb = self.is_srgb();
+        let pick = |linear: u32, srgb_format: u32| if srgb { srgb_format } else { linear };
+        match *self {
+            BlockFormat::Bc1 { alpha: false, .. } => pick(0x83F0, 0x8C4C),
+            BlockFormat::Bc1 { alpha: true, .. } => pick(0x83F1, 0x8C4D),
+            BlockFormat::Bc2 { .. } => pick(0x83F2, 0x8C4E),
+            BlockFormat::Bc3 { .. } => pick(0x83F3, 0x8C4F),
+            BlockFormat::Bc4 { signed } => 0x8DBB + signed as u32,
+            BlockFormat::Bc5 { signed } => 0x8DBD + signed as u32,
+            BlockFormat::Bc6h { signed } => 0x8E8F - signed as u32,
+            BlockFormat::Bc7 { .. } => pick(0x8E8C, 0x8E8D),
+            BlockFormat::EacR11 { signed } => 0x9270 + signed as u32,
+            BlockFormat::EacRg11 { signed } => 0x9272 + signed as u32,
+            BlockFormat::Etc2 { .. } => pick(0x9274, 0x9275),
+            BlockFormat::Etc2A1 { .. } => pick(0x9276, 0x9277),
+            BlockFormat::Etc2Eac { .. } => pick(0x9278, 0x9279),
+            BlockFormat::Astc { width, height, .. } => {
+                let index = ASTC_BLOCKS
+                    .iter()
+                    .position(|&size| size == (width, height))
+                    .unwrap_or(0) as u32;
+                pick(0x93B0 + index, 0x93D0 + index)
+            }
+        }
+    }
+}
+
+/// Returns whether the context can sample textures of the given compression type natively.
+pub(crate) fn supports_compression(
+    context: &three_d::Context,
+    compression: &CompressionTextureType,
+) -> bool {
+    let extensions = context.supported_extensions();
+    let has = |name: &str| extensions.contains(name);
+    let version = context.version();
+    let desktop = |major: u32, minor: u32| {
+        !version.is_embedded && (version.major, version.minor) >= (major, minor)
+    };
+    let es = |major: u32| version.is_embedded && version.major >= major;
+    match compression {
+        CompressionTextureType::S3TC => has("GL_EXT_texture_compression_s3tc"),
+        CompressionTextureType::S3TCSRGB => {
+            has("GL_EXT_texture_compression_s3tc")
+                && (has("GL_EXT_texture_sRGB") || has("GL_EXT_texture_compression_s3tc_srgb"))
+        }
+        CompressionTextureType::RGTC => {
+            desktop(3, 0)
+                || has("GL_ARB_texture_compression_rgtc")
+                || has("GL_EXT_texture_compression_rgtc")
+        }
+        CompressionTextureType::BPTC => {
+            desktop(4, 2)
+                || has("GL_ARB_texture_compression_bptc")
+                || has("GL_EXT_texture_compression_bptc")
+        }
+        CompressionTextureType::ETC2 | CompressionTextureType::ETC2EAC => {
+            desktop(4, 3) || es(3) || has("GL_ARB_ES3_compatibility")
+        }
+        CompressionTextureType::ASTC => has("GL_KHR_texture_compression_astc_ldr"),
+        CompressionTextureType::SVGA => false,
+    }
+}
+
+/// A block-compressed 2D texture with its mip chain, stored in CPU memory.
+///
+/// Rows are kept in file order, so the first stored row ends up at `v = 0`
+/// when sampled, unlike [`CpuTexture`] which puts the top row at `v = 1`.
+#[napi]
+pub struct CpuCompressedTexture {
+    pub(crate) name: String,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) format: BlockFormat,
+    /// Mip levels, largest first.
+    pub(crate) levels: Vec<Vec<u8>>,
+    pub(crate) options: TextureOptions,
+}
+
+impl CpuCompressedTexture {
+    /// Validates the mip chain against the format and dimensions.
+    pub(crate) fn new(
+        width: u32,
+        height: u32,
+        format: BlockFormat,
+        levels: Vec<Vec<u8>>,
+        options: TextureOptions,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 || levels.is_empty() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Compressed texture has no image data",
+            ));
+        }
+        for (level, data) in levels.iter().enumerate() {
+            let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+            let expected = format.level_size(w, h).ok_or_else(|| {
+                Error::new(
+                    Status::InvalidArg,
+                    format!("A {w}x{h} {format:?} texture is too large"),
+                )
+            })?;
+            if data.len() != expected {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Mip level {level} has {} bytes, expected {expected} for {w}x{h} {format:?}",
+                        data.len(),
+                    ),
+                ));
+            }
+        }
+        Ok(CpuCompressedTexture {
+            name: options.name.clone().unwrap_or_else(|| "default".to_owned()),
+            width,
+            height,
+            format,
+            levels,
+            options,
+        })
+    }
+}
+
+#[napi]
+impl CpuCompressedTexture {
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.height
+    }
+
+    #[napi(getter)]
+    pub fn compression(&self) -> CompressionTextureType {
+        self.format.compression()
+    }
+
+    /// Whether the texels are sRGB encoded.
+    #[napi(getter)]
+    pub fn srgb(&self) -> bool {
+        self.format.is_srgb()
+    }
+
+    /// Number of mip levels stored in the file.
+    #[napi(getter)]
+    pub fn mip_levels(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    /// Decodes the first mip level on the CPU. Rows are returned in file order.
+    #[napi]
+    pub fn decompress(&self) -> Result<CpuTexture> {
+        self.decode(false)
+    }
+}
+
+impl CpuCompressedTexture {
+    /// Decodes the first mip level, optionally flipping rows so that three-d's
+    /// own flip on upload restores file order.
+    fn decode(&self, flip: bool) -> Result<CpuTexture> {
+        let mut image =
+            crate::io::decompress(self.format, self.width, self.height, &self.levels[0])?;
+        if flip {
+            image.flip_rows();
+        }
+        let options = TextureOptions {
+            name: Some(self.name.clone()),
+            ..self.options.clone()
+        };
+        CpuTexture::from_data(
+            self.width,
+            self.height,
+            image.into_texture_data(false),
+            options,
+        )
+    }
+}
+
+/// A natively compressed GL texture. three-d cannot wrap these, so the
+/// handle is owned and deleted here.
+pub(crate) struct NativeCompressedTexture {
+    pub context: three_d::Context,
+    pub id: three_d::context::Texture,
+}
+
+impl Drop for NativeCompressedTexture {
+    fn drop(&mut self) {
+        // SAFETY: the texture was created by this context and is no longer referenced.
+        unsafe { self.context.delete_texture(self.id) }
+    }
+}
+
+/// GPU storage of a [`CompressedTexture2D`].
+#[derive(Clone)]
+pub(crate) enum CompressedStorage {
+    Native(Arc<NativeCompressedTexture>),
+    Decompressed(Arc<three_d::Texture2D>),
+}
+
+/// A 2D texture uploaded from a [`CpuCompressedTexture`].
+///
+/// The blocks are uploaded as is when the context supports the format, and
+/// decoded on the CPU into an uncompressed texture otherwise. Both paths
+/// sample identically.
+#[napi]
+pub struct CompressedTexture2D {
+    pub(crate) storage: CompressedStorage,
+    context: three_d::Context,
+    id: three_d::context::Texture,
+    width: u32,
+    height: u32,
+    format: BlockFormat,
+}
+
+fn gl_filter(filter: Interpolation) -> i32 {
+    match filter {
+        Interpolation::Nearest => three_d::context::NEAREST as i32,
+        _ => three_d::context::LINEAR as i32,
+    }
+}
+
+fn gl_wrap(wrap: Wrapping) -> i32 {
+    (match wrap {
+        Wrapping::Repeat => three_d::context::REPEAT,
+        Wrapping::MirroredRepeat => three_d::context::MIRRORED_REPEAT,
+        Wrapping::ClampToEdge => three_d::context::CLAMP_TO_EDGE,
+    }) as i32
+}
+
+#[napi]
+impl CompressedTexture2D {
+    /// Uploads a compressed texture. Pass `decompress` to force CPU decoding
+    /// even when the context supports the format.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        texture: &CpuCompressedTexture,
+        decompress: Option<bool>,
+    ) -> Result<Self> {
+        let ctx = &context.inner;
+        let native = !decompress.unwrap_or(false)
+            && supports_compression(ctx, &texture.format.compression());
+        let storage = if native {
+            CompressedStorage::Native(Arc::new(upload(ctx, texture)?))
+        } else {
+            let cpu = texture.decode(true)?;
+            CompressedStorage::Decompressed(Arc::new(three_d::Texture2D::new(ctx, &cpu.inner)))
+        };
+        let id = match &storage {
+            CompressedStorage::Native(native) => native.id,
+            CompressedStorage::Decompressed(_) => {
+                bound_texture(ctx, three_d::context::TEXTURE_BINDING_2D)?
+            }
+        };
+        Ok(CompressedTexture2D {
+            storage,
+            context: ctx.clone(),
+            id,
+            width: texture.width,
+            height: texture.height,
+            format: texture.format,
+        })
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.height
+    }
+
+    #[napi(getter)]
+    pub fn compression(&self) -> CompressionTextureType {
+        self.format.compression()
+    }
+
+    /// Whether the texture is stored compressed on the GPU rather than decoded on the CPU.
+    #[napi(getter)]
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.storage, CompressedStorage::Native(_))
+    }
+
+    /// Reads back mip level 0 as RGBA, rows in file order. Values are bytes,
+    /// or floats for HDR and signed formats. Needs a desktop OpenGL context,
+    /// as OpenGL ES cannot read textures back directly.
+    #[napi]
+    pub fn read_pixels(&self) -> Result<PixelData> {
+        use three_d::context::PixelPackData;
+        if self.context.version().is_embedded {
+            return Err(Error::new(
+                Status::GenericFailure,
+                "Reading compressed textures back needs a desktop OpenGL context",
+            ));
+        }
+        let component = self.format.component();
+        let size = match component {
+            Component::U8 => 1,
+            _ => 4,
+        };
+        let mut bytes = vec![0u8; self.width as usize * self.height as usize * 4 * size];
+        let data_type = match component {
+            Component::U8 => three_d::context::UNSIGNED_BYTE,
+            _ => three_d::context::FLOAT,
+        };
+        // SAFETY: the buffer holds exactly one RGBA image of mip level 0.
+        unsafe {
+            self.context
+                .bind_texture(three_d::context::TEXTURE_2D, Some(self.id));
+            self.context
+                .pixel_store_i32(three_d::context::PACK_ALIGNMENT, 1);
+            self.context.get_tex_image(
+                three_d::context::TEXTURE_2D,
+                0,
+                three_d::context::RGBA,
+                data_type,
+                PixelPackData::Slice(&mut bytes),
+            );
+        }
+        Ok(pixel_data(component, &bytes))
+    }
+}
+
+/// Uploads every mip level of a compressed texture as is.
+fn upload(
+    context: &three_d::Context,
+    texture: &CpuCompressedTexture,
+) -> Result<NativeCompressedTexture> {
+    let sampling = texture.options.sampling()?;
+    let target = three_d::context::TEXTURE_2D;
+    // SAFETY: every level was validated against the format's block size when
+    // the CPU texture was created.
+    unsafe {
+        let id = context
+            .create_texture()
+            .map_err(|e| Error::new(Status::GenericFailure, e))?;
+        let native = NativeCompressedTexture {
+            context: context.clone(),
+            id,
+        };
+        context.bind_texture(target, Some(id));
+        for (level, data) in texture.levels.iter().enumerate() {
+            context.compressed_tex_image_2d(
+                target,
+                level as i32,
+                texture.format.gl_format() as i32,
+                (texture.width >> level).max(1) as i32,
+                (texture.height >> level).max(1) as i32,
+                0,
+                data.len() as i32,
+                data,
+            );
+        }
+        let levels = texture.levels.len() as i32;
+        let min_filter = match (sampling.mipmap, levels > 1) {
+            (Some(mipmap), true) => {
+                (match (sampling.min_filter, mipmap.filter) {
+                    (Interpolation::Nearest, Interpolation::Nearest) => {
+                        three_d::context::NEAREST_MIPMAP_NEAREST
+                    }
+                    (Interpolation::Nearest, _) => three_d::context::NEAREST_MIPMAP_LINEAR,
+                    (_, Interpolation::Nearest) => three_d::context::LINEAR_MIPMAP_NEAREST,
+                    _ => three_d::context::LINEAR_MIPMAP_LINEAR,
+                }) as i32
+            }
+            _ => gl_filter(sampling.min_filter),
+        };
+        context.tex_parameter_i32(target, three_d::context::TEXTURE_MIN_FILTER, min_filter);
+        context.tex_parameter_i32(
+            target,
+            three_d::context::TEXTURE_MAG_FILTER,
+            gl_filter(sampling.mag_filter),
+        );
+        context.tex_parameter_i32(
+            target,
+            three_d::context::TEXTURE_WRAP_S,
+            gl_wrap(sampling.wrap_s),
+        );
+        context.tex_parameter_i32(
+            target,
+            three_d::context::TEXTURE_WRAP_T,
+            gl_wrap(sampling.wrap_t),
+        );
+        context.tex_parameter_i32(target, three_d::context::TEXTURE_MAX_LEVEL, levels - 1);
+        let error = context.get_error();
+        if error != three_d::context::NO_ERROR {
+            return Err(Error::new(
+                Status::GenericFailure,
+                format!(
+                    "Failed to upload {:?} texture (GL error {error:#x})",
+                    texture.format
+                ),
+            ));
+        }
+        Ok(native)
+    }
+}
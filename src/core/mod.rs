@@ -1,2 +1,6 @@
 pub mod buffer;
+pub mod compressed;
 pub mod render_states;
+pub mod render_target;
+pub mod texture;
+pub mod viewport;
@@ -0,0 +1,1156 @@
+//! Render targets drawing into user owned textures.
+
+use crate::context::Context;
+use crate::core::texture::{
+    color_format, pixel_data, read_depth_image, transfer_format, Component, DepthImage,
+    DepthTexture2D, DepthTexture2DArray, DepthTextureCubeMap, PixelData, Texture2D, Texture2DArray,
+    TextureCubeMap,
+};
+use crate::enums::{CubeMapSide, FramebufferAttachment, TextureFormat};
+use crate::renderer::effect::ScreenEffect;
+use crate::renderer::material::{CustomMaterial, SCREEN_VERTEX_SHADER};
+use crate::renderer::Camera;
+use crate::types::{TextureHeight, TextureWidth};
+use napi::bindgen_prelude::{Either, Float32Array};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Values a render target is cleared to. Channels that are left out keep
+/// their current content.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct ClearState {
+    pub red: Option<f64>,
+    pub green: Option<f64>,
+    pub blue: Option<f64>,
+    pub alpha: Option<f64>,
+    pub depth: Option<f64>,
+}
+
+impl ClearState {
+    fn inner(&self) -> three_d::ClearState {
+        let channel = |value: Option<f64>| value.map(|v| v as f32);
+        three_d::ClearState {
+            red: channel(self.red),
+            green: channel(self.green),
+            blue: channel(self.blue),
+            alpha: channel(self.alpha),
+            depth: channel(self.depth),
+        }
+    }
+}
+
+/// The images of a color texture a [`ColorTarget`] renders into.
+#[derive(Clone)]
+enum ColorImages {
+    /// A 2D texture or a cube map face, given by its image target.
+    Image(u32),
+    /// Layers of a 2D array texture, one color attachment each.
+    Layers(Vec<u32>),
+}
+
+/// One or more images of a color texture to render into.
+/// Rendering regenerates the texture's mip maps.
+#[napi]
+#[derive(Clone)]
+pub struct ColorTarget {
+    // Keeps the texture alive and marks it as in use.
+    _texture: Arc<dyn Any>,
+    id: three_d::context::Texture,
+    target: u32,
+    images: ColorImages,
+    mip_maps: u32,
+    width: TextureWidth,
+    height: TextureHeight,
+    format: TextureFormat,
+}
+
+#[napi]
+impl ColorTarget {
+    /// Renders into a 2D texture.
+    #[napi(factory)]
+    pub fn from_texture(texture: &Texture2D) -> Result<Self> {
+        color_format(&texture.format)?;
+        Ok(ColorTarget {
+            _texture: texture.inner.clone(),
+            id: texture.id,
+            target: three_d::context::TEXTURE_2D,
+            images: ColorImages::Image(three_d::context::TEXTURE_2D),
+            mip_maps: texture.inner.number_of_mip_maps(),
+            width: texture.width(),
+            height: texture.height(),
+            format: texture.format(),
+        })
+    }
+
+    /// Renders into layers of a texture array. Layer `layers[i]` is written by
+    /// fragment shader output `i`.
+    #[napi(factory)]
+    pub fn from_texture_array(texture: &Texture2DArray, layers: Vec<u32>) -> Result<Self> {
+        if layers.is_empty() || layers.iter().any(|&layer| layer >= texture.layers()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Expected one or more layers below {}, got {layers:?}",
+                    texture.layers()
+                ),
+            ));
+        }
+        Ok(ColorTarget {
+            _texture: texture.inner.clone(),
+            id: texture.id,
+            target: three_d::context::TEXTURE_2D_ARRAY,
+            images: ColorImages::Layers(layers),
+            mip_maps: texture.inner.number_of_mip_maps(),
+            width: texture.width(),
+            height: texture.height(),
+            format: texture.format(),
+        })
+    }
+
+    /// Renders into one face of a cube map.
+    #[napi(factory)]
+    pub fn from_cube_map(texture: &TextureCubeMap, side: CubeMapSide) -> Self {
+        ColorTarget {
+            _texture: texture.inner.clone(),
+            id: texture.id,
+            target: three_d::context::TEXTURE_CUBE_MAP,
+            images: ColorImages::Image(side as u32),
+            mip_maps: texture.inner.number_of_mip_maps(),
+            width: texture.width(),
+            height: texture.height(),
+            format: texture.format(),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.height
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// The number of color attachments, one per layer for texture arrays.
+    #[napi(getter)]
+    pub fn count(&self) -> u32 {
+        match &self.images {
+            ColorImages::Image(_) => 1,
+            ColorImages::Layers(layers) => layers.len() as u32,
+        }
+    }
+}
+
+/// A depth texture image to render into.
+#[napi]
+#[derive(Clone)]
+pub struct DepthTarget {
+    // Keeps the texture alive and marks it as in use.
+    _texture: Arc<dyn Any>,
+    id: three_d::context::Texture,
+    image: DepthImage,
+    width: TextureWidth,
+    height: TextureHeight,
+    format: TextureFormat,
+}
+
+#[napi]
+impl DepthTarget {
+    /// Renders into a 2D depth texture.
+    #[napi(factory)]
+    pub fn from_texture(texture: &DepthTexture2D) -> Self {
+        DepthTarget {
+            _texture: texture.inner.clone(),
+            id: texture.id,
+            image: DepthImage::Image(three_d::context::TEXTURE_2D),
+            width: texture.width(),
+            height: texture.height(),
+            format: texture.format(),
+        }
+    }
+
+    /// Renders into one layer of a depth texture array.
+    #[napi(factory)]
+    pub fn from_texture_array(texture: &DepthTexture2DArray, layer: u32) -> Result<Self> {
+        if layer >= texture.layers() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Layer {layer} is out of range for {} layers",
+                    texture.layers()
+                ),
+            ));
+        }
+        Ok(DepthTarget {
+            _texture: texture.inner.clone(),
+            id: texture.id,
+            image: DepthImage::Layer(layer),
+            width: texture.width(),
+            height: texture.height(),
+            format: texture.format(),
+        })
+    }
+
+    /// Renders into one face of a depth cube map.
+    #[napi(factory)]
+    pub fn from_cube_map(texture: &DepthTextureCubeMap, side: CubeMapSide) -> Self {
+        DepthTarget {
+            _texture: texture.inner.clone(),
+            id: texture.id,
+            image: DepthImage::Image(side as u32),
+            width: texture.width(),
+            height: texture.height(),
+            format: texture.format(),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.height
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+}
+
+/// An owned GL framebuffer with fixed attachments, drawn to through three-d.
+struct Framebuffer {
+    context: three_d::Context,
+    id: three_d::context::Framebuffer,
+    width: u32,
+    height: u32,
+}
+
+impl Framebuffer {
+    /// Creates a framebuffer with `color_attachments` draw buffers. `attach`
+    /// attaches the images while the framebuffer is bound to `FRAMEBUFFER`.
+    fn new(
+        context: &three_d::Context,
+        width: u32,
+        height: u32,
+        color_attachments: u32,
+        attach: impl FnOnce(&three_d::Context),
+    ) -> Result<Self> {
+        use three_d::context::HasContext;
+        // SAFETY: the framebuffer is owned by the returned value and deleted on
+        // drop or failure; `attach` only attaches images to the bound framebuffer.
+        unsafe {
+            let id = context
+                .create_framebuffer()
+                .map_err(|e| Error::new(Status::GenericFailure, e))?;
+            context.bind_framebuffer(three_d::context::FRAMEBUFFER, Some(id));
+            attach(context);
+            let mut draw_buffers: Vec<u32> = (0..color_attachments)
+                .map(|i| three_d::context::COLOR_ATTACHMENT0 + i)
+                .collect();
+            if draw_buffers.is_empty() {
+                draw_buffers.push(three_d::context::NONE);
+            }
+            context.draw_buffers(&draw_buffers);
+            context.read_buffer(draw_buffers[0]);
+            let status = context.check_framebuffer_status(three_d::context::FRAMEBUFFER);
+            context.bind_framebuffer(three_d::context::FRAMEBUFFER, None);
+            if status != three_d::context::FRAMEBUFFER_COMPLETE {
+                context.delete_framebuffer(id);
+                return Err(Error::new(
+                    Status::GenericFailure,
+                    format!("Render target is incomplete (status {status:#x})"),
+                ));
+            }
+            Ok(Framebuffer {
+                context: context.clone(),
+                id,
+                width,
+                height,
+            })
+        }
+    }
+
+    /// Runs `f` with a three-d render target for this framebuffer.
+    fn write(&self, f: impl FnOnce(&three_d::RenderTarget)) {
+        let target = three_d::RenderTarget::from_framebuffer(
+            &self.context,
+            self.width,
+            self.height,
+            self.id,
+        );
+        f(&target);
+        // The framebuffer is owned by `self`, so keep three-d from deleting it.
+        target.into_framebuffer();
+    }
+
+    /// Clears the framebuffer. Without a state, color is cleared to
+    /// transparent black and depth to 1.
+    fn clear(&self, state: Option<ClearState>) {
+        let state = state.unwrap_or(ClearState {
+            red: Some(0.0),
+            green: Some(0.0),
+            blue: Some(0.0),
+            alpha: Some(0.0),
+            depth: Some(1.0),
+        });
+        self.write(|target| {
+            target.clear(state.inner());
+        });
+    }
+
+    fn apply_screen_material(
+        &self,
+        material: &CustomMaterial,
+        camera: Option<&Camera>,
+    ) -> Result<()> {
+        let mut state = material.inner.borrow_mut();
+        state.check_screen_program(&self.context)?;
+        let mut camera = camera
+            .map(|camera| camera.inner.clone())
+            .unwrap_or_else(|| Camera::default().inner);
+        camera.set_viewport(three_d::Viewport::new_at_origo(self.width, self.height));
+        self.write(|target| {
+            target.apply_screen_material(&*state, &camera, &[]);
+        });
+        Ok(())
+    }
+
+    fn apply_screen_effect(
+        &self,
+        effect: &ScreenEffect,
+        color_texture: Option<&Texture2D>,
+        depth_texture: Option<&DepthTexture2D>,
+        camera: Option<&Camera>,
+    ) -> Result<()> {
+        let color_texture = color_texture.map(|t| three_d::ColorTexture::Single(&t.inner));
+        let depth_texture = depth_texture.map(|t| three_d::DepthTexture::Single(&t.inner));
+        let mut state = effect.inner.borrow_mut();
+        state.check(
+            &self.context,
+            &[SCREEN_VERTEX_SHADER.to_owned()],
+            color_texture,
+            depth_texture,
+        )?;
+        let effect = state.prepare(color_texture, depth_texture, true)?;
+        let mut camera = camera
+            .map(|camera| camera.inner.clone())
+            .unwrap_or_else(|| Camera::default().inner);
+        camera.set_viewport(three_d::Viewport::new_at_origo(self.width, self.height));
+        self.write(|target| {
+            target.apply_screen_effect(&effect, &camera, &[], color_texture, depth_texture);
+        });
+        Ok(())
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        use three_d::context::HasContext;
+        // SAFETY: the framebuffer was created by `Framebuffer::new`.
+        unsafe { self.context.delete_framebuffer(self.id) };
+    }
+}
+
+/// A framebuffer combining an optional color target and an optional depth
+/// target of the same size. The textures stay in use until the render target
+/// is garbage collected.
+#[napi]
+pub struct RenderTarget {
+    framebuffer: Framebuffer,
+    color: Option<ColorTarget>,
+    depth: Option<DepthTarget>,
+}
+
+#[napi]
+impl RenderTarget {
+    /// Creates a render target from at least one of a color and a depth target.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        color: Option<&ColorTarget>,
+        depth: Option<&DepthTarget>,
+    ) -> Result<Self> {
+        use three_d::context::HasContext;
+        let (width, height) = match (color, depth) {
+            (None, None) => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    "Expected a color target, a depth target or both",
+                ))
+            }
+            (Some(color), Some(depth))
+                if color.width != depth.width || color.height != depth.height =>
+            {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Color target is {}x{} but depth target is {}x{}",
+                        color.width, color.height, depth.width, depth.height
+                    ),
+                ))
+            }
+            (Some(color), _) => (color.width, color.height),
+            (None, Some(depth)) => (depth.width, depth.height),
+        };
+        let color_attachments = color.map_or(0, |color| color.count());
+        // SAFETY: all attached textures are kept alive by the color and depth targets.
+        let framebuffer = Framebuffer::new(
+            &context.inner,
+            width,
+            height,
+            color_attachments,
+            |context| unsafe {
+                if let Some(color) = color {
+                    let attachment = three_d::context::COLOR_ATTACHMENT0;
+                    match &color.images {
+                        ColorImages::Image(target) => context.framebuffer_texture_2d(
+                            three_d::context::FRAMEBUFFER,
+                            attachment,
+                            *target,
+                            Some(color.id),
+                            0,
+                        ),
+                        ColorImages::Layers(layers) => {
+                            for (i, &layer) in layers.iter().enumerate() {
+                                context.framebuffer_texture_layer(
+                                    three_d::context::FRAMEBUFFER,
+                                    attachment + i as u32,
+                                    Some(color.id),
+                                    0,
+                                    layer as i32,
+                                );
+                            }
+                        }
+                    }
+                }
+                if let Some(depth) = depth {
+                    match depth.image {
+                        DepthImage::Image(target) => context.framebuffer_texture_2d(
+                            three_d::context::FRAMEBUFFER,
+                            three_d::context::DEPTH_ATTACHMENT,
+                            target,
+                            Some(depth.id),
+                            0,
+                        ),
+                        DepthImage::Layer(layer) => context.framebuffer_texture_layer(
+                            three_d::context::FRAMEBUFFER,
+                            three_d::context::DEPTH_ATTACHMENT,
+                            Some(depth.id),
+                            0,
+                            layer as i32,
+                        ),
+                    }
+                }
+            },
+        )?;
+        Ok(RenderTarget {
+            framebuffer,
+            color: color.cloned(),
+            depth: depth.cloned(),
+        })
+    }
+
+    /// Clears the render target. Without a state, color is cleared to
+    /// transparent black and depth to 1.
+    #[napi]
+    pub fn clear(&self, state: Option<ClearState>) {
+        self.framebuffer.clear(state);
+        self.update_mip_maps();
+    }
+
+    /// Runs a custom material over every pixel of the render target, reading
+    /// `in vec2 uvs;` in the fragment shader. The camera's viewport is replaced
+    /// by the size of the render target.
+    #[napi]
+    pub fn apply_screen_material(
+        &self,
+        material: &CustomMaterial,
+        camera: Option<&Camera>,
+    ) -> Result<()> {
+        self.framebuffer.apply_screen_material(material, camera)?;
+        self.update_mip_maps();
+        Ok(())
+    }
+
+    /// Runs a screen effect over every pixel of the render target. The effect
+    /// can sample `colorTexture` and `depthTexture`, which must not be
+    /// attached to this render target. The depth of the render target is
+    /// left unchanged and the camera's viewport is replaced by its size.
+    #[napi]
+    pub fn apply_screen_effect(
+        &self,
+        effect: &ScreenEffect,
+        color_texture: Option<&Texture2D>,
+        depth_texture: Option<&DepthTexture2D>,
+        camera: Option<&Camera>,
+    ) -> Result<()> {
+        self.check_not_attached(color_texture, depth_texture)?;
+        self.framebuffer
+            .apply_screen_effect(effect, color_texture, depth_texture, camera)?;
+        self.update_mip_maps();
+        Ok(())
+    }
+
+    pub(crate) fn context(&self) -> &three_d::Context {
+        &self.framebuffer.context
+    }
+
+    /// Fails if an effect input is attached to this render target, as
+    /// sampling a texture while drawing into it is a GL feedback loop.
+    pub(crate) fn check_not_attached(
+        &self,
+        color_texture: Option<&Texture2D>,
+        depth_texture: Option<&DepthTexture2D>,
+    ) -> Result<()> {
+        let ids = [
+            self.color.as_ref().map(|color| color.id),
+            self.depth.as_ref().map(|depth| depth.id),
+        ];
+        let inputs = [
+            ("color", color_texture.map(|t| t.id)),
+            ("depth", depth_texture.map(|t| t.id)),
+        ];
+        for (kind, id) in inputs {
+            if id.is_some() && ids.contains(&id) {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "The {kind} texture is attached to the render target and cannot be sampled while drawing into it"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `f` with a three-d render target drawing into this one.
+    pub(crate) fn write(&self, f: impl FnOnce(&three_d::RenderTarget)) {
+        self.framebuffer.write(f);
+        self.update_mip_maps();
+    }
+
+    /// Reads the color attachment `index` (0 by default), top row first.
+    #[napi]
+    pub fn read_color(&self, index: Option<u32>) -> Result<PixelData> {
+        use three_d::context::{HasContext, PixelPackData};
+        let color = self.color.as_ref().ok_or_else(|| {
+            Error::new(Status::GenericFailure, "Render target has no color target")
+        })?;
+        let index = index.unwrap_or(0);
+        if index >= color.count() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Color attachment {index} is out of range for {} attachments",
+                    color.count()
+                ),
+            ));
+        }
+        let (channels, component) = color_format(&color.format)?;
+        let (pixel_format, data_type) = transfer_format(channels, component);
+        let size = match component {
+            Component::U8 => 1,
+            Component::F16 => 2,
+            Component::F32 => 4,
+        };
+        let Framebuffer {
+            context,
+            id,
+            width,
+            height,
+        } = &self.framebuffer;
+        let row = *width as usize * channels * size;
+        let mut bytes = vec![0u8; row * *height as usize];
+        // SAFETY: the buffer is sized for the whole attachment with the given
+        // format and pixel type, and rows are tightly packed.
+        unsafe {
+            context.bind_framebuffer(three_d::context::READ_FRAMEBUFFER, Some(*id));
+            context.read_buffer(three_d::context::COLOR_ATTACHMENT0 + index);
+            context.pixel_store_i32(three_d::context::PACK_ALIGNMENT, 1);
+            context.read_pixels(
+                0,
+                0,
+                *width as i32,
+                *height as i32,
+                pixel_format,
+                data_type,
+                PixelPackData::Slice(&mut bytes),
+            );
+            context.bind_framebuffer(three_d::context::READ_FRAMEBUFFER, None);
+        }
+        let flipped: Vec<u8> = bytes.chunks_exact(row).rev().flatten().copied().collect();
+        Ok(pixel_data(component, &flipped))
+    }
+
+    /// Reads the depth target, top row first. Returns raw depth in `[0, 1]`, or
+    /// the linear distance from `camera` along its view direction if given.
+    #[napi]
+    pub fn read_depth(&self, camera: Option<&Camera>) -> Result<Float32Array> {
+        let depth = self.depth.as_ref().ok_or_else(|| {
+            Error::new(Status::GenericFailure, "Render target has no depth target")
+        })?;
+        read_depth_image(
+            &self.framebuffer.context,
+            depth.id,
+            depth.image,
+            self.width(),
+            self.height(),
+            camera,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.framebuffer.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.framebuffer.height
+    }
+
+    /// The attachments of the framebuffer, color attachments first.
+    #[napi(getter)]
+    pub fn attachments(&self) -> Vec<FramebufferAttachment> {
+        let colors = self.color.as_ref().map_or(0, |color| color.count());
+        (0..colors)
+            .map(|i| FramebufferAttachment::Color(Some(i)))
+            .chain(self.depth.as_ref().map(|_| FramebufferAttachment::Depth))
+            .collect()
+    }
+}
+
+impl RenderTarget {
+    fn update_mip_maps(&self) {
+        if let Some(color) = self.color.as_ref().filter(|color| color.mip_maps > 1) {
+            use three_d::context::HasContext;
+            let context = &self.framebuffer.context;
+            // SAFETY: the texture is kept alive by the color target.
+            unsafe {
+                context.bind_texture(color.target, Some(color.id));
+                context.generate_mipmap(color.target);
+            }
+        }
+    }
+}
+
+/// Returns the sized internal format of a color format.
+fn sized_color_format(format: &TextureFormat) -> Result<u32> {
+    use three_d::context::*;
+    Ok(match color_format(format)? {
+        (1, Component::U8) => R8,
+        (2, Component::U8) => RG8,
+        (3, Component::U8) => RGB8,
+        (4, Component::U8) => RGBA8,
+        (1, Component::F16) => R16F,
+        (2, Component::F16) => RG16F,
+        (3, Component::F16) => RGB16F,
+        (4, Component::F16) => RGBA16F,
+        (1, Component::F32) => R32F,
+        (2, Component::F32) => RG32F,
+        (3, Component::F32) => RGB32F,
+        _ => RGBA32F,
+    })
+}
+
+/// A multisampled renderbuffer, deleted when the last target using it is dropped.
+struct Renderbuffer {
+    context: three_d::Context,
+    id: three_d::context::Renderbuffer,
+    samples: u32,
+}
+
+impl Renderbuffer {
+    /// Allocates a renderbuffer with `samples` clamped to `[1, MAX_SAMPLES]`.
+    fn new(
+        context: &three_d::Context,
+        width: u32,
+        height: u32,
+        samples: u32,
+        internal_format: u32,
+    ) -> Result<Self> {
+        use three_d::context::HasContext;
+        if width == 0 || height == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Render targets must be at least 1x1",
+            ));
+        }
+        // SAFETY: the renderbuffer is owned by the returned value and deleted on drop.
+        unsafe {
+            let max = context
+                .get_parameter_i32(three_d::context::MAX_SAMPLES)
+                .max(1) as u32;
+            let samples = samples.clamp(1, max);
+            let id = context
+                .create_renderbuffer()
+                .map_err(|e| Error::new(Status::GenericFailure, e))?;
+            context.bind_renderbuffer(three_d::context::RENDERBUFFER, Some(id));
+            context.renderbuffer_storage_multisample(
+                three_d::context::RENDERBUFFER,
+                samples as i32,
+                internal_format,
+                width as i32,
+                height as i32,
+            );
+            context.bind_renderbuffer(three_d::context::RENDERBUFFER, None);
+            Ok(Renderbuffer {
+                context: context.clone(),
+                id,
+                samples,
+            })
+        }
+    }
+}
+
+impl Drop for Renderbuffer {
+    fn drop(&mut self) {
+        use three_d::context::HasContext;
+        // SAFETY: the renderbuffer was created by `Renderbuffer::new`.
+        unsafe { self.context.delete_renderbuffer(self.id) };
+    }
+}
+
+/// A multisampled color buffer. Render into it with a
+/// [`RenderTargetMultisample`] and resolve it into a texture.
+#[napi]
+#[derive(Clone)]
+pub struct ColorTargetMultisample {
+    renderbuffer: Arc<Renderbuffer>,
+    width: TextureWidth,
+    height: TextureHeight,
+    format: TextureFormat,
+}
+
+#[napi]
+impl ColorTargetMultisample {
+    /// Allocates a color buffer, `R8G8B8A8` by default. The sample count is
+    /// clamped to the context's maximum.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        width: TextureWidth,
+        height: TextureHeight,
+        samples: u32,
+        format: Option<TextureFormat>,
+    ) -> Result<Self> {
+        let format = format.unwrap_or(TextureFormat::R8G8B8A8);
+        let internal_format = sized_color_format(&format)?;
+        Ok(ColorTargetMultisample {
+            renderbuffer: Arc::new(Renderbuffer::new(
+                &context.inner,
+                width,
+                height,
+                samples,
+                internal_format,
+            )?),
+            width,
+            height,
+            format,
+        })
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.height
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// The number of samples per pixel after clamping.
+    #[napi(getter)]
+    pub fn samples(&self) -> u32 {
+        self.renderbuffer.samples
+    }
+}
+
+/// A multisampled depth buffer. Render into it with a
+/// [`RenderTargetMultisample`] and resolve it into a depth texture.
+#[napi]
+#[derive(Clone)]
+pub struct DepthTargetMultisample {
+    renderbuffer: Arc<Renderbuffer>,
+    width: TextureWidth,
+    height: TextureHeight,
+    format: TextureFormat,
+}
+
+#[napi]
+impl DepthTargetMultisample {
+    /// Allocates a depth buffer, `Depth32F` by default. The sample count is
+    /// clamped to the context's maximum.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        width: TextureWidth,
+        height: TextureHeight,
+        samples: u32,
+        format: Option<TextureFormat>,
+    ) -> Result<Self> {
+        let format = format.unwrap_or(TextureFormat::Depth32F);
+        if !matches!(
+            format,
+            TextureFormat::Depth16 | TextureFormat::Depth24 | TextureFormat::Depth32F
+        ) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("{format:?} is not a depth format, expected Depth16, Depth24 or Depth32F"),
+            ));
+        }
+        Ok(DepthTargetMultisample {
+            renderbuffer: Arc::new(Renderbuffer::new(
+                &context.inner,
+                width,
+                height,
+                samples,
+                format.clone() as u32,
+            )?),
+            width,
+            height,
+            format,
+        })
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.height
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// The number of samples per pixel after clamping.
+    #[napi(getter)]
+    pub fn samples(&self) -> u32 {
+        self.renderbuffer.samples
+    }
+}
+
+/// A multisampled framebuffer combining an optional color target and an
+/// optional depth target of the same size and sample count. Draw into it with
+/// `renderWithMaterial` or `renderWithEffect`; its content is only readable
+/// after resolving it into a [`RenderTarget`].
+#[napi]
+pub struct RenderTargetMultisample {
+    framebuffer: Framebuffer,
+    color: Option<ColorTargetMultisample>,
+    depth: Option<DepthTargetMultisample>,
+}
+
+#[napi]
+impl RenderTargetMultisample {
+    /// Creates a render target from at least one of a color and a depth target.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        color: Option<&ColorTargetMultisample>,
+        depth: Option<&DepthTargetMultisample>,
+    ) -> Result<Self> {
+        use three_d::context::HasContext;
+        let (width, height) = match (color, depth) {
+            (None, None) => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    "Expected a color target, a depth target or both",
+                ))
+            }
+            (Some(color), Some(depth))
+                if color.width != depth.width || color.height != depth.height =>
+            {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Color target is {}x{} but depth target is {}x{}",
+                        color.width, color.height, depth.width, depth.height
+                    ),
+                ))
+            }
+            (Some(color), Some(depth)) if color.samples() != depth.samples() => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Color target has {} samples but depth target has {}",
+                        color.samples(),
+                        depth.samples()
+                    ),
+                ))
+            }
+            (Some(color), _) => (color.width, color.height),
+            (None, Some(depth)) => (depth.width, depth.height),
+        };
+        // SAFETY: the renderbuffers are kept alive by the color and depth targets.
+        let framebuffer = Framebuffer::new(
+            &context.inner,
+            width,
+            height,
+            color.is_some() as u32,
+            |context| unsafe {
+                if let Some(color) = color {
+                    context.framebuffer_renderbuffer(
+                        three_d::context::FRAMEBUFFER,
+                        three_d::context::COLOR_ATTACHMENT0,
+                        three_d::context::RENDERBUFFER,
+                        Some(color.renderbuffer.id),
+                    );
+                }
+                if let Some(depth) = depth {
+                    context.framebuffer_renderbuffer(
+                        three_d::context::FRAMEBUFFER,
+                        three_d::context::DEPTH_ATTACHMENT,
+                        three_d::context::RENDERBUFFER,
+                        Some(depth.renderbuffer.id),
+                    );
+                }
+            },
+        )?;
+        Ok(RenderTargetMultisample {
+            framebuffer,
+            color: color.cloned(),
+            depth: depth.cloned(),
+        })
+    }
+
+    /// Clears the render target. Without a state, color is cleared to
+    /// transparent black and depth to 1.
+    #[napi]
+    pub fn clear(&self, state: Option<ClearState>) {
+        self.framebuffer.clear(state);
+    }
+
+    /// Runs a custom material over every pixel of the render target, reading
+    /// `in vec2 uvs;` in the fragment shader. The camera's viewport is replaced
+    /// by the size of the render target.
+    #[napi]
+    pub fn apply_screen_material(
+        &self,
+        material: &CustomMaterial,
+        camera: Option<&Camera>,
+    ) -> Result<()> {
+        self.framebuffer.apply_screen_material(material, camera)
+    }
+
+    /// Runs a screen effect over every pixel of the render target. The effect
+    /// can sample `colorTexture` and `depthTexture`. The depth of the render
+    /// target is left unchanged and the camera's viewport is replaced by its
+    /// size.
+    #[napi]
+    pub fn apply_screen_effect(
+        &self,
+        effect: &ScreenEffect,
+        color_texture: Option<&Texture2D>,
+        depth_texture: Option<&DepthTexture2D>,
+        camera: Option<&Camera>,
+    ) -> Result<()> {
+        self.framebuffer
+            .apply_screen_effect(effect, color_texture, depth_texture, camera)
+    }
+
+    /// Averages the samples of the color target into the color texture of
+    /// `target` and copies the depth target into its depth texture, for each
+    /// target both render targets have. Both must have the same size and
+    /// color and depth formats.
+    #[napi]
+    pub fn resolve(&self, target: &RenderTarget) -> Result<()> {
+        use three_d::context::HasContext;
+        if self.width() != target.width() || self.height() != target.height() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Cannot resolve a {}x{} render target into a {}x{} one",
+                    self.width(),
+                    self.height(),
+                    target.width(),
+                    target.height()
+                ),
+            ));
+        }
+        let mut mask = 0;
+        if let (Some(color), Some(resolved)) = (&self.color, &target.color) {
+            if color_format(&color.format)? != color_format(&resolved.format)? {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Cannot resolve {:?} samples into a {:?} texture",
+                        color.format, resolved.format
+                    ),
+                ));
+            }
+            mask |= three_d::context::COLOR_BUFFER_BIT;
+        }
+        if let (Some(depth), Some(resolved)) = (&self.depth, &target.depth) {
+            if depth.format != resolved.format {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "Cannot resolve {:?} samples into a {:?} texture",
+                        depth.format, resolved.format
+                    ),
+                ));
+            }
+            mask |= three_d::context::DEPTH_BUFFER_BIT;
+        }
+        if mask == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "The render targets have no color or depth target in common",
+            ));
+        }
+        let context = &self.framebuffer.context;
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        // SAFETY: both framebuffers are complete and of the same size, and the
+        // formats of the blitted buffers were checked above.
+        unsafe {
+            context.disable(three_d::context::SCISSOR_TEST);
+            context.bind_framebuffer(
+                three_d::context::READ_FRAMEBUFFER,
+                Some(self.framebuffer.id),
+            );
+            context.bind_framebuffer(
+                three_d::context::DRAW_FRAMEBUFFER,
+                Some(target.framebuffer.id),
+            );
+            context.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                mask,
+                three_d::context::NEAREST,
+            );
+            context.bind_framebuffer(three_d::context::READ_FRAMEBUFFER, None);
+            context.bind_framebuffer(three_d::context::DRAW_FRAMEBUFFER, None);
+        }
+        target.update_mip_maps();
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.framebuffer.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.framebuffer.height
+    }
+
+    /// The number of samples per pixel.
+    #[napi(getter)]
+    pub fn samples(&self) -> u32 {
+        self.color
+            .as_ref()
+            .map(|color| color.samples())
+            .or_else(|| self.depth.as_ref().map(|depth| depth.samples()))
+            .unwrap_or(1)
+    }
+
+    pub(crate) fn context(&self) -> &three_d::Context {
+        &self.framebuffer.context
+    }
+
+    /// Runs `f` with a three-d render target drawing into this one.
+    pub(crate) fn write(&self, f: impl FnOnce(&three_d::RenderTarget)) {
+        self.framebuffer.write(f);
+    }
+
+    /// Averages the samples inside `scissor_box` into `target`, which must
+    /// have the same size and formats as this render target.
+    pub(crate) fn resolve_partially(
+        &self,
+        target: &three_d::RenderTarget,
+        scissor_box: three_d::ScissorBox,
+    ) -> Result<()> {
+        use three_d::context::HasContext;
+        let context = &self.framebuffer.context;
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        let mut mask = 0;
+        if self.color.is_some() {
+            mask |= three_d::context::COLOR_BUFFER_BIT;
+        }
+        if self.depth.is_some() {
+            mask |= three_d::context::DEPTH_BUFFER_BIT;
+        }
+        // Writing binds `target` as the draw framebuffer and enables the
+        // scissor test, which limits the blit to the scissor box.
+        target
+            .write_partially::<three_d::RendererError>(scissor_box, || {
+                // SAFETY: both framebuffers are complete and of the same size
+                // and formats, as the caller guarantees.
+                unsafe {
+                    context.bind_framebuffer(
+                        three_d::context::READ_FRAMEBUFFER,
+                        Some(self.framebuffer.id),
+                    );
+                    context.blit_framebuffer(
+                        0,
+                        0,
+                        width,
+                        height,
+                        0,
+                        0,
+                        width,
+                        height,
+                        mask,
+                        three_d::context::NEAREST,
+                    );
+                    context.bind_framebuffer(three_d::context::READ_FRAMEBUFFER, None);
+                }
+                Ok(())
+            })
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// A render target or a multisampled one, for functions that draw into
+/// either. Draw into a multisampled target and resolve it into a
+/// [`RenderTarget`] for antialiased edges.
+pub type AnyRenderTarget<'a> = Either<&'a RenderTarget, &'a RenderTargetMultisample>;
+
+/// The context, width and height of a render target of either kind.
+pub(crate) fn target_size(target: &AnyRenderTarget) -> (three_d::Context, u32, u32) {
+    match target {
+        Either::A(target) => (target.context().clone(), target.width(), target.height()),
+        Either::B(target) => (target.context().clone(), target.width(), target.height()),
+    }
+}
+
+/// Runs `f` with a three-d render target drawing into a render target of
+/// either kind.
+pub(crate) fn write_target(target: &AnyRenderTarget, f: impl FnOnce(&three_d::RenderTarget)) {
+    match target {
+        Either::A(target) => target.write(f),
+        Either::B(target) => target.write(f),
+    }
+}
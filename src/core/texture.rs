@@ -0,0 +1,1503 @@
+use crate::context::Context;
+use crate::enums::{CubeMapSide, TextureFormat, TextureMagFilter, TextureMinFilter, TextureWrap};
+use crate::prelude::NF16;
+use crate::renderer::Camera;
+use crate::types::{TextureDepth, TextureHeight, TextureWidth};
+use napi::bindgen_prelude::{Either3, Either4, Float32Array, Generator, Uint16Array, Uint8Array};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::sync::Arc;
+use three_d::{f16, Interpolation, Mipmap, TextureData, Wrapping};
+
+/// Pixel data accepted by texture constructors and `fill`.
+/// `Uint16Array` holds raw IEEE half-float bit patterns.
+pub type TexturePixels = Either4<Uint8Array, Uint16Array, Float32Array, Vec<&'static NF16>>;
+
+/// Sampling options shared by all texture types.
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct TextureOptions {
+    /// Texture name, used for debugging.
+    pub name: Option<String>,
+    /// Minification filter. Defaults to `Linear` with mipmaps sampled
+    /// linearly, like `LinearMipmapLinear`.
+    pub min_filter: Option<TextureMinFilter>,
+    /// Magnification filter, defaults to `Linear`.
+    pub mag_filter: Option<TextureMagFilter>,
+    /// Wrapping along the horizontal axis, defaults to `Repeat`.
+    pub wrap_s: Option<TextureWrap>,
+    /// Wrapping along the vertical axis, defaults to `Repeat`.
+    pub wrap_t: Option<TextureWrap>,
+    /// Wrapping along the depth axis of 3D textures, defaults to `Repeat`.
+    pub wrap_r: Option<TextureWrap>,
+    /// Whether to generate mipmaps. Defaults to `true` if `minFilter` is
+    /// unset or one of the `*Mipmap*` filters and to `false` for `Nearest`
+    /// and `Linear`, which must not be combined with `true`.
+    pub mipmap: Option<bool>,
+}
+
+/// Sampler settings resolved from [`TextureOptions`] into three-d types.
+pub(crate) struct Sampling {
+    pub min_filter: Interpolation,
+    pub mag_filter: Interpolation,
+    pub mipmap: Option<Mipmap>,
+    pub wrap_s: Wrapping,
+    pub wrap_t: Wrapping,
+    pub wrap_r: Wrapping,
+}
+
+impl TextureOptions {
+    pub(crate) fn sampling(&self) -> Result<Sampling> {
+        let (min_filter, mipmap_filter) = match self.min_filter {
+            None | Some(TextureMinFilter::Linear) => (Interpolation::Linear, None),
+            Some(TextureMinFilter::Nearest) => (Interpolation::Nearest, None),
+            Some(TextureMinFilter::NearestMipmapNearest) => {
+                (Interpolation::Nearest, Some(Interpolation::Nearest))
+            }
+            Some(TextureMinFilter::LinearMipmapNearest) => {
+                (Interpolation::Linear, Some(Interpolation::Nearest))
+            }
+            Some(TextureMinFilter::NearestMipmapLinear) => {
+                (Interpolation::Nearest, Some(Interpolation::Linear))
+            }
+            Some(TextureMinFilter::LinearMipmapLinear) => {
+                (Interpolation::Linear, Some(Interpolation::Linear))
+            }
+        };
+        let mipmap = match (self.mipmap, &self.min_filter, mipmap_filter) {
+            (Some(false), None, _) | (None | Some(false), Some(_), None) => None,
+            (None | Some(true), None, _) => Some(Mipmap::default()),
+            (None | Some(true), Some(_), Some(filter)) => Some(Mipmap {
+                filter,
+                ..Default::default()
+            }),
+            (Some(mipmap), Some(min_filter), _) => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("mipmap: {mipmap} cannot be combined with the {min_filter:?} minification filter"),
+                ))
+            }
+        };
+        let mag_filter = match self.mag_filter {
+            None | Some(TextureMagFilter::Linear) => Interpolation::Linear,
+            Some(TextureMagFilter::Nearest) => Interpolation::Nearest,
+        };
+        Ok(Sampling {
+            min_filter,
+            mag_filter,
+            mipmap,
+            wrap_s: wrapping(self.wrap_s.as_ref())?,
+            wrap_t: wrapping(self.wrap_t.as_ref())?,
+            wrap_r: wrapping(self.wrap_r.as_ref())?,
+        })
+    }
+}
+
+pub(crate) fn wrapping(wrap: Option<&TextureWrap>) -> Result<Wrapping> {
+    match wrap {
+        None | Some(TextureWrap::Repeat) => Ok(Wrapping::Repeat),
+        Some(TextureWrap::MirroredRepeat) => Ok(Wrapping::MirroredRepeat),
+        Some(TextureWrap::ClampToEdge) => Ok(Wrapping::ClampToEdge),
+        Some(TextureWrap::ClampToBorder) => Err(Error::new(
+            Status::InvalidArg,
+            "ClampToBorder wrapping is not supported",
+        )),
+    }
+}
+
+/// Component type of a color texture format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Component {
+    U8,
+    F16,
+    F32,
+}
+
+/// Returns the number of channels and the component type of a color format,
+/// or an error for formats three-d cannot sample from.
+pub(crate) fn color_format(format: &TextureFormat) -> Result<(usize, Component)> {
+    Ok(match format {
+        TextureFormat::R8 => (1, Component::U8),
+        TextureFormat::R8G8 => (2, Component::U8),
+        TextureFormat::R8G8B8 => (3, Component::U8),
+        TextureFormat::R8G8B8A8 | TextureFormat::R8G8B8A8Unorm => (4, Component::U8),
+        TextureFormat::R16F => (1, Component::F16),
+        TextureFormat::R16F16I => (2, Component::F16),
+        TextureFormat::R16G16B16F => (3, Component::F16),
+        TextureFormat::R16G16B16A16F => (4, Component::F16),
+        TextureFormat::R32F => (1, Component::F32),
+        TextureFormat::R32F32I => (2, Component::F32),
+        TextureFormat::R32G32B32F => (3, Component::F32),
+        TextureFormat::R32G32B32A32F => (4, Component::F32),
+        _ => {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("{format:?} is not a supported color texture format"),
+            ))
+        }
+    })
+}
+
+/// Returns the texture format matching three-d texture data.
+pub(crate) fn format_of(data: &TextureData) -> TextureFormat {
+    match data {
+        TextureData::RU8(_) => TextureFormat::R8,
+        TextureData::RgU8(_) => TextureFormat::R8G8,
+        TextureData::RgbU8(_) => TextureFormat::R8G8B8,
+        TextureData::RgbaU8(_) => TextureFormat::R8G8B8A8,
+        TextureData::RF16(_) => TextureFormat::R16F,
+        TextureData::RgF16(_) => TextureFormat::R16F16I,
+        TextureData::RgbF16(_) => TextureFormat::R16G16B16F,
+        TextureData::RgbaF16(_) => TextureFormat::R16G16B16A16F,
+        TextureData::RF32(_) => TextureFormat::R32F,
+        TextureData::RgF32(_) => TextureFormat::R32F32I,
+        TextureData::RgbF32(_) => TextureFormat::R32G32B32F,
+        TextureData::RgbaF32(_) => TextureFormat::R32G32B32A32F,
+    }
+}
+
+/// Converts JS pixel data into three-d texture data of the given format,
+/// checking that it holds exactly `texels` texels.
+pub(crate) fn texture_data(
+    format: &TextureFormat,
+    pixels: &TexturePixels,
+    texels: usize,
+) -> Result<TextureData> {
+    let (channels, component) = color_format(format)?;
+    let len = match pixels {
+        Either4::A(data) => data.len(),
+        Either4::B(data) => data.len(),
+        Either4::C(data) => data.len(),
+        Either4::D(data) => data.len(),
+    };
+    if len != texels * channels {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "Expected {} values for {texels} texels of {format:?}, got {len}",
+                texels * channels
+            ),
+        ));
+    }
+    match (component, pixels) {
+        (Component::U8, Either4::A(data)) => Ok(u8_data(channels, data)),
+        (Component::F16, Either4::B(data)) => {
+            let data: Vec<f16> = data.iter().map(|&bits| f16::from_bits(bits)).collect();
+            Ok(f16_data(channels, &data))
+        }
+        (Component::F16, Either4::C(data)) => {
+            let data: Vec<f16> = data.iter().map(|&v| f16::from_f32(v)).collect();
+            Ok(f16_data(channels, &data))
+        }
+        (Component::F16, Either4::D(data)) => {
+            let data: Vec<f16> = data.iter().map(|&v| v.into()).collect();
+            Ok(f16_data(channels, &data))
+        }
+        (Component::F32, Either4::C(data)) => Ok(f32_data(channels, data)),
+        _ => Err(Error::new(
+            Status::InvalidArg,
+            format!("Pixel data type does not match texture format {format:?}"),
+        )),
+    }
+}
+
+pub(crate) fn u8_data(channels: usize, data: &[u8]) -> TextureData {
+    match channels {
+        1 => TextureData::RU8(data.to_vec()),
+        2 => TextureData::RgU8(texels(data)),
+        3 => TextureData::RgbU8(texels(data)),
+        _ => TextureData::RgbaU8(texels(data)),
+    }
+}
+
+pub(crate) fn f16_data(channels: usize, data: &[f16]) -> TextureData {
+    match channels {
+        1 => TextureData::RF16(data.to_vec()),
+        2 => TextureData::RgF16(texels(data)),
+        3 => TextureData::RgbF16(texels(data)),
+        _ => TextureData::RgbaF16(texels(data)),
+    }
+}
+
+pub(crate) fn f32_data(channels: usize, data: &[f32]) -> TextureData {
+    match channels {
+        1 => TextureData::RF32(data.to_vec()),
+        2 => TextureData::RgF32(texels(data)),
+        3 => TextureData::RgbF32(texels(data)),
+        _ => TextureData::RgbaF32(texels(data)),
+    }
+}
+
+fn texels<T: Copy + Default, const N: usize>(data: &[T]) -> Vec<[T; N]> {
+    data.chunks_exact(N)
+        .map(|chunk| {
+            let mut texel = [T::default(); N];
+            texel.copy_from_slice(chunk);
+            texel
+        })
+        .collect()
+}
+
+/// Returns texture data as tightly packed bytes in native byte order.
+pub(crate) fn texture_bytes(data: &TextureData) -> Vec<u8> {
+    fn bytes<T: Copy, const N: usize, const B: usize>(
+        data: &[[T; N]],
+        to_bytes: impl Fn(T) -> [u8; B],
+    ) -> Vec<u8> {
+        data.iter().flatten().flat_map(|&v| to_bytes(v)).collect()
+    }
+    let half = |v: f16| v.to_bits().to_ne_bytes();
+    match data {
+        TextureData::RU8(data) => data.clone(),
+        TextureData::RgU8(data) => data.iter().flatten().copied().collect(),
+        TextureData::RgbU8(data) => data.iter().flatten().copied().collect(),
+        TextureData::RgbaU8(data) => data.iter().flatten().copied().collect(),
+        TextureData::RF16(data) => data.iter().flat_map(|&v| half(v)).collect(),
+        TextureData::RgF16(data) => bytes(data, half),
+        TextureData::RgbF16(data) => bytes(data, half),
+        TextureData::RgbaF16(data) => bytes(data, half),
+        TextureData::RF32(data) => data.iter().flat_map(|v| v.to_ne_bytes()).collect(),
+        TextureData::RgF32(data) => bytes(data, f32::to_ne_bytes),
+        TextureData::RgbF32(data) => bytes(data, f32::to_ne_bytes),
+        TextureData::RgbaF32(data) => bytes(data, f32::to_ne_bytes),
+    }
+}
+
+/// Returns the GL pixel format and pixel type used to transfer a color format.
+pub(crate) fn transfer_format(channels: usize, component: Component) -> (u32, u32) {
+    let format = match channels {
+        1 => three_d::context::RED,
+        2 => three_d::context::RG,
+        3 => three_d::context::RGB,
+        _ => three_d::context::RGBA,
+    };
+    let data_type = match component {
+        Component::U8 => three_d::context::UNSIGNED_BYTE,
+        Component::F16 => three_d::context::HALF_FLOAT,
+        Component::F32 => three_d::context::FLOAT,
+    };
+    (format, data_type)
+}
+
+/// Returns the texture currently bound to `binding` on the active texture unit.
+///
+/// three-d does not expose texture handles, but every texture constructor leaves
+/// the new texture bound, so querying the binding right after construction
+/// yields its handle for sub-region uploads.
+pub(crate) fn bound_texture(
+    context: &three_d::Context,
+    binding: u32,
+) -> Result<three_d::context::Texture> {
+    use three_d::context::HasContext;
+    // SAFETY: plain state query on the current context.
+    unsafe { context.get_parameter_texture(binding) }
+        .ok_or_else(|| Error::new(Status::GenericFailure, "Failed to query texture handle"))
+}
+
+/// A region of a layered or 3D texture, in texels.
+pub(crate) struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+}
+
+/// Uploads pixel data into a region of a `TEXTURE_2D_ARRAY` or `TEXTURE_3D`
+/// and regenerates its mip maps.
+///
+/// For 2D arrays `region.y` counts from the top row, matching three-d which
+/// flips rows of 2D images on upload, and `region.z` is the first layer.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn upload_region(
+    context: &three_d::Context,
+    target: u32,
+    id: three_d::context::Texture,
+    size: (u32, u32, u32),
+    mip_maps: u32,
+    region: Region,
+    format: &TextureFormat,
+    pixels: &TexturePixels,
+) -> Result<()> {
+    use three_d::context::{HasContext, PixelUnpackData};
+    let (width, height, depth) = size;
+    let outside = |start: u32, size: u32, end: u32| {
+        size == 0 || start.checked_add(size).is_none_or(|last| last > end)
+    };
+    if outside(region.x, region.width, width)
+        || outside(region.y, region.height, height)
+        || outside(region.z, region.depth, depth)
+    {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("Region is outside of the {width}x{height}x{depth} texture"),
+        ));
+    }
+    let texels = region.width as usize * region.height as usize * region.depth as usize;
+    let mut bytes = texture_bytes(&texture_data(format, pixels, texels)?);
+    let (channels, component) = color_format(format)?;
+    let flip = target == three_d::context::TEXTURE_2D_ARRAY;
+    let y = if flip {
+        let row = bytes.len() / (region.height as usize * region.depth as usize);
+        for slice in bytes.chunks_exact_mut(row * region.height as usize) {
+            let rows: Vec<&[u8]> = slice.chunks_exact(row).rev().collect();
+            let flipped = rows.concat();
+            slice.copy_from_slice(&flipped);
+        }
+        height - region.y - region.height
+    } else {
+        region.y
+    };
+    let (pixel_format, data_type) = transfer_format(channels, component);
+    // SAFETY: the region was validated against the texture size and the
+    // byte length matches the region, format and pixel type.
+    unsafe {
+        context.bind_texture(target, Some(id));
+        context.tex_sub_image_3d(
+            target,
+            0,
+            region.x as i32,
+            y as i32,
+            region.z as i32,
+            region.width as i32,
+            region.height as i32,
+            region.depth as i32,
+            pixel_format,
+            data_type,
+            PixelUnpackData::Slice(&bytes),
+        );
+        if mip_maps > 1 {
+            context.generate_mipmap(target);
+        }
+    }
+    Ok(())
+}
+
+/// A 2D texture stored in CPU memory, ready to be uploaded to the GPU.
+#[napi]
+pub struct CpuTexture {
+    pub(crate) inner: three_d::CpuTexture,
+}
+
+#[napi]
+impl CpuTexture {
+    /// Creates a CPU texture from tightly packed pixel rows, top row first.
+    #[napi(constructor)]
+    pub fn new(
+        width: TextureWidth,
+        height: TextureHeight,
+        format: TextureFormat,
+        data: TexturePixels,
+        options: Option<TextureOptions>,
+    ) -> Result<Self> {
+        let data = texture_data(&format, &data, width as usize * height as usize)?;
+        Self::from_data(width, height, data, options.unwrap_or_default())
+    }
+
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        format_of(&self.inner.data)
+    }
+
+    /// Returns a copy of the pixel data, top row first.
+    #[napi]
+    pub fn pixels(&self) -> Result<PixelData> {
+        let (_, component) = color_format(&format_of(&self.inner.data))?;
+        Ok(pixel_data(component, &texture_bytes(&self.inner.data)))
+    }
+}
+
+impl CpuTexture {
+    /// Wraps texture data whose length was already validated.
+    pub(crate) fn from_data(
+        width: TextureWidth,
+        height: TextureHeight,
+        data: TextureData,
+        options: TextureOptions,
+    ) -> Result<Self> {
+        let sampling = options.sampling()?;
+        Ok(CpuTexture {
+            inner: three_d::CpuTexture {
+                name: options.name.unwrap_or_else(|| "default".to_owned()),
+                data,
+                width,
+                height,
+                min_filter: sampling.min_filter,
+                mag_filter: sampling.mag_filter,
+                mipmap: sampling.mipmap,
+                wrap_s: sampling.wrap_s,
+                wrap_t: sampling.wrap_t,
+            },
+        })
+    }
+}
+
+/// A 2D color texture stored on the GPU.
+#[napi]
+pub struct Texture2D {
+    pub(crate) inner: Arc<three_d::Texture2D>,
+    pub(crate) format: TextureFormat,
+    pub(crate) id: three_d::context::Texture,
+}
+
+#[napi]
+impl Texture2D {
+    /// Uploads a CPU texture to the GPU.
+    #[napi(constructor)]
+    pub fn new(context: &Context, cpu_texture: &CpuTexture) -> Result<Self> {
+        let inner = three_d::Texture2D::new(&context.inner, &cpu_texture.inner);
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_2D)?;
+        Ok(Texture2D {
+            inner: Arc::new(inner),
+            format: cpu_texture.format(),
+            id,
+        })
+    }
+
+    /// Creates a texture directly from pixel data, skipping the intermediate `CpuTexture`.
+    #[napi(factory)]
+    pub fn from_data(
+        context: &Context,
+        width: TextureWidth,
+        height: TextureHeight,
+        format: TextureFormat,
+        data: TexturePixels,
+        options: Option<TextureOptions>,
+    ) -> Result<Self> {
+        let cpu_texture = CpuTexture::new(width, height, format, data, options)?;
+        Self::new(context, &cpu_texture)
+    }
+
+    /// Replaces the texture content with new pixel data in the texture's format.
+    /// Fails if the texture is currently shared with a material.
+    #[napi]
+    pub fn fill(&mut self, data: TexturePixels) -> Result<()> {
+        let texels = self.inner.width() as usize * self.inner.height() as usize;
+        let data = texture_data(&self.format, &data, texels)?;
+        let texture = Arc::get_mut(&mut self.inner).ok_or_else(|| {
+            Error::new(
+                Status::GenericFailure,
+                "Texture2D is in use and cannot be modified",
+            )
+        })?;
+        match data {
+            TextureData::RU8(data) => texture.fill(&data),
+            TextureData::RgU8(data) => texture.fill(&data),
+            TextureData::RgbU8(data) => texture.fill(&data),
+            TextureData::RgbaU8(data) => texture.fill(&data),
+            TextureData::RF16(data) => texture.fill(&data),
+            TextureData::RgF16(data) => texture.fill(&data),
+            TextureData::RgbF16(data) => texture.fill(&data),
+            TextureData::RgbaF16(data) => texture.fill(&data),
+            TextureData::RF32(data) => texture.fill(&data),
+            TextureData::RgF32(data) => texture.fill(&data),
+            TextureData::RgbF32(data) => texture.fill(&data),
+            TextureData::RgbaF32(data) => texture.fill(&data),
+        }
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// Returns the number of mip levels, 1 if mipmapping is disabled.
+    #[napi]
+    pub fn number_of_mip_maps(&self) -> u32 {
+        self.inner.number_of_mip_maps()
+    }
+}
+
+/// A 3D texture stored in CPU memory, ready to be uploaded to the GPU.
+#[napi]
+pub struct CpuTexture3D {
+    pub(crate) inner: three_d::CpuTexture3D,
+}
+
+#[napi]
+impl CpuTexture3D {
+    /// Creates a CPU 3D texture from tightly packed slices, each slice stored row by row.
+    #[napi(constructor)]
+    pub fn new(
+        width: TextureWidth,
+        height: TextureHeight,
+        depth: TextureDepth,
+        format: TextureFormat,
+        data: TexturePixels,
+        options: Option<TextureOptions>,
+    ) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        let sampling = options.sampling()?;
+        let texels = width as usize * height as usize * depth as usize;
+        let data = texture_data(&format, &data, texels)?;
+        Ok(CpuTexture3D {
+            inner: three_d::CpuTexture3D {
+                name: options.name.unwrap_or_else(|| "default".to_owned()),
+                data,
+                width,
+                height,
+                depth,
+                min_filter: sampling.min_filter,
+                mag_filter: sampling.mag_filter,
+                mipmap: sampling.mipmap,
+                wrap_s: sampling.wrap_s,
+                wrap_t: sampling.wrap_t,
+                wrap_r: sampling.wrap_r,
+            },
+        })
+    }
+
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height
+    }
+
+    #[napi(getter)]
+    pub fn depth(&self) -> TextureDepth {
+        self.inner.depth
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        format_of(&self.inner.data)
+    }
+}
+
+/// An array of 2D color textures of equal size and format stored on the GPU.
+/// Sampled as `sampler2DArray` in shaders.
+#[napi]
+pub struct Texture2DArray {
+    pub(crate) inner: Arc<three_d::Texture2DArray>,
+    pub(crate) format: TextureFormat,
+    context: three_d::Context,
+    pub(crate) id: three_d::context::Texture,
+}
+
+#[napi]
+impl Texture2DArray {
+    /// Uploads one CPU texture per layer. All layers must share size and format,
+    /// and the sampling options of the first layer are used.
+    #[napi(constructor)]
+    pub fn new(context: &Context, layers: Vec<&CpuTexture>) -> Result<Self> {
+        let first = layers
+            .first()
+            .ok_or_else(|| Error::new(Status::InvalidArg, "Expected at least one layer"))?;
+        let format = first.format();
+        if layers.iter().any(|layer| {
+            layer.inner.width != first.inner.width
+                || layer.inner.height != first.inner.height
+                || layer.format() != format
+        }) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "All layers must have the same width, height and format",
+            ));
+        }
+        let cpu_textures: Vec<&three_d::CpuTexture> =
+            layers.iter().map(|layer| &layer.inner).collect();
+        let inner = three_d::Texture2DArray::new(&context.inner, &cpu_textures);
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_2D_ARRAY)?;
+        Ok(Texture2DArray {
+            inner: Arc::new(inner),
+            format,
+            context: context.inner.clone(),
+            id,
+        })
+    }
+
+    /// Replaces the content of a single layer, given top row first.
+    #[napi]
+    pub fn fill_layer(&self, layer: u32, data: TexturePixels) -> Result<()> {
+        self.update_region(layer, 0, 0, self.width(), self.height(), data)
+    }
+
+    /// Replaces a rectangle of a single layer. `y` counts from the top row.
+    /// Materials and render targets sharing the texture see the new texels.
+    #[napi]
+    pub fn update_region(
+        &self,
+        layer: u32,
+        x: u32,
+        y: u32,
+        width: TextureWidth,
+        height: TextureHeight,
+        data: TexturePixels,
+    ) -> Result<()> {
+        upload_region(
+            &self.context,
+            three_d::context::TEXTURE_2D_ARRAY,
+            self.id,
+            (self.width(), self.height(), self.layers()),
+            self.inner.number_of_mip_maps(),
+            Region {
+                x,
+                y,
+                z: layer,
+                width,
+                height,
+                depth: 1,
+            },
+            &self.format,
+            &data,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    /// The number of layers.
+    #[napi(getter)]
+    pub fn layers(&self) -> TextureDepth {
+        self.inner.depth()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// Returns the number of mip levels, 1 if mipmapping is disabled.
+    #[napi]
+    pub fn number_of_mip_maps(&self) -> u32 {
+        self.inner.number_of_mip_maps()
+    }
+}
+
+/// A 3D color texture stored on the GPU, sampled as `sampler3D` in shaders.
+#[napi]
+pub struct Texture3D {
+    pub(crate) inner: Arc<three_d::Texture3D>,
+    pub(crate) format: TextureFormat,
+    context: three_d::Context,
+    id: three_d::context::Texture,
+}
+
+#[napi]
+impl Texture3D {
+    /// Uploads a CPU 3D texture to the GPU.
+    #[napi(constructor)]
+    pub fn new(context: &Context, cpu_texture: &CpuTexture3D) -> Result<Self> {
+        let inner = three_d::Texture3D::new(&context.inner, &cpu_texture.inner);
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_3D)?;
+        Ok(Texture3D {
+            inner: Arc::new(inner),
+            format: cpu_texture.format(),
+            context: context.inner.clone(),
+            id,
+        })
+    }
+
+    /// Replaces the whole content of the texture.
+    #[napi]
+    pub fn fill(&self, data: TexturePixels) -> Result<()> {
+        self.update_region(0, 0, 0, self.width(), self.height(), self.depth(), data)
+    }
+
+    /// Replaces a single depth slice.
+    #[napi]
+    pub fn fill_slice(&self, z: u32, data: TexturePixels) -> Result<()> {
+        self.update_region(0, 0, z, self.width(), self.height(), 1, data)
+    }
+
+    /// Replaces a box of texels starting at `(x, y, z)`.
+    /// Materials sharing the texture see the new texels.
+    #[napi]
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_region(
+        &self,
+        x: u32,
+        y: u32,
+        z: u32,
+        width: TextureWidth,
+        height: TextureHeight,
+        depth: TextureDepth,
+        data: TexturePixels,
+    ) -> Result<()> {
+        upload_region(
+            &self.context,
+            three_d::context::TEXTURE_3D,
+            self.id,
+            (self.width(), self.height(), self.depth()),
+            self.inner.number_of_mip_maps(),
+            Region {
+                x,
+                y,
+                z,
+                width,
+                height,
+                depth,
+            },
+            &self.format,
+            &data,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    #[napi(getter)]
+    pub fn depth(&self) -> TextureDepth {
+        self.inner.depth()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// Returns the number of mip levels, 1 if mipmapping is disabled.
+    #[napi]
+    pub fn number_of_mip_maps(&self) -> u32 {
+        self.inner.number_of_mip_maps()
+    }
+}
+
+/// Pixel data returned by texture readbacks: `Uint8Array` for 8-bit formats,
+/// `Uint16Array` of half-float bit patterns for 16-bit float formats and
+/// `Float32Array` for 32-bit float formats.
+pub type PixelData = Either3<Uint8Array, Uint16Array, Float32Array>;
+
+/// Converts tightly packed native-endian bytes into JS pixel data.
+pub(crate) fn pixel_data(component: Component, bytes: &[u8]) -> PixelData {
+    match component {
+        Component::U8 => Either3::A(Uint8Array::new(bytes.to_vec())),
+        Component::F16 => Either3::B(Uint16Array::new(
+            bytes
+                .chunks_exact(2)
+                .map(|b| u16::from_ne_bytes([b[0], b[1]]))
+                .collect(),
+        )),
+        Component::F32 => Either3::C(Float32Array::new(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                .collect(),
+        )),
+    }
+}
+
+/// Reads mip level 0 of a 2D texture image (a 2D texture or a cube map face)
+/// through a temporary framebuffer. Rows are returned in GL order, bottom row first.
+pub(crate) fn read_texture_image(
+    context: &three_d::Context,
+    image_target: u32,
+    id: three_d::context::Texture,
+    width: u32,
+    height: u32,
+    format: &TextureFormat,
+) -> Result<PixelData> {
+    use three_d::context::{HasContext, PixelPackData};
+    let (channels, component) = color_format(format)?;
+    let (pixel_format, data_type) = transfer_format(channels, component);
+    let size = match component {
+        Component::U8 => 1,
+        Component::F16 => 2,
+        Component::F32 => 4,
+    };
+    let mut bytes = vec![0u8; width as usize * height as usize * channels * size];
+    // SAFETY: the framebuffer is created, used and deleted within this block and the
+    // buffer is sized for the requested rectangle, format and pixel type with
+    // tightly packed rows.
+    unsafe {
+        context.pixel_store_i32(three_d::context::PACK_ALIGNMENT, 1);
+        let framebuffer = context
+            .create_framebuffer()
+            .map_err(|e| Error::new(Status::GenericFailure, e))?;
+        context.bind_framebuffer(three_d::context::READ_FRAMEBUFFER, Some(framebuffer));
+        context.framebuffer_texture_2d(
+            three_d::context::READ_FRAMEBUFFER,
+            three_d::context::COLOR_ATTACHMENT0,
+            image_target,
+            Some(id),
+            0,
+        );
+        context.read_buffer(three_d::context::COLOR_ATTACHMENT0);
+        context.read_pixels(
+            0,
+            0,
+            width as i32,
+            height as i32,
+            pixel_format,
+            data_type,
+            PixelPackData::Slice(&mut bytes),
+        );
+        context.bind_framebuffer(three_d::context::READ_FRAMEBUFFER, None);
+        context.delete_framebuffer(framebuffer);
+    }
+    Ok(pixel_data(component, &bytes))
+}
+
+/// Iterates the six sides of a cube map in GL order:
+/// `PositiveX`, `NegativeX`, `PositiveY`, `NegativeY`, `PositiveZ`, `NegativeZ`.
+#[napi(iterator)]
+pub struct CubeMapSideIterator {
+    index: usize,
+}
+
+#[napi]
+impl CubeMapSideIterator {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        CubeMapSideIterator { index: 0 }
+    }
+}
+
+impl Default for CubeMapSideIterator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Generator for CubeMapSideIterator {
+    type Yield = CubeMapSide;
+    type Next = ();
+    type Return = ();
+
+    fn next(&mut self, _value: Option<()>) -> Option<CubeMapSide> {
+        self.index += 1;
+        match self.index {
+            1 => Some(CubeMapSide::PositiveX),
+            2 => Some(CubeMapSide::NegativeX),
+            3 => Some(CubeMapSide::PositiveY),
+            4 => Some(CubeMapSide::NegativeY),
+            5 => Some(CubeMapSide::PositiveZ),
+            6 => Some(CubeMapSide::NegativeZ),
+            _ => None,
+        }
+    }
+}
+
+/// A cube map color texture stored on the GPU, sampled as `samplerCube` in shaders.
+#[napi]
+pub struct TextureCubeMap {
+    pub(crate) inner: Arc<three_d::TextureCubeMap>,
+    pub(crate) format: TextureFormat,
+    context: three_d::Context,
+    pub(crate) id: three_d::context::Texture,
+}
+
+#[napi]
+impl TextureCubeMap {
+    /// Uploads six square faces of equal size and format, given in the order of
+    /// [`CubeMapSideIterator`]. The sampling options of the first face are used.
+    #[napi(constructor)]
+    pub fn new(context: &Context, faces: Vec<&CpuTexture>) -> Result<Self> {
+        let [right, left, top, bottom, front, back] = faces.as_slice() else {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Expected 6 cube map faces, got {}", faces.len()),
+            ));
+        };
+        let format = right.format();
+        if right.inner.width != right.inner.height
+            || faces.iter().any(|face| {
+                face.inner.width != right.inner.width
+                    || face.inner.height != right.inner.height
+                    || face.format() != format
+            })
+        {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "All cube map faces must be square and have the same size and format",
+            ));
+        }
+        let inner = three_d::TextureCubeMap::new(
+            &context.inner,
+            &right.inner,
+            &left.inner,
+            &top.inner,
+            &bottom.inner,
+            &front.inner,
+            &back.inner,
+        );
+        Self::from_inner(context, inner, format)
+    }
+
+    /// Converts an equirectangular panorama into a cube map on the GPU.
+    /// Each face is a quarter of the panorama's width and stored as RGBA
+    /// with the panorama's component type.
+    #[napi(factory)]
+    pub fn from_equirectangular(context: &Context, panorama: &CpuTexture) -> Result<Self> {
+        let (_, component) = color_format(&panorama.format())?;
+        if panorama.inner.width < 4 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Equirectangular images must be at least 4 texels wide",
+            ));
+        }
+        let (inner, format) = match component {
+            Component::U8 => (
+                three_d::TextureCubeMap::new_from_equirectangular::<u8>(
+                    &context.inner,
+                    &panorama.inner,
+                ),
+                TextureFormat::R8G8B8A8,
+            ),
+            Component::F16 => (
+                three_d::TextureCubeMap::new_from_equirectangular::<f16>(
+                    &context.inner,
+                    &panorama.inner,
+                ),
+                TextureFormat::R16G16B16A16F,
+            ),
+            Component::F32 => (
+                three_d::TextureCubeMap::new_from_equirectangular::<f32>(
+                    &context.inner,
+                    &panorama.inner,
+                ),
+                TextureFormat::R32G32B32A32F,
+            ),
+        };
+        Self::from_inner(context, inner, format)
+    }
+
+    fn from_inner(
+        context: &Context,
+        inner: three_d::TextureCubeMap,
+        format: TextureFormat,
+    ) -> Result<Self> {
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_CUBE_MAP)?;
+        Ok(TextureCubeMap {
+            inner: Arc::new(inner),
+            format,
+            context: context.inner.clone(),
+            id,
+        })
+    }
+
+    /// Replaces the content of one face.
+    /// Materials and skyboxes sharing the texture see the new texels.
+    #[napi]
+    pub fn fill_face(&self, side: CubeMapSide, data: TexturePixels) -> Result<()> {
+        use three_d::context::{HasContext, PixelUnpackData};
+        let (width, height) = (self.width(), self.height());
+        let texels = width as usize * height as usize;
+        let bytes = texture_bytes(&texture_data(&self.format, &data, texels)?);
+        let (channels, component) = color_format(&self.format)?;
+        let (pixel_format, data_type) = transfer_format(channels, component);
+        // SAFETY: the byte length matches the face size, format and pixel type.
+        unsafe {
+            self.context
+                .bind_texture(three_d::context::TEXTURE_CUBE_MAP, Some(self.id));
+            self.context.tex_sub_image_2d(
+                side as u32,
+                0,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                pixel_format,
+                data_type,
+                PixelUnpackData::Slice(&bytes),
+            );
+            if self.inner.number_of_mip_maps() > 1 {
+                self.context
+                    .generate_mipmap(three_d::context::TEXTURE_CUBE_MAP);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads back the content of one face, in the same row order as it was uploaded.
+    #[napi]
+    pub fn read_face(&self, side: CubeMapSide) -> Result<PixelData> {
+        read_texture_image(
+            &self.context,
+            side as u32,
+            self.id,
+            self.width(),
+            self.height(),
+            &self.format,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+
+    /// Returns the number of mip levels, 1 if mipmapping is disabled.
+    #[napi]
+    pub fn number_of_mip_maps(&self) -> u32 {
+        self.inner.number_of_mip_maps()
+    }
+}
+
+/// Evaluates `$body` with `$T` aliased to the three-d depth component type of
+/// `$format`, or fails for formats that are not depth formats.
+macro_rules! with_depth_type {
+    ($format:expr, $T:ident => $body:expr) => {
+        match $format {
+            TextureFormat::Depth16 => {
+                type $T = f16;
+                Ok($body)
+            }
+            TextureFormat::Depth24 => {
+                type $T = three_d::f24;
+                Ok($body)
+            }
+            TextureFormat::Depth32F => {
+                type $T = f32;
+                Ok($body)
+            }
+            format => Err(Error::new(
+                Status::InvalidArg,
+                format!("{format:?} is not a depth format, expected Depth16, Depth24 or Depth32F"),
+            )),
+        }
+    };
+}
+
+/// The image of a depth texture attached to a temporary framebuffer.
+#[derive(Clone, Copy)]
+pub(crate) enum DepthImage {
+    /// A 2D texture or a cube map face, given by its image target.
+    Image(u32),
+    /// A layer of a 2D array texture.
+    Layer(u32),
+}
+
+/// Runs `f` with `image` attached as the depth attachment of a temporary
+/// framebuffer bound to both the read and draw targets.
+fn with_depth_framebuffer<T>(
+    context: &three_d::Context,
+    id: three_d::context::Texture,
+    image: DepthImage,
+    f: impl FnOnce(&three_d::Context) -> T,
+) -> Result<T> {
+    use three_d::context::HasContext;
+    // SAFETY: the framebuffer is created, used and deleted within this block.
+    unsafe {
+        let framebuffer = context
+            .create_framebuffer()
+            .map_err(|e| Error::new(Status::GenericFailure, e))?;
+        context.bind_framebuffer(three_d::context::FRAMEBUFFER, Some(framebuffer));
+        match image {
+            DepthImage::Image(target) => context.framebuffer_texture_2d(
+                three_d::context::FRAMEBUFFER,
+                three_d::context::DEPTH_ATTACHMENT,
+                target,
+                Some(id),
+                0,
+            ),
+            DepthImage::Layer(layer) => context.framebuffer_texture_layer(
+                three_d::context::FRAMEBUFFER,
+                three_d::context::DEPTH_ATTACHMENT,
+                Some(id),
+                0,
+                layer as i32,
+            ),
+        }
+        context.draw_buffers(&[three_d::context::NONE]);
+        context.read_buffer(three_d::context::NONE);
+        let result = f(context);
+        context.bind_framebuffer(three_d::context::FRAMEBUFFER, None);
+        context.delete_framebuffer(framebuffer);
+        Ok(result)
+    }
+}
+
+/// Clears a depth image to `depth`, which defaults to the far plane.
+fn clear_depth_image(
+    context: &three_d::Context,
+    id: three_d::context::Texture,
+    image: DepthImage,
+    depth: Option<f64>,
+) -> Result<()> {
+    use three_d::context::HasContext;
+    with_depth_framebuffer(context, id, image, |context| {
+        // SAFETY: state changes and a clear of the bound framebuffer.
+        unsafe {
+            context.disable(three_d::context::SCISSOR_TEST);
+            context.depth_mask(true);
+            context.clear_depth_f32(depth.unwrap_or(1.0).clamp(0.0, 1.0) as f32);
+            context.clear(three_d::context::DEPTH_BUFFER_BIT);
+        }
+    })
+}
+
+/// Reads a depth image, top row first.
+///
+/// Without a camera the stored window-space depth in `[0, 1]` is returned. With
+/// a camera each value is converted into the distance from the camera along its
+/// view direction using the camera's projection, so the texture must have been
+/// rendered with that camera. Texels at the far plane map to the far distance.
+pub(crate) fn read_depth_image(
+    context: &three_d::Context,
+    id: three_d::context::Texture,
+    image: DepthImage,
+    width: u32,
+    height: u32,
+    camera: Option<&Camera>,
+) -> Result<Float32Array> {
+    use three_d::context::{HasContext, PixelPackData};
+    let mut bytes = vec![0u8; width as usize * height as usize * 4];
+    with_depth_framebuffer(context, id, image, |context| {
+        // SAFETY: the buffer is sized for the requested rectangle as 32-bit floats.
+        unsafe {
+            context.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                three_d::context::DEPTH_COMPONENT,
+                three_d::context::FLOAT,
+                PixelPackData::Slice(&mut bytes),
+            );
+        }
+    })?;
+    // Computed in f64 from the clip planes, as the f32 projection matrix loses
+    // most of its precision near the far plane.
+    let linearize = camera.map(|camera| {
+        let (near, far) = (camera.inner.z_near() as f64, camera.inner.z_far() as f64);
+        // Only perspective projections divide by the view-space depth.
+        let perspective = camera.inner.projection().z.w != 0.0;
+        move |depth: f32| {
+            let z_ndc = 2.0 * depth as f64 - 1.0;
+            let distance = if perspective {
+                2.0 * far * near / (far + near - z_ndc * (far - near))
+            } else {
+                (z_ndc * (far - near) + far + near) / 2.0
+            };
+            distance as f32
+        }
+    });
+    let row = width as usize * 4;
+    let values = bytes
+        .chunks_exact(row.max(1))
+        .rev()
+        .flat_map(|row| row.chunks_exact(4))
+        .map(|b| {
+            let depth = f32::from_ne_bytes([b[0], b[1], b[2], b[3]]);
+            linearize
+                .as_ref()
+                .map_or(depth, |linearize| linearize(depth))
+        })
+        .collect();
+    Ok(Float32Array::new(values))
+}
+
+/// A 2D depth texture stored on the GPU, sampled as `sampler2D` in shaders.
+/// Depth textures always use nearest filtering and have a single mip level.
+#[napi]
+pub struct DepthTexture2D {
+    pub(crate) inner: Arc<three_d::DepthTexture2D>,
+    pub(crate) format: TextureFormat,
+    pub(crate) context: three_d::Context,
+    pub(crate) id: three_d::context::Texture,
+}
+
+#[napi]
+impl DepthTexture2D {
+    /// Allocates a depth texture of the given format, `Depth32F` by default.
+    /// Only the wrapping of `options` is used.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        width: TextureWidth,
+        height: TextureHeight,
+        format: Option<TextureFormat>,
+        options: Option<TextureOptions>,
+    ) -> Result<Self> {
+        let format = format.unwrap_or(TextureFormat::Depth32F);
+        let sampling = options.unwrap_or_default().sampling()?;
+        let inner = with_depth_type!(&format, T => three_d::DepthTexture2D::new::<T>(
+            &context.inner,
+            width,
+            height,
+            sampling.wrap_s,
+            sampling.wrap_t,
+        ))?;
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_2D)?;
+        Ok(DepthTexture2D {
+            inner: Arc::new(inner),
+            format,
+            context: context.inner.clone(),
+            id,
+        })
+    }
+
+    /// Sets every texel to `depth`, which defaults to 1 (the far plane).
+    #[napi]
+    pub fn clear(&self, depth: Option<f64>) -> Result<()> {
+        clear_depth_image(
+            &self.context,
+            self.id,
+            DepthImage::Image(three_d::context::TEXTURE_2D),
+            depth,
+        )
+    }
+
+    /// Reads the depth values, top row first. Returns raw depth in `[0, 1]`, or
+    /// the linear distance from `camera` along its view direction if given.
+    #[napi]
+    pub fn read_depth(&self, camera: Option<&Camera>) -> Result<Float32Array> {
+        read_depth_image(
+            &self.context,
+            self.id,
+            DepthImage::Image(three_d::context::TEXTURE_2D),
+            self.width(),
+            self.height(),
+            camera,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+}
+
+/// An array of 2D depth textures stored on the GPU, sampled as
+/// `sampler2DArray` in shaders.
+#[napi]
+pub struct DepthTexture2DArray {
+    pub(crate) inner: Arc<three_d::DepthTexture2DArray>,
+    pub(crate) format: TextureFormat,
+    pub(crate) context: three_d::Context,
+    pub(crate) id: three_d::context::Texture,
+}
+
+#[napi]
+impl DepthTexture2DArray {
+    /// Allocates `layers` depth textures of the given format, `Depth32F` by default.
+    /// Only the wrapping of `options` is used.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        width: TextureWidth,
+        height: TextureHeight,
+        layers: TextureDepth,
+        format: Option<TextureFormat>,
+        options: Option<TextureOptions>,
+    ) -> Result<Self> {
+        if layers == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Expected at least one layer",
+            ));
+        }
+        let format = format.unwrap_or(TextureFormat::Depth32F);
+        let sampling = options.unwrap_or_default().sampling()?;
+        let inner = with_depth_type!(&format, T => three_d::DepthTexture2DArray::new::<T>(
+            &context.inner,
+            width,
+            height,
+            layers,
+            sampling.wrap_s,
+            sampling.wrap_t,
+        ))?;
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_2D_ARRAY)?;
+        Ok(DepthTexture2DArray {
+            inner: Arc::new(inner),
+            format,
+            context: context.inner.clone(),
+            id,
+        })
+    }
+
+    /// Sets every texel of a layer to `depth`, which defaults to 1 (the far plane).
+    #[napi]
+    pub fn clear(&self, layer: u32, depth: Option<f64>) -> Result<()> {
+        clear_depth_image(&self.context, self.id, self.layer(layer)?, depth)
+    }
+
+    /// Reads the depth values of a layer, top row first. Returns raw depth in
+    /// `[0, 1]`, or the linear distance from `camera` along its view direction if given.
+    #[napi]
+    pub fn read_depth(&self, layer: u32, camera: Option<&Camera>) -> Result<Float32Array> {
+        read_depth_image(
+            &self.context,
+            self.id,
+            self.layer(layer)?,
+            self.width(),
+            self.height(),
+            camera,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    /// The number of layers.
+    #[napi(getter)]
+    pub fn layers(&self) -> TextureDepth {
+        self.inner.depth()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+}
+
+impl DepthTexture2DArray {
+    fn layer(&self, layer: u32) -> Result<DepthImage> {
+        if layer >= self.layers() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Layer {layer} is out of range for {} layers", self.layers()),
+            ));
+        }
+        Ok(DepthImage::Layer(layer))
+    }
+}
+
+/// A cube map depth texture stored on the GPU, sampled as `samplerCube` in
+/// shaders. Typically used for omnidirectional shadow maps.
+#[napi]
+pub struct DepthTextureCubeMap {
+    pub(crate) inner: Arc<three_d::DepthTextureCubeMap>,
+    pub(crate) format: TextureFormat,
+    pub(crate) context: three_d::Context,
+    pub(crate) id: three_d::context::Texture,
+}
+
+#[napi]
+impl DepthTextureCubeMap {
+    /// Allocates six square depth faces of the given format, `Depth32F` by default.
+    /// Only the wrapping of `options` is used.
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        size: TextureWidth,
+        format: Option<TextureFormat>,
+        options: Option<TextureOptions>,
+    ) -> Result<Self> {
+        let format = format.unwrap_or(TextureFormat::Depth32F);
+        let sampling = options.unwrap_or_default().sampling()?;
+        let inner = with_depth_type!(&format, T => three_d::DepthTextureCubeMap::new::<T>(
+            &context.inner,
+            size,
+            size,
+            sampling.wrap_s,
+            sampling.wrap_t,
+            sampling.wrap_r,
+        ))?;
+        let id = bound_texture(&context.inner, three_d::context::TEXTURE_BINDING_CUBE_MAP)?;
+        Ok(DepthTextureCubeMap {
+            inner: Arc::new(inner),
+            format,
+            context: context.inner.clone(),
+            id,
+        })
+    }
+
+    /// Sets every texel of one face to `depth`, which defaults to 1 (the far plane).
+    #[napi]
+    pub fn clear(&self, side: CubeMapSide, depth: Option<f64>) -> Result<()> {
+        clear_depth_image(
+            &self.context,
+            self.id,
+            DepthImage::Image(side as u32),
+            depth,
+        )
+    }
+
+    /// Reads the depth values of one face, top row first. Returns raw depth in
+    /// `[0, 1]`, or the linear distance from `camera` along its view direction if given.
+    #[napi]
+    pub fn read_depth(&self, side: CubeMapSide, camera: Option<&Camera>) -> Result<Float32Array> {
+        read_depth_image(
+            &self.context,
+            self.id,
+            DepthImage::Image(side as u32),
+            self.width(),
+            self.height(),
+            camera,
+        )
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> TextureWidth {
+        self.inner.width()
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> TextureHeight {
+        self.inner.height()
+    }
+
+    #[napi(getter)]
+    pub fn format(&self) -> TextureFormat {
+        self.format.clone()
+    }
+}
@@ -0,0 +1,70 @@
+use napi_derive::napi;
+
+/// A rectangular region of a render target in pixels, with the origin in the
+/// bottom left corner.
+#[napi]
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[napi]
+impl Viewport {
+    #[napi(constructor)]
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates a viewport starting at `(0, 0)`.
+    #[napi(factory)]
+    pub fn at_origin(width: u32, height: u32) -> Self {
+        Self::new(0, 0, width, height)
+    }
+
+    /// Returns the width divided by the height.
+    #[napi]
+    pub fn aspect_ratio(&self) -> f64 {
+        self.width as f64 / self.height as f64
+    }
+
+    /// Returns whether the pixel `(x, y)` lies inside the viewport.
+    #[napi]
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (left, bottom) = (self.x as f64, self.y as f64);
+        x >= left && x < left + self.width as f64 && y >= bottom && y < bottom + self.height as f64
+    }
+
+    /// Returns a short description of the viewport.
+    #[napi]
+    pub fn get_info(&self) -> String {
+        format!(
+            "Viewport {{ x: {}, y: {}, width: {}, height: {} }}",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+impl From<&Viewport> for three_d::Viewport {
+    fn from(v: &Viewport) -> Self {
+        three_d::Viewport {
+            x: v.x,
+            y: v.y,
+            width: v.width,
+            height: v.height,
+        }
+    }
+}
+
+impl From<three_d::Viewport> for Viewport {
+    fn from(v: three_d::Viewport) -> Self {
+        Viewport::new(v.x, v.y, v.width, v.height)
+    }
+}
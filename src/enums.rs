@@ -79,6 +79,16 @@ pub enum CompressionTextureType {
     S3TC,
     S3TCSRGB,
     SVGA,
+    RGTC,
+}
+
+/// Image file format used when encoding textures.
+#[napi]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Exr,
 }
 
 /// Shader type enumeration.
@@ -228,7 +238,7 @@ pub enum CubeMapSide {
 
 /// Texture internal format.
 #[napi]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TextureFormat {
     R8 = 0x8229,          // GL_R8
     R8I = 0x8231,         // GL_R8I
@@ -248,9 +258,9 @@ pub enum TextureFormat {
     R8G8B8A8 = 0x1908,    // GL_RGBA
     R8G8B8A8I = 0x8D95,   // GL_RGBA8I
     R8G8B8A8UI = 0x8D81,  // GL_RGBA8UI
-    R16F16I = 0x822F,     // GL_R16F (special 16-bit float)
+    R16F16I = 0x822F,     // GL_RG16F
     R16I16UI = 0x8239,    // GL_R16I (special 16-bit integer)
-    R32F32I = 0x8230,     // GL_R32F (special 32-bit float)
+    R32F32I = 0x8230,     // GL_RG32F
     R32I32UI = 0x823A,    // GL_R32I (special 32-bit integer)
     R16F16I16UI = 0x881E, // GL_R16F (special for RGB)
     R16I16UI16F = 0x8E59, // GL_R16I (special for RGB)
@@ -258,6 +268,10 @@ pub enum TextureFormat {
     R16F16I16UI16F = 0x8E5A,   // GL_R16F (special for RGBA)
     R16I16UI16F16F = 0x8E5B,   // GL_R16I (special for RGBA)
     R32F32I32UI32F = 0x8E5C,   // GL_R32F (special for RGBA)
+    R16G16B16F = 0x881B,       // GL_RGB16F
+    R16G16B16A16F = 0x881A,    // GL_RGBA16F
+    R32G32B32F = 0x8815,       // GL_RGB32F
+    R32G32B32A32F = 0x8814,    // GL_RGBA32F
     R8G8B8A8Unorm = 0x8058,    // Special RGBA8
     R8G8B8A8Snorm = 0x8F97,    // Special RGBA8 SNORM
     R8G8B8A8Sint = 0x8D94,     // Special RGBA8 SINT
@@ -314,11 +328,15 @@ pub enum DrawMode {
 
 /// Transparency blending mode.
 #[napi]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Transparency {
+    /// Replaces what is behind and writes depth.
     Opaque,
+    /// Blends over what is behind by the alpha of the color.
     Alpha,
+    /// Adds the color, weighted by its alpha, to what is behind.
     Additive,
+    /// Multiplies what is behind by the color.
     Multiply,
 }
 
@@ -333,7 +351,7 @@ pub enum FilterMode {
 
 /// Tone mapping algorithm for HDR rendering.
 #[napi]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ToneMapping {
     None,
     Reinhard,
@@ -344,6 +362,30 @@ pub enum ToneMapping {
     Unreal,
 }
 
+/// Color space the final image is written in.
+#[napi]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMapping {
+    /// Linear values, for intermediate passes.
+    Linear,
+    /// sRGB encoded values, for display and images.
+    Srgb,
+}
+
+/// A texture of the G-buffer written by deferred materials.
+#[napi]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GBufferTexture {
+    /// Linear albedo color.
+    Albedo,
+    /// World space normal.
+    Normal,
+    /// Occlusion, roughness and metallic.
+    ORM,
+    /// Depth buffer value in `[0, 1]`.
+    Depth,
+}
+
 /// Background rendering mode.
 #[napi]
 #[derive(Debug, Clone)]
@@ -390,13 +432,25 @@ pub enum LightType {
 
 /// Eye stereo mode for stereoscopic rendering.
 #[napi]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum StereoMode {
     Mono,
     Left,
     Right,
     SideBySide,
     TopBottom,
+    /// Red/cyan anaglyph: red from the left eye, green and blue from the right.
+    Anaglyph,
+}
+
+/// Easing curve applied to the progress of an animation.
+#[napi]
+#[derive(Debug, Clone, Copy)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
 }
 
 /// Framebuffer attachment type.
@@ -0,0 +1,240 @@
+//! Basis Universal transcoding of KTX2 payloads.
+//!
+//! The transcoder only reads `.basis` files, so the KTX2 levels and BasisLZ
+//! global data are repackaged into one in memory. Both containers store the
+//! same slice data, only the headers differ.
+
+use crate::core::compressed::BlockFormat;
+use crate::enums::CompressionTextureType;
+use basis_universal::{TranscodeParameters, Transcoder, TranscoderTextureFormat};
+use napi::{Error, Result, Status};
+
+fn invalid(message: &str) -> Error {
+    Error::new(
+        Status::InvalidArg,
+        format!("Invalid Basis Universal texture: {message}"),
+    )
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> usize {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+}
+
+/// Writes the low `size` bytes of a little-endian field.
+fn write(file: &mut [u8], offset: usize, size: usize, value: usize) {
+    file[offset..offset + size].copy_from_slice(&(value as u64).to_le_bytes()[..size]);
+}
+
+/// UASTC blocks are 4x4 texels in 16 bytes, laid out like 4x4 ASTC.
+pub(crate) const UASTC: BlockFormat = BlockFormat::Astc {
+    width: 4,
+    height: 4,
+    srgb: false,
+};
+
+/// The Basis Universal codec of a KTX2 texture.
+pub(crate) enum Codec<'a> {
+    /// ETC1S slices with the BasisLZ global data: codebooks followed by
+    /// one image descriptor per mip level.
+    Etc1s { global: &'a [u8] },
+    /// UASTC blocks, already stripped of any Zstandard supercompression.
+    Uastc,
+}
+
+const HEADER_SIZE: usize = 77;
+const SLICE_DESC_SIZE: usize = 23;
+const FLAG_ETC1S: usize = 1;
+const FLAG_HAS_ALPHA_SLICES: usize = 4;
+const FLAG_SRGB: usize = 16;
+const SLICE_HAS_ALPHA: usize = 1;
+
+/// Picks the transcoder output for a target compression type.
+fn target_format(
+    target: &CompressionTextureType,
+    alpha: bool,
+    srgb: bool,
+) -> Result<(TranscoderTextureFormat, BlockFormat)> {
+    Ok(match target {
+        CompressionTextureType::BPTC => {
+            (TranscoderTextureFormat::BC7_RGBA, BlockFormat::Bc7 { srgb })
+        }
+        CompressionTextureType::S3TC | CompressionTextureType::S3TCSRGB if alpha => {
+            (TranscoderTextureFormat::BC3_RGBA, BlockFormat::Bc3 { srgb })
+        }
+        CompressionTextureType::S3TC | CompressionTextureType::S3TCSRGB => (
+            TranscoderTextureFormat::BC1_RGB,
+            BlockFormat::Bc1 { alpha: false, srgb },
+        ),
+        CompressionTextureType::ETC2 | CompressionTextureType::ETC2EAC if alpha => (
+            TranscoderTextureFormat::ETC2_RGBA,
+            BlockFormat::Etc2Eac { srgb },
+        ),
+        // ETC1 blocks are valid ETC2 blocks.
+        CompressionTextureType::ETC2 | CompressionTextureType::ETC2EAC => (
+            TranscoderTextureFormat::ETC1_RGB,
+            BlockFormat::Etc2 { srgb },
+        ),
+        CompressionTextureType::ASTC => (
+            TranscoderTextureFormat::ASTC_4x4_RGBA,
+            BlockFormat::Astc {
+                width: 4,
+                height: 4,
+                srgb,
+            },
+        ),
+        CompressionTextureType::RGTC if alpha => (
+            TranscoderTextureFormat::BC5_RG,
+            BlockFormat::Bc5 { signed: false },
+        ),
+        CompressionTextureType::RGTC => (
+            TranscoderTextureFormat::BC4_R,
+            BlockFormat::Bc4 { signed: false },
+        ),
+        CompressionTextureType::SVGA => {
+            return Err(invalid(&format!("cannot transcode to {target:?}")))
+        }
+    })
+}
+
+/// Transcodes the mip levels of a Basis Universal texture to the block
+/// format of `target`. With alpha, S3TC becomes BC3, ETC2 becomes ETC2 with
+/// EAC alpha and RGTC becomes BC5 with alpha in the green channel.
+pub(crate) fn transcode(
+    width: u32,
+    height: u32,
+    codec: Codec,
+    levels: &[Vec<u8>],
+    alpha: bool,
+    srgb: bool,
+    target: &CompressionTextureType,
+) -> Result<(BlockFormat, Vec<Vec<u8>>)> {
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(invalid("textures are limited to 65535 texels per side"));
+    }
+    let (output, format) = target_format(target, alpha, srgb)?;
+
+    // Codebooks and (level, alpha, data) of every slice.
+    let mut codebooks: [(usize, &[u8]); 3] = [(0, &[]); 3];
+    let mut slices = Vec::new();
+    match codec {
+        Codec::Etc1s { global } => {
+            let descs = 20 + levels.len() * 20;
+            if global.len() < descs {
+                return Err(invalid("truncated global data"));
+            }
+            let lengths = [4, 8, 12].map(|offset| read_u32(global, offset));
+            let mut offset = descs;
+            for (i, length) in lengths.into_iter().enumerate() {
+                let data = global
+                    .get(offset..offset + length)
+                    .filter(|_| length < 1 << 24)
+                    .ok_or_else(|| invalid("truncated codebooks"))?;
+                // Endpoint and selector counts precede the lengths.
+                let count = match i {
+                    2 => 0,
+                    _ => u16::from_le_bytes([global[i * 2], global[i * 2 + 1]]) as usize,
+                };
+                codebooks[i] = (count, data);
+                offset += length;
+            }
+            for (level, data) in levels.iter().enumerate() {
+                let desc = 20 + level * 20;
+                let planes = if alpha { 2 } else { 1 };
+                for plane in 0..planes {
+                    let start = read_u32(global, desc + 4 + plane * 8);
+                    let length = read_u32(global, desc + 8 + plane * 8);
+                    let slice = data
+                        .get(start..start + length)
+                        .filter(|slice| !slice.is_empty())
+                        .ok_or_else(|| invalid(&format!("mip level {level} is truncated")))?;
+                    slices.push((level, plane == 1, slice));
+                }
+            }
+        }
+        Codec::Uastc => {
+            for (level, data) in levels.iter().enumerate() {
+                let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+                if UASTC.level_size(w, h) != Some(data.len()) {
+                    return Err(invalid(&format!(
+                        "mip level {level} has {} bytes",
+                        data.len()
+                    )));
+                }
+                slices.push((level, alpha, data.as_slice()));
+            }
+        }
+    }
+
+    let etc1s = matches!(codec, Codec::Etc1s { .. });
+    let mut file = vec![0u8; HEADER_SIZE + slices.len() * SLICE_DESC_SIZE];
+    let mut offsets = [0; 3];
+    for (offset, (_, data)) in offsets.iter_mut().zip(&codebooks) {
+        *offset = file.len();
+        file.extend_from_slice(data);
+    }
+    for (i, &(level, has_alpha, data)) in slices.iter().enumerate() {
+        let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+        let desc = HEADER_SIZE + i * SLICE_DESC_SIZE;
+        write(&mut file, desc + 3, 1, level);
+        write(
+            &mut file,
+            desc + 4,
+            1,
+            if has_alpha { SLICE_HAS_ALPHA } else { 0 },
+        );
+        write(&mut file, desc + 5, 2, w as usize);
+        write(&mut file, desc + 7, 2, h as usize);
+        write(&mut file, desc + 9, 2, w.div_ceil(4) as usize);
+        write(&mut file, desc + 11, 2, h.div_ceil(4) as usize);
+        let offset = file.len();
+        write(&mut file, desc + 13, 4, offset);
+        write(&mut file, desc + 17, 4, data.len());
+        file.extend_from_slice(data);
+    }
+    if file.len() > u32::MAX as usize {
+        return Err(invalid("texture is too large"));
+    }
+
+    let flags = if etc1s { FLAG_ETC1S } else { 0 }
+        | if alpha { FLAG_HAS_ALPHA_SLICES } else { 0 }
+        | if srgb { FLAG_SRGB } else { 0 };
+    let header = [
+        (0, 2, 0x4273),
+        (2, 2, 0x13),
+        (4, 2, HEADER_SIZE),
+        (8, 4, file.len() - HEADER_SIZE),
+        (14, 3, slices.len()),
+        (17, 3, 1),
+        (20, 1, if etc1s { 0 } else { 1 }),
+        (21, 2, flags),
+        (39, 2, codebooks[0].0),
+        (41, 4, offsets[0]),
+        (45, 3, codebooks[0].1.len()),
+        (48, 2, codebooks[1].0),
+        (50, 4, offsets[1]),
+        (54, 3, codebooks[1].1.len()),
+        (57, 4, offsets[2]),
+        (61, 4, codebooks[2].1.len()),
+        (65, 4, HEADER_SIZE),
+    ];
+    for (offset, size, value) in header {
+        write(&mut file, offset, size, value);
+    }
+
+    let mut transcoder = Transcoder::new();
+    transcoder
+        .prepare_transcoding(&file)
+        .map_err(|_| invalid("corrupt codebooks"))?;
+    let levels = (0..levels.len())
+        .map(|level| {
+            let parameters = TranscodeParameters {
+                level_index: level as u32,
+                ..Default::default()
+            };
+            transcoder
+                .transcode_image_level(&file, output, parameters)
+                .map_err(|e| invalid(&format!("mip level {level} failed to transcode: {e:?}")))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok((format, levels))
+}
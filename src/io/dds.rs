@@ -0,0 +1,122 @@
+//! DirectDraw Surface container parsing for block-compressed 2D textures.
+
+use crate::core::compressed::BlockFormat;
+use napi::{Error, Result, Status};
+
+fn invalid(message: &str) -> Error {
+    Error::new(Status::InvalidArg, format!("Invalid DDS file: {message}"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+const DDS_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+/// Maps a DXGI format of a DX10 extended header to a block format.
+fn dxgi_format(format: u32) -> Option<BlockFormat> {
+    Some(match format {
+        70 | 71 => BlockFormat::Bc1 {
+            alpha: true,
+            srgb: false,
+        },
+        72 => BlockFormat::Bc1 {
+            alpha: true,
+            srgb: true,
+        },
+        73 | 74 => BlockFormat::Bc2 { srgb: false },
+        75 => BlockFormat::Bc2 { srgb: true },
+        76 | 77 => BlockFormat::Bc3 { srgb: false },
+        78 => BlockFormat::Bc3 { srgb: true },
+        79 | 80 => BlockFormat::Bc4 { signed: false },
+        81 => BlockFormat::Bc4 { signed: true },
+        82 | 83 => BlockFormat::Bc5 { signed: false },
+        84 => BlockFormat::Bc5 { signed: true },
+        94 | 95 => BlockFormat::Bc6h { signed: false },
+        96 => BlockFormat::Bc6h { signed: true },
+        97 | 98 => BlockFormat::Bc7 { srgb: false },
+        99 => BlockFormat::Bc7 { srgb: true },
+        _ => return None,
+    })
+}
+
+/// Maps a legacy four character code to a block format.
+fn four_cc_format(four_cc: &[u8]) -> Option<BlockFormat> {
+    Some(match four_cc {
+        b"DXT1" => BlockFormat::Bc1 {
+            alpha: true,
+            srgb: false,
+        },
+        b"DXT2" | b"DXT3" => BlockFormat::Bc2 { srgb: false },
+        b"DXT4" | b"DXT5" => BlockFormat::Bc3 { srgb: false },
+        b"ATI1" | b"BC4U" => BlockFormat::Bc4 { signed: false },
+        b"BC4S" => BlockFormat::Bc4 { signed: true },
+        b"ATI2" | b"BC5U" => BlockFormat::Bc5 { signed: false },
+        b"BC5S" => BlockFormat::Bc5 { signed: true },
+        _ => return None,
+    })
+}
+
+/// Parses a DDS file into its dimensions, block format and mip levels.
+pub(crate) fn parse(bytes: &[u8]) -> Result<(u32, u32, BlockFormat, Vec<Vec<u8>>)> {
+    if bytes.len() < 128 || read_u32(bytes, 4) != 124 {
+        return Err(invalid("truncated header"));
+    }
+    let flags = read_u32(bytes, 8);
+    let height = read_u32(bytes, 12);
+    let width = read_u32(bytes, 16);
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(bytes, 28).max(1)
+    } else {
+        1
+    };
+    if read_u32(bytes, 80) & DDPF_FOURCC == 0 {
+        return Err(invalid("uncompressed formats are not supported"));
+    }
+    if read_u32(bytes, 112) & (DDSCAPS2_CUBEMAP | DDSCAPS2_VOLUME) != 0 {
+        return Err(invalid("only 2D textures are supported"));
+    }
+    let four_cc = &bytes[84..88];
+    let (format, mut offset) = if four_cc == b"DX10" {
+        if bytes.len() < 148 {
+            return Err(invalid("truncated DX10 header"));
+        }
+        let dxgi = read_u32(bytes, 128);
+        if read_u32(bytes, 132) != DDS_RESOURCE_DIMENSION_TEXTURE2D
+            || read_u32(bytes, 136) & DDS_RESOURCE_MISC_TEXTURECUBE != 0
+            || read_u32(bytes, 140) > 1
+        {
+            return Err(invalid("only 2D textures are supported"));
+        }
+        let format =
+            dxgi_format(dxgi).ok_or_else(|| invalid(&format!("unsupported DXGI format {dxgi}")))?;
+        (format, 148usize)
+    } else {
+        let format = four_cc_format(four_cc).ok_or_else(|| {
+            invalid(&format!(
+                "unsupported format {:?}",
+                String::from_utf8_lossy(four_cc)
+            ))
+        })?;
+        (format, 128)
+    };
+    let mut levels = Vec::new();
+    for level in 0..mip_count.min(32) {
+        let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+        let size = format
+            .level_size(w, h)
+            .ok_or_else(|| invalid("image is too large"))?;
+        let data = offset
+            .checked_add(size)
+            .and_then(|end| bytes.get(offset..end))
+            .ok_or_else(|| invalid("truncated image data"))?;
+        levels.push(data.to_vec());
+        offset += size;
+    }
+    Ok((width, height, format, levels))
+}
@@ -0,0 +1,56 @@
+//! CPU decoder for EAC R11 blocks, the channels of the EAC R11 and RG11
+//! formats.
+//!
+//! ETC2 and its 8-bit EAC alpha are decoded by `texture2ddecoder`, but its
+//! R11 decoders read the index bits in the wrong byte order, so R11 is
+//! decoded here. Blocks are big-endian 64-bit words and texels are indexed
+//! column by column.
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn bits(block: u64, high: u32, count: u32) -> i32 {
+    ((block >> (high + 1 - count)) & ((1 << count) - 1)) as i32
+}
+
+/// Decodes an EAC R11 block into values normalized to `[0, 1]`, or
+/// `[-1, 1]` when `signed`, in row-major order.
+pub(crate) fn eac_r11(data: &[u8], signed: bool) -> [f32; 16] {
+    let block = u64::from_be_bytes(data[..8].try_into().unwrap());
+    let multiplier = bits(block, 55, 4);
+    let table = EAC_MODIFIERS[bits(block, 51, 4) as usize];
+    let scale = |modifier: i32| {
+        if multiplier == 0 {
+            modifier
+        } else {
+            modifier * multiplier * 8
+        }
+    };
+    let mut values = [0.0; 16];
+    for k in 0..16 {
+        let modifier = table[bits(block, 47 - 3 * k as u32, 3) as usize];
+        values[(k % 4) * 4 + k / 4] = if signed {
+            let base = (bits(block, 63, 8) as u8 as i8).max(-127) as i32;
+            (base * 8 + scale(modifier)).clamp(-1023, 1023) as f32 / 1023.0
+        } else {
+            (bits(block, 63, 8) * 8 + 4 + scale(modifier)).clamp(0, 2047) as f32 / 2047.0
+        };
+    }
+    values
+}
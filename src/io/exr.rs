@@ -0,0 +1,127 @@
+//! OpenEXR decoding and encoding through the `exr` crate.
+
+use super::{Image, Samples, MAX_DECODED_BYTES};
+use exr::prelude::*;
+use napi::{Error, Result, Status};
+use std::io::Cursor;
+
+fn invalid(error: impl std::fmt::Display) -> Error {
+    Error::new(Status::InvalidArg, format!("Invalid EXR image: {error}"))
+}
+
+/// Decodes the first layer of an OpenEXR image at its largest resolution.
+/// Returns the `R`, `G`, `B` and `A` channels, or the luminance channel `Y`,
+/// as linear floats, top row first. Half-float images stay half-float.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image> {
+    let meta = MetaData::read_from_buffered(Cursor::new(bytes), false).map_err(invalid)?;
+    for header in &meta.headers {
+        let size = (header.layer_size.area() as u64)
+            .checked_mul(header.channels.bytes_per_pixel as u64)
+            .filter(|&size| size <= MAX_DECODED_BYTES);
+        if size.is_none() {
+            return Err(invalid(format!(
+                "{}x{} pixels exceed the decoding limit",
+                header.layer_size.width(),
+                header.layer_size.height()
+            )));
+        }
+    }
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .all_channels()
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(Cursor::new(bytes))
+        .map_err(invalid)?;
+    let layer = image.layer_data;
+    let channels = &layer.channel_data.list;
+    let find = |name: &str| channels.iter().position(|c| c.name.eq(name));
+    let selected: Vec<usize> = match (find("R"), find("G"), find("B"), find("Y")) {
+        (Some(r), Some(g), Some(b), _) => [Some(r), Some(g), Some(b), find("A")]
+            .into_iter()
+            .flatten()
+            .collect(),
+        (_, _, _, Some(y)) => vec![y],
+        _ if channels.len() == 1 => vec![0],
+        _ => return Err(invalid("no R, G, B or Y channels")),
+    };
+    if selected.iter().any(|&c| channels[c].sampling != Vec2(1, 1)) {
+        return Err(invalid("subsampled channels are not supported"));
+    }
+    let texels = layer.size.area();
+    let halves: Vec<&Vec<f16>> = selected
+        .iter()
+        .filter_map(|&c| match &channels[c].sample_data {
+            FlatSamples::F16(data) => Some(data),
+            _ => None,
+        })
+        .collect();
+    let samples = if halves.len() == selected.len() {
+        Samples::F16(
+            (0..texels)
+                .flat_map(|i| halves.iter().map(move |data| data[i]))
+                .collect(),
+        )
+    } else {
+        Samples::F32(
+            (0..texels)
+                .flat_map(|i| {
+                    selected
+                        .iter()
+                        .map(move |&c| channels[c].sample_data.value_by_flat_index(i).to_f32())
+                })
+                .collect(),
+        )
+    };
+    Ok(Image {
+        width: layer.size.width() as u32,
+        height: layer.size.height() as u32,
+        channels: selected.len(),
+        samples,
+    })
+}
+
+/// Pixel values written to an EXR file.
+pub(crate) enum ExrPixels {
+    Half(Vec<f16>),
+    Float(Vec<f32>),
+}
+
+/// Encodes a ZIP compressed scanline EXR. One channel is written as `Y`, two
+/// as `R` and `G`, three as `RGB` and four as `RGBA`.
+pub(crate) fn encode(
+    width: u32,
+    height: u32,
+    channels: usize,
+    pixels: &ExrPixels,
+) -> Result<Vec<u8>> {
+    let names = match channels {
+        1 => &["Y"][..],
+        2 => &["R", "G"][..],
+        3 => &["R", "G", "B"][..],
+        _ => &["R", "G", "B", "A"][..],
+    };
+    let list = names.iter().enumerate().map(|(c, &name)| {
+        let samples = match pixels {
+            ExrPixels::Half(data) => {
+                FlatSamples::F16(data.iter().skip(c).step_by(channels).copied().collect())
+            }
+            ExrPixels::Float(data) => {
+                FlatSamples::F32(data.iter().skip(c).step_by(channels).copied().collect())
+            }
+        };
+        AnyChannel::new(name, samples)
+    });
+    let image = exr::image::Image::from_encoded_channels(
+        (width as usize, height as usize),
+        Encoding::SMALL_LOSSLESS,
+        AnyChannels::sort(list.collect()),
+    );
+    let mut out = Cursor::new(Vec::new());
+    image
+        .write()
+        .to_buffered(&mut out)
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Failed to encode EXR: {e}")))?;
+    Ok(out.into_inner())
+}
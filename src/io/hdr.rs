@@ -0,0 +1,10 @@
+//! Radiance HDR decoding through the `image` crate.
+
+use super::Image;
+use image::ImageFormat;
+use napi::Result;
+
+/// Decodes an RGBE encoded Radiance image to linear float RGB samples.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image> {
+    super::decode_image(bytes, ImageFormat::Hdr)
+}
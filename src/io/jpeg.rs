@@ -0,0 +1,37 @@
+//! JPEG decoding and encoding through the `image` crate.
+
+use super::Image;
+use image::codecs::jpeg::JpegEncoder;
+use image::{ExtendedColorType, ImageEncoder, ImageFormat};
+use napi::{Error, Result, Status};
+
+/// Decodes a baseline or progressive JPEG to 8-bit grayscale or RGB samples.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image> {
+    super::decode_image(bytes, ImageFormat::Jpeg)
+}
+
+/// Encodes 8-bit sRGB grayscale or RGB pixels as a baseline JPEG with
+/// `quality` from 1 to 100.
+pub(crate) fn encode(
+    width: u32,
+    height: u32,
+    channels: usize,
+    data: &[u8],
+    quality: u32,
+) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let color = if channels == 1 {
+        ExtendedColorType::L8
+    } else {
+        ExtendedColorType::Rgb8
+    };
+    JpegEncoder::new_with_quality(&mut out, quality.clamp(1, 100) as u8)
+        .write_image(data, width, height, color)
+        .map_err(|e| {
+            Error::new(
+                Status::GenericFailure,
+                format!("Failed to encode JPEG: {e}"),
+            )
+        })?;
+    Ok(out)
+}
@@ -0,0 +1,225 @@
+//! KTX 2.0 container parsing for block-compressed 2D textures.
+
+use super::basis::{self, Codec, UASTC};
+use crate::core::compressed::{BlockFormat, ASTC_BLOCKS};
+use crate::enums::CompressionTextureType;
+use napi::{Error, Result, Status};
+use std::io::Read;
+
+fn invalid(message: &str) -> Error {
+    Error::new(Status::InvalidArg, format!("Invalid KTX2 file: {message}"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// File identifier of KTX 2.0 files.
+pub(crate) const IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_BASIS_LZ: u32 = 1;
+const SUPERCOMPRESSION_ZSTD: u32 = 2;
+const SUPERCOMPRESSION_ZLIB: u32 = 3;
+
+/// Maps a Vulkan format to a block format.
+fn vk_format(format: u32) -> Option<BlockFormat> {
+    let srgb = format.is_multiple_of(2);
+    Some(match format {
+        131 | 132 => BlockFormat::Bc1 {
+            alpha: false,
+            srgb: format == 132,
+        },
+        133 | 134 => BlockFormat::Bc1 {
+            alpha: true,
+            srgb: format == 134,
+        },
+        135 | 136 => BlockFormat::Bc2 { srgb },
+        137 | 138 => BlockFormat::Bc3 { srgb },
+        139 | 140 => BlockFormat::Bc4 { signed: srgb },
+        141 | 142 => BlockFormat::Bc5 { signed: srgb },
+        143 | 144 => BlockFormat::Bc6h { signed: srgb },
+        145 | 146 => BlockFormat::Bc7 { srgb },
+        147 | 148 => BlockFormat::Etc2 { srgb },
+        149 | 150 => BlockFormat::Etc2A1 { srgb },
+        151 | 152 => BlockFormat::Etc2Eac { srgb },
+        153 | 154 => BlockFormat::EacR11 { signed: srgb },
+        155 | 156 => BlockFormat::EacRg11 { signed: srgb },
+        157..=184 => {
+            let (width, height) = ASTC_BLOCKS[(format - 157) as usize / 2];
+            BlockFormat::Astc {
+                width,
+                height,
+                srgb,
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> usize {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap()) as usize
+}
+
+/// KHR data format descriptor color models of Basis Universal payloads.
+const COLOR_MODEL_ETC1S: u8 = 163;
+const COLOR_MODEL_UASTC: u8 = 166;
+
+/// Reads the color model of a Basis Universal texture from its data format
+/// descriptor, and whether it has alpha and sRGB encoded colors.
+fn basis_descriptor(bytes: &[u8]) -> Result<(u8, bool, bool)> {
+    let (offset, length) = (read_u32(bytes, 48) as usize, read_u32(bytes, 52) as usize);
+    // The total size, one descriptor block header and at least one sample.
+    let dfd = offset
+        .checked_add(length)
+        .and_then(|end| bytes.get(offset..end))
+        .filter(|dfd| dfd.len() >= 44)
+        .ok_or_else(|| invalid("truncated data format descriptor"))?;
+    let block_size = u16::from_le_bytes([dfd[10], dfd[11]]) as usize;
+    let samples = block_size.saturating_sub(24) / 16;
+    let (model, srgb, channel) = (dfd[12], dfd[14] == 2, dfd[31] & 15);
+    let alpha = match model {
+        // A second slice holds alpha.
+        COLOR_MODEL_ETC1S => samples > 1,
+        // RGBA or RRRG.
+        COLOR_MODEL_UASTC => channel == 3 || channel == 5,
+        _ => return Err(invalid(&format!("unsupported color model {model}"))),
+    };
+    Ok((model, alpha, srgb))
+}
+
+/// Decompresses a supercompressed mip level, reading no more than the
+/// level needs whatever the stream holds.
+fn inflate(
+    reader: impl Read,
+    level: usize,
+    uncompressed_length: usize,
+    expected: usize,
+) -> Result<Vec<u8>> {
+    if uncompressed_length != expected {
+        return Err(invalid(&format!(
+            "mip level {level} does not inflate to {expected} bytes"
+        )));
+    }
+    let mut inflated = Vec::with_capacity(expected);
+    reader
+        .take(expected as u64)
+        .read_to_end(&mut inflated)
+        .map_err(|e| invalid(&format!("invalid compressed data: {e}")))?;
+    if inflated.len() != expected {
+        return Err(invalid(&format!(
+            "mip level {level} inflates to {} bytes, expected {expected}",
+            inflated.len()
+        )));
+    }
+    Ok(inflated)
+}
+
+/// Parses a KTX2 file into its dimensions, block format and mip levels.
+/// Basis Universal textures are transcoded to the block format of `target`.
+pub(crate) fn parse(
+    bytes: &[u8],
+    target: &CompressionTextureType,
+) -> Result<(u32, u32, BlockFormat, Vec<Vec<u8>>)> {
+    if bytes.len() < 80 {
+        return Err(invalid("truncated header"));
+    }
+    let vk = read_u32(bytes, 12);
+    let width = read_u32(bytes, 20);
+    let height = read_u32(bytes, 24);
+    let depth = read_u32(bytes, 28);
+    let layers = read_u32(bytes, 32);
+    let faces = read_u32(bytes, 36);
+    let level_count = read_u32(bytes, 40).max(1) as usize;
+    let supercompression = read_u32(bytes, 44);
+    if height == 0 || depth > 1 || layers > 1 || faces != 1 {
+        return Err(invalid("only 2D textures are supported"));
+    }
+    let (format, descriptor) = if vk == 0 {
+        let (model, alpha, srgb) = basis_descriptor(bytes)?;
+        let basis_lz = supercompression == SUPERCOMPRESSION_BASIS_LZ;
+        if (model == COLOR_MODEL_ETC1S) != basis_lz {
+            return Err(invalid(
+                "ETC1S textures must use BasisLZ supercompression and UASTC textures must not",
+            ));
+        }
+        (UASTC, Some((model, alpha, srgb)))
+    } else {
+        let format =
+            vk_format(vk).ok_or_else(|| invalid(&format!("unsupported Vulkan format {vk}")))?;
+        (format, None)
+    };
+    if !matches!(
+        supercompression,
+        SUPERCOMPRESSION_NONE
+            | SUPERCOMPRESSION_BASIS_LZ
+            | SUPERCOMPRESSION_ZSTD
+            | SUPERCOMPRESSION_ZLIB
+    ) {
+        return Err(invalid(&format!(
+            "unknown supercompression scheme {supercompression}"
+        )));
+    }
+    if supercompression == SUPERCOMPRESSION_BASIS_LZ && descriptor.is_none() {
+        return Err(invalid(
+            "BasisLZ supercompression requires an ETC1S texture",
+        ));
+    }
+    if level_count > 32 {
+        return Err(invalid("too many mip levels"));
+    }
+    if bytes.len() < 80 + level_count * 24 {
+        return Err(invalid("truncated level index"));
+    }
+    let mut levels = Vec::with_capacity(level_count);
+    for level in 0..level_count {
+        let entry = 80 + level * 24;
+        let (offset, length) = (read_u64(bytes, entry), read_u64(bytes, entry + 8));
+        let data = offset
+            .checked_add(length)
+            .and_then(|end| bytes.get(offset..end))
+            .ok_or_else(|| invalid("truncated image data"))?;
+        let (w, h) = ((width >> level).max(1), (height >> level).max(1));
+        let expected = format
+            .level_size(w, h)
+            .ok_or_else(|| invalid("image is too large"))?;
+        let uncompressed_length = read_u64(bytes, entry + 16);
+        let data = match supercompression {
+            SUPERCOMPRESSION_ZLIB => inflate(
+                flate2::read::ZlibDecoder::new(data),
+                level,
+                uncompressed_length,
+                expected,
+            )?,
+            SUPERCOMPRESSION_ZSTD => inflate(
+                ruzstd::decoding::StreamingDecoder::new(data)
+                    .map_err(|e| invalid(&format!("invalid Zstandard data: {e}")))?,
+                level,
+                uncompressed_length,
+                expected,
+            )?,
+            _ => data.to_vec(),
+        };
+        levels.push(data);
+    }
+    match descriptor {
+        Some((model, alpha, srgb)) => {
+            let codec = if model == COLOR_MODEL_ETC1S {
+                let (offset, length) = (read_u64(bytes, 64), read_u64(bytes, 72));
+                let global = offset
+                    .checked_add(length)
+                    .and_then(|end| bytes.get(offset..end))
+                    .ok_or_else(|| invalid("truncated supercompression global data"))?;
+                Codec::Etc1s { global }
+            } else {
+                Codec::Uastc
+            };
+            let (format, levels) =
+                basis::transcode(width, height, codec, &levels, alpha, srgb, target)?;
+            Ok((width, height, format, levels))
+        }
+        None => Ok((width, height, format, levels)),
+    }
+}
@@ -0,0 +1,548 @@
+//! Image file decoding and encoding, and model loading.
+
+mod basis;
+mod dds;
+mod etc;
+mod exr;
+mod hdr;
+mod jpeg;
+mod ktx2;
+mod model;
+pub(crate) mod png;
+
+use crate::core::compressed::{BlockFormat, CpuCompressedTexture};
+use crate::core::texture::{
+    color_format, f16_data, f32_data, format_of, texture_bytes, u8_data, Component, CpuTexture,
+    TextureOptions,
+};
+use crate::enums::{CompressionTextureType, ImageFormat};
+use napi::bindgen_prelude::{Buffer, Either};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use three_d::{f16, TextureData};
+
+pub use model::{load_model, load_model_async};
+
+/// Decoded pixel samples, interleaved and top row first.
+pub(crate) enum Samples {
+    /// 8-bit sRGB encoded samples.
+    U8(Vec<u8>),
+    /// 16-bit sRGB encoded samples.
+    U16(Vec<u16>),
+    /// Linear half-float samples.
+    F16(Vec<f16>),
+    /// Linear float samples.
+    F32(Vec<f32>),
+}
+
+/// A decoded image.
+pub(crate) struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub channels: usize,
+    pub samples: Samples,
+}
+
+impl Image {
+    /// Converts the image into texture data. Integer images stay sRGB
+    /// encoded 8-bit data unless `linear` is set, in which case they are
+    /// decoded to linear half floats. Alpha is never converted.
+    pub(crate) fn into_texture_data(self, linear: bool) -> TextureData {
+        let channels = self.channels;
+        let to_linear = |i: usize, value: f32| {
+            if is_alpha(channels, i % channels) {
+                f16::from_f32(value)
+            } else {
+                f16::from_f32(srgb_to_linear(value))
+            }
+        };
+        match self.samples {
+            Samples::U8(data) if !linear => u8_data(channels, &data),
+            Samples::U8(data) => {
+                let data: Vec<f16> = data
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| to_linear(i, v as f32 / 255.0))
+                    .collect();
+                f16_data(channels, &data)
+            }
+            Samples::U16(data) if !linear => {
+                let data: Vec<u8> = data
+                    .iter()
+                    .map(|&v| ((v as u32 * 255 + 32767) / 65535) as u8)
+                    .collect();
+                u8_data(channels, &data)
+            }
+            Samples::U16(data) => {
+                let data: Vec<f16> = data
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &v)| to_linear(i, v as f32 / 65535.0))
+                    .collect();
+                f16_data(channels, &data)
+            }
+            Samples::F16(data) => f16_data(channels, &data),
+            Samples::F32(data) => f32_data(channels, &data),
+        }
+    }
+
+    /// Reverses the order of the rows.
+    pub(crate) fn flip_rows(&mut self) {
+        fn flip<T>(data: &mut [T], row: usize) {
+            let rows = data.len() / row;
+            for y in 0..rows / 2 {
+                let (top, bottom) = data.split_at_mut((rows - 1 - y) * row);
+                top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+            }
+        }
+        let row = self.width as usize * self.channels;
+        match &mut self.samples {
+            Samples::U8(data) => flip(data, row),
+            Samples::U16(data) => flip(data, row),
+            Samples::F16(data) => flip(data, row),
+            Samples::F32(data) => flip(data, row),
+        }
+    }
+}
+
+/// Whether `channel` holds alpha in an image with `channels` channels.
+fn is_alpha(channels: usize, channel: usize) -> bool {
+    (channels == 2 && channel == 1) || (channels == 4 && channel == 3)
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Largest decoded image accepted from a file, in bytes, matching the
+/// `image` crate's default allocation limit.
+pub(crate) const MAX_DECODED_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Decodes `bytes` with the `image` crate, which three-d-asset also decodes
+/// images with. Its own loader panics on 16-bit and RGBA float images, so
+/// the decoded image is converted here instead.
+fn decode_image(bytes: &[u8], format: image::ImageFormat) -> Result<Image> {
+    let mut reader = image::ImageReader::with_format(std::io::Cursor::new(bytes), format);
+    let mut limits = image::Limits::default();
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+    reader.limits(limits);
+    let decoded = reader
+        .decode()
+        .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid {format:?} image: {e}")))?;
+    let (width, height) = (decoded.width(), decoded.height());
+    let channels = decoded.color().channel_count() as usize;
+    let samples = match decoded {
+        image::DynamicImage::ImageLuma8(image) => Samples::U8(image.into_raw()),
+        image::DynamicImage::ImageLumaA8(image) => Samples::U8(image.into_raw()),
+        image::DynamicImage::ImageRgb8(image) => Samples::U8(image.into_raw()),
+        image::DynamicImage::ImageRgba8(image) => Samples::U8(image.into_raw()),
+        image::DynamicImage::ImageLuma16(image) => Samples::U16(image.into_raw()),
+        image::DynamicImage::ImageLumaA16(image) => Samples::U16(image.into_raw()),
+        image::DynamicImage::ImageRgb16(image) => Samples::U16(image.into_raw()),
+        image::DynamicImage::ImageRgba16(image) => Samples::U16(image.into_raw()),
+        image::DynamicImage::ImageRgb32F(image) => Samples::F32(image.into_raw()),
+        image::DynamicImage::ImageRgba32F(image) => Samples::F32(image.into_raw()),
+        other => {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Unsupported {format:?} pixel type {:?}", other.color()),
+            ))
+        }
+    };
+    Ok(Image {
+        width,
+        height,
+        channels,
+        samples,
+    })
+}
+
+/// Detects the image format from its signature and decodes it.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png::decode(bytes)
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        jpeg::decode(bytes)
+    } else if bytes.starts_with(b"#?") {
+        hdr::decode(bytes)
+    } else if bytes.starts_with(&[0x76, 0x2f, 0x31, 0x01]) {
+        exr::decode(bytes)
+    } else {
+        Err(Error::new(
+            Status::InvalidArg,
+            "Unrecognized image format, expected PNG, JPEG, HDR or EXR",
+        ))
+    }
+}
+
+/// Decodes one mip level of block-compressed data on the CPU. Rows stay in
+/// the order they are stored in. BC6H decodes to linear half floats and
+/// ASTC, LDR blocks only, to 8-bit RGBA.
+pub(crate) fn decompress(
+    format: BlockFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<Image> {
+    let (channels, signed) = match format {
+        BlockFormat::Bc1 { alpha: false, .. }
+        | BlockFormat::Etc2 { .. }
+        | BlockFormat::Bc6h { .. } => (3, false),
+        BlockFormat::Bc4 { signed } | BlockFormat::EacR11 { signed } => (1, signed),
+        BlockFormat::Bc5 { signed } | BlockFormat::EacRg11 { signed } => (2, signed),
+        _ => (4, false),
+    };
+    let hdr = matches!(format, BlockFormat::Bc6h { .. });
+    let (w, h) = (width as usize, height as usize);
+    let (block_width, block_height) = format.block_size();
+    let (bw, bh) = (block_width as usize, block_height as usize);
+    let block_bytes = format.block_bytes();
+    // One decoded 4x4 block, `stride` values per texel.
+    let stride = match format {
+        BlockFormat::Bc4 { .. } => 1,
+        BlockFormat::Bc5 { .. } => 2,
+        BlockFormat::Bc6h { .. } => 3,
+        _ => 4,
+    };
+    let mut texels = [0u8; 64];
+    let mut values = [0.0f32; 32];
+    let mut rgb = [0u16; 48];
+    let mut etc = [0u32; 16];
+    let mut bytes = vec![0u8; if hdr || signed { 0 } else { w * h * channels }];
+    let mut floats = vec![0.0f32; if signed { w * h * channels } else { 0 }];
+    let mut halves = vec![0u16; if hdr { w * h * channels } else { 0 }];
+    for (i, block) in data.chunks_exact(block_bytes).enumerate() {
+        let (bx, by) = (i % w.div_ceil(bw) * bw, i / w.div_ceil(bw) * bh);
+        let pitch = 4 * stride;
+        match format {
+            BlockFormat::Bc1 { .. } => bcdec_rs::bc1(block, &mut texels, pitch),
+            BlockFormat::Bc2 { .. } => bcdec_rs::bc2(block, &mut texels, pitch),
+            BlockFormat::Bc3 { .. } => bcdec_rs::bc3(block, &mut texels, pitch),
+            BlockFormat::Bc4 { signed: true } => bcdec_rs::bc4_float(block, &mut values, 4, true),
+            BlockFormat::Bc4 { .. } => bcdec_rs::bc4(block, &mut texels, pitch, false),
+            BlockFormat::Bc5 { signed: true } => bcdec_rs::bc5_float(block, &mut values, 8, true),
+            BlockFormat::Bc5 { .. } => bcdec_rs::bc5(block, &mut texels, pitch, false),
+            BlockFormat::Bc6h { signed } => bcdec_rs::bc6h_half(block, &mut rgb, pitch, signed),
+            BlockFormat::Bc7 { .. } => bcdec_rs::bc7(block, &mut texels, pitch),
+            BlockFormat::EacR11 { signed } | BlockFormat::EacRg11 { signed } => {
+                for c in 0..channels {
+                    let decoded = etc::eac_r11(&block[c * 8..], signed);
+                    for (t, value) in decoded.into_iter().enumerate() {
+                        values[t * 2 + c] = value;
+                        texels[t * 4 + c] = (value * 255.0).round() as u8;
+                    }
+                }
+            }
+            BlockFormat::Etc2 { .. } | BlockFormat::Etc2A1 { .. } | BlockFormat::Etc2Eac { .. } => {
+                match format {
+                    BlockFormat::Etc2 { .. } => {
+                        texture2ddecoder::decode_etc2_rgb_block(block, &mut etc)
+                    }
+                    BlockFormat::Etc2A1 { .. } => {
+                        texture2ddecoder::decode_etc2_rgba1_block(block, &mut etc)
+                    }
+                    _ => texture2ddecoder::decode_etc2_rgba8_block(block, &mut etc),
+                }
+                // texture2ddecoder packs texels as BGRA.
+                for (texel, packed) in texels.chunks_exact_mut(4).zip(etc) {
+                    let [b, g, r, a] = packed.to_le_bytes();
+                    // Punch-through texels are transparent black, but
+                    // texture2ddecoder keeps their color.
+                    let punched = a == 0 && matches!(format, BlockFormat::Etc2A1 { .. });
+                    let rgb = if punched { [0; 3] } else { [r, g, b] };
+                    texel.copy_from_slice(&[rgb[0], rgb[1], rgb[2], a]);
+                }
+            }
+            BlockFormat::Astc { .. } => {
+                // ASTC blocks are up to 12x12 texels, so they are written
+                // straight to the image. Invalid and HDR blocks decode to
+                // magenta.
+                let mut astc = [0u8; 16];
+                astc.copy_from_slice(block);
+                let footprint = astc_decode::Footprint::new(block_width, block_height);
+                astc_decode::astc_decode_block(&astc, footprint, |x, y, texel| {
+                    let (x, y) = (bx + x as usize, by + y as usize);
+                    if x < w && y < h {
+                        bytes[(y * w + x) * 4..][..4].copy_from_slice(&texel);
+                    }
+                });
+                continue;
+            }
+        }
+        // Signed values are stored one per texel for BC4, two for BC5 and EAC.
+        let value_stride = if matches!(format, BlockFormat::Bc4 { .. }) {
+            1
+        } else {
+            2
+        };
+        for t in 0..16 {
+            let (x, y) = (bx + t % 4, by + t / 4);
+            if x >= w || y >= h {
+                continue;
+            }
+            let pixel = (y * w + x) * channels;
+            for c in 0..channels {
+                if hdr {
+                    halves[pixel + c] = rgb[t * stride + c];
+                } else if signed {
+                    floats[pixel + c] = values[t * value_stride + c];
+                } else {
+                    bytes[pixel + c] = texels[t * stride + c];
+                }
+            }
+        }
+    }
+    let samples = if hdr {
+        Samples::F16(halves.into_iter().map(f16::from_bits).collect())
+    } else if signed {
+        Samples::F32(floats)
+    } else {
+        Samples::U8(bytes)
+    };
+    Ok(Image {
+        width,
+        height,
+        channels,
+        samples,
+    })
+}
+
+/// Returns the channel count and samples of texture data as linear floats.
+fn linear_samples(data: &TextureData) -> Result<(usize, Component, Vec<f32>)> {
+    let (channels, component) = color_format(&format_of(data))?;
+    let bytes = texture_bytes(data);
+    let values = match component {
+        Component::U8 => bytes
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let value = v as f32 / 255.0;
+                if is_alpha(channels, i % channels) {
+                    value
+                } else {
+                    srgb_to_linear(value)
+                }
+            })
+            .collect(),
+        Component::F16 => bytes
+            .chunks_exact(2)
+            .map(|b| f16::from_bits(u16::from_ne_bytes([b[0], b[1]])).to_f32())
+            .collect(),
+        Component::F32 => bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    };
+    Ok((channels, component, values))
+}
+
+/// Returns texture data as 8-bit sRGB encoded samples. Float data is
+/// treated as linear, clamped and encoded; 8-bit data is copied as is.
+fn srgb_samples(data: &TextureData) -> Result<(usize, Vec<u8>)> {
+    let (channels, component) = color_format(&format_of(data))?;
+    if component == Component::U8 {
+        return Ok((channels, texture_bytes(data)));
+    }
+    let (_, _, values) = linear_samples(data)?;
+    let bytes = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let value = if is_alpha(channels, i % channels) {
+                v
+            } else {
+                linear_to_srgb(v.max(0.0))
+            };
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect();
+    Ok((channels, bytes))
+}
+
+/// Options for [`load_texture`].
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct LoadTextureOptions {
+    /// Decodes 8- and 16-bit sRGB images to linear half floats instead of
+    /// keeping 8-bit sRGB data, e.g. for images used as data rather than color.
+    /// Defaults to `false`. HDR and EXR images are always linear.
+    pub linear: Option<bool>,
+    /// Sampling options of the texture. The name defaults to the file path.
+    pub texture: Option<TextureOptions>,
+}
+
+/// Loads a PNG, JPEG, Radiance HDR or OpenEXR image from a file path or an
+/// in-memory buffer.
+///
+/// PNG and JPEG images become 8-bit sRGB encoded textures (`R8`, `R8G8`,
+/// `R8G8B8` or `R8G8B8A8`), which is what three-d expects for color textures.
+/// HDR images become linear `R32G32B32F` textures and EXR images linear
+/// half or float textures matching their channel type.
+#[napi]
+pub fn load_texture(
+    source: Either<String, Buffer>,
+    options: Option<LoadTextureOptions>,
+) -> Result<CpuTexture> {
+    let options = options.unwrap_or_default();
+    let mut texture = options.texture.unwrap_or_default();
+    let image = match &source {
+        Either::A(path) => {
+            let bytes = std::fs::read(path).map_err(|e| {
+                Error::new(
+                    Status::GenericFailure,
+                    format!("Failed to read {path}: {e}"),
+                )
+            })?;
+            texture.name.get_or_insert_with(|| path.clone());
+            decode(&bytes)?
+        }
+        Either::B(bytes) => decode(bytes)?,
+    };
+    let (width, height) = (image.width, image.height);
+    let data = image.into_texture_data(options.linear.unwrap_or(false));
+    CpuTexture::from_data(width, height, data, texture)
+}
+
+/// Like [`load_texture`], but reads and decodes the image on a worker thread
+/// and resolves with the CPU texture. Upload it with `Texture2D` on the
+/// context's thread.
+#[napi]
+pub async fn load_texture_async(
+    source: Either<String, Buffer>,
+    options: Option<LoadTextureOptions>,
+) -> Result<CpuTexture> {
+    blocking(move || load_texture(source, options)).await
+}
+
+/// Encodes a CPU texture as an image file.
+///
+/// PNG and JPEG output is 8-bit sRGB: 8-bit textures are written as is and
+/// float textures are treated as linear and encoded. JPEG drops alpha, writes
+/// one and two channel textures as grayscale from their first channel, and
+/// uses `quality` from 1 to 100 (default 90). EXR output is linear: float
+/// textures keep their precision and 8-bit textures are decoded from sRGB to
+/// half floats.
+#[napi]
+pub fn encode_image(
+    texture: &CpuTexture,
+    format: ImageFormat,
+    quality: Option<u32>,
+) -> Result<Buffer> {
+    let (width, height, data) = (
+        texture.inner.width,
+        texture.inner.height,
+        &texture.inner.data,
+    );
+    if width == 0 || height == 0 || (format == ImageFormat::Jpeg && width.max(height) > 65535) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("Cannot encode a {width}x{height} image as {format:?}"),
+        ));
+    }
+    let bytes = match format {
+        ImageFormat::Png => {
+            let (channels, samples) = srgb_samples(data)?;
+            png::encode(width, height, channels, &samples)?
+        }
+        ImageFormat::Jpeg => {
+            let (channels, samples) = srgb_samples(data)?;
+            let (output, samples) = match channels {
+                3 => (3, samples),
+                1 => (1, samples),
+                2 => (1, samples.iter().step_by(2).copied().collect()),
+                _ => (
+                    3,
+                    samples
+                        .chunks_exact(4)
+                        .flat_map(|p| [p[0], p[1], p[2]])
+                        .collect(),
+                ),
+            };
+            jpeg::encode(width, height, output, &samples, quality.unwrap_or(90))?
+        }
+        ImageFormat::Exr => {
+            let (channels, component, values) = linear_samples(data)?;
+            let pixels = match component {
+                Component::F32 => exr::ExrPixels::Float(values),
+                _ => exr::ExrPixels::Half(values.into_iter().map(f16::from_f32).collect()),
+            };
+            exr::encode(width, height, channels, &pixels)?
+        }
+    };
+    Ok(bytes.into())
+}
+
+/// Loads a block-compressed 2D texture with its mip chain from a KTX2 or DDS
+/// file path or in-memory buffer.
+///
+/// Supported formats are BC1 to BC7, ETC2, EAC and ASTC, with zlib or
+/// Zstandard supercompression in KTX2 files. Basis Universal textures, ETC1S
+/// and UASTC, are transcoded to `transcodeTarget`, BPTC by default. Upload
+/// the result with `CompressedTexture2D`, which decodes it on the CPU when
+/// the context lacks the format.
+#[napi]
+pub fn load_compressed_texture(
+    source: Either<String, Buffer>,
+    options: Option<TextureOptions>,
+    transcode_target: Option<CompressionTextureType>,
+) -> Result<CpuCompressedTexture> {
+    let mut options = options.unwrap_or_default();
+    let bytes = match &source {
+        Either::A(path) => {
+            options.name.get_or_insert_with(|| path.clone());
+            std::fs::read(path).map_err(|e| {
+                Error::new(
+                    Status::GenericFailure,
+                    format!("Failed to read {path}: {e}"),
+                )
+            })?
+        }
+        Either::B(bytes) => bytes.to_vec(),
+    };
+    let (width, height, format, levels) = if bytes.starts_with(&ktx2::IDENTIFIER) {
+        ktx2::parse(
+            &bytes,
+            &transcode_target.unwrap_or(CompressionTextureType::BPTC),
+        )?
+    } else if bytes.starts_with(b"DDS ") {
+        dds::parse(&bytes)?
+    } else {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "Unrecognized texture container, expected KTX2 or DDS",
+        ));
+    };
+    CpuCompressedTexture::new(width, height, format, levels, options)
+}
+
+/// Like [`load_compressed_texture`], but reads and parses the file on a
+/// worker thread and resolves with the CPU texture. Upload it with
+/// `CompressedTexture2D` on the context's thread.
+#[napi]
+pub async fn load_compressed_texture_async(
+    source: Either<String, Buffer>,
+    options: Option<TextureOptions>,
+    transcode_target: Option<CompressionTextureType>,
+) -> Result<CpuCompressedTexture> {
+    blocking(move || load_compressed_texture(source, options, transcode_target)).await
+}
+
+/// Runs CPU-bound decoding on tokio's blocking thread pool, keeping both the
+/// JS thread and the async runtime's workers free.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Error::new(Status::GenericFailure, format!("Decoding failed: {e}")))?
+}
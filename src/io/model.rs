@@ -0,0 +1,84 @@
+//! glTF and OBJ model loading through `three-d-asset`.
+
+use super::blocking;
+use crate::renderer::object::CpuModel;
+use napi::bindgen_prelude::{Buffer, Either};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::path::Path;
+use three_d_asset::io::RawAssets;
+
+fn invalid(message: impl std::fmt::Display) -> Error {
+    Error::new(Status::InvalidArg, format!("Invalid model: {message}"))
+}
+
+/// Rejects glTF documents that `three-d-asset` would panic on instead of
+/// returning an error: ones without a scene, and images stored in strided
+/// buffer views.
+fn check_gltf(bytes: &[u8]) -> Result<()> {
+    // three-d-asset 0.9 calls `document.scenes().nth(0).unwrap()` in
+    // `gltf::deserialize_gltf` and `unimplemented!()` for a strided image
+    // view in `gltf::parse_texture`, which abort the process with
+    // `panic = "abort"`. Remove this check once both return errors upstream.
+
+    // Unparsable documents are reported by three-d-asset.
+    let Ok(gltf) = gltf::Gltf::from_slice(bytes) else {
+        return Ok(());
+    };
+    if gltf.document.scenes().next().is_none() {
+        return Err(invalid("the glTF file has no scene"));
+    }
+    let strided = gltf.document.images().any(|image| match image.source() {
+        gltf::image::Source::View { view, .. } => view.stride().is_some(),
+        gltf::image::Source::Uri { .. } => false,
+    });
+    if strided {
+        return Err(invalid(
+            "glTF images must not be stored in strided buffer views",
+        ));
+    }
+    Ok(())
+}
+
+/// Loads a glTF or OBJ model from a file path, with the buffers, materials
+/// and textures it references, or a binary glTF (`.glb`) model from an
+/// in-memory buffer.
+#[napi]
+pub fn load_model(source: Either<String, Buffer>) -> Result<CpuModel> {
+    let (mut assets, path) = match &source {
+        Either::A(path) => {
+            let assets = three_d_asset::io::load(&[path]).map_err(|e| {
+                Error::new(
+                    Status::GenericFailure,
+                    format!("Failed to read {path}: {e}"),
+                )
+            })?;
+            (assets, path.as_str())
+        }
+        Either::B(bytes) => {
+            if !bytes.starts_with(b"glTF") {
+                return Err(invalid("in-memory models must be binary glTF"));
+            }
+            let mut assets = RawAssets::new();
+            assets.insert("model.glb", bytes.to_vec());
+            (assets, "model.glb")
+        }
+    };
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    if matches!(extension.as_deref(), Some("gltf" | "glb")) {
+        check_gltf(assets.get(path).map_err(invalid)?)?;
+    }
+    let model: three_d::CpuModel = assets.deserialize(path).map_err(invalid)?;
+    Ok(CpuModel { inner: model })
+}
+
+/// Like [`load_model`], but reads and parses the files on a worker thread and
+/// resolves with the CPU model. Upload it with `Model` on the context's
+/// thread.
+#[napi]
+pub async fn load_model_async(source: Either<String, Buffer>) -> Result<CpuModel> {
+    blocking(move || load_model(source)).await
+}
@@ -0,0 +1,55 @@
+//! PNG decoding and encoding through the `png` crate.
+
+use super::{Image, Samples};
+use napi::{Error, Result, Status};
+
+fn error(error: impl std::fmt::Display) -> Error {
+    Error::new(Status::InvalidArg, format!("Invalid PNG: {error}"))
+}
+
+/// Decodes a PNG, expanding palettes and low bit depths to 8 bits per
+/// sample. 16-bit images keep their full precision.
+pub(crate) fn decode(bytes: &[u8]) -> Result<Image> {
+    let limits = png::Limits {
+        bytes: super::MAX_DECODED_BYTES as usize,
+    };
+    let mut decoder = png::Decoder::new_with_limits(bytes, limits);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(error)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(error)?;
+    buffer.truncate(info.buffer_size());
+    let samples = match info.bit_depth {
+        png::BitDepth::Sixteen => Samples::U16(
+            buffer
+                .chunks_exact(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]))
+                .collect(),
+        ),
+        _ => Samples::U8(buffer),
+    };
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        channels: info.color_type.samples(),
+        samples,
+    })
+}
+
+/// Encodes 8-bit sRGB pixels with one to four channels as a PNG.
+pub(crate) fn encode(width: u32, height: u32, channels: usize, data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width, height);
+    encoder.set_color(match channels {
+        1 => png::ColorType::Grayscale,
+        2 => png::ColorType::GrayscaleAlpha,
+        3 => png::ColorType::Rgb,
+        _ => png::ColorType::Rgba,
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let mut writer = encoder.write_header().map_err(error)?;
+    writer.write_image_data(data).map_err(error)?;
+    writer.finish().map_err(error)?;
+    Ok(out)
+}
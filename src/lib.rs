@@ -1,6 +1,7 @@
 pub mod context;
 pub mod core;
 pub mod enums;
+pub mod io;
 pub mod prelude;
 pub mod renderer;
 pub mod types;
@@ -1,10 +1,12 @@
+use napi::bindgen_prelude::{Float32Array, Uint16Array};
 use napi_derive::napi;
 use three_d;
 use three_d::{
-    Deg as ThreeDDeg, Matrix2 as ThreeDMatrix2, Matrix3 as ThreeDMatrix3, Matrix4 as ThreeDMatrix4,
-    Point2 as ThreeDPoint2, Point3 as ThreeDPoint3, Quaternion as ThreeDQuaternion,
-    Rad as ThreeDRad, SquareMatrix, Srgba as ThreeDSrgba, Vector2 as ThreeDVector2,
-    Vector3 as ThreeDVector3, Vector4 as ThreeDVector4,
+    f16, AxisAlignedBoundingBox as ThreeDAabb, Deg as ThreeDDeg, Matrix2 as ThreeDMatrix2,
+    Matrix3 as ThreeDMatrix3, Matrix4 as ThreeDMatrix4, Point2 as ThreeDPoint2,
+    Point3 as ThreeDPoint3, Quaternion as ThreeDQuaternion, Rad as ThreeDRad, SquareMatrix,
+    Srgba as ThreeDSrgba, Vector2 as ThreeDVector2, Vector3 as ThreeDVector3,
+    Vector4 as ThreeDVector4,
 };
 
 // Import N-API types (renamed to avoid conflicts with three_d types)
@@ -728,18 +730,149 @@ impl From<&NQuaternion> for ThreeDQuaternion<f32> {
     }
 }
 
-/// Represents a 24-bit half-precision floating point number.
+/// An axis aligned bounding box, used for culling, picking and framing.
 #[napi]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
+pub struct AxisAlignedBoundingBox {
+    pub(crate) inner: ThreeDAabb,
+}
+
+#[napi]
+impl AxisAlignedBoundingBox {
+    /// Creates the smallest box containing both corners, so the order of the
+    /// coordinates within each axis does not matter.
+    #[napi(constructor)]
+    pub fn new(min_x: f64, min_y: f64, min_z: f64, max_x: f64, max_y: f64, max_z: f64) -> Self {
+        let corners = [
+            three_d::vec3(min_x as f32, min_y as f32, min_z as f32),
+            three_d::vec3(max_x as f32, max_y as f32, max_z as f32),
+        ];
+        AxisAlignedBoundingBox {
+            inner: ThreeDAabb::new_with_positions(&corners),
+        }
+    }
+
+    /// Creates a box containing nothing, which grows to fit the first box
+    /// merged into it.
+    #[napi(factory)]
+    pub fn empty() -> Self {
+        AxisAlignedBoundingBox {
+            inner: ThreeDAabb::EMPTY,
+        }
+    }
+
+    #[napi]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the minimum corner as `[x, y, z]`.
+    #[napi]
+    pub fn min(&self) -> Vec<f64> {
+        vec3_to_array(self.inner.min())
+    }
+
+    /// Returns the maximum corner as `[x, y, z]`.
+    #[napi]
+    pub fn max(&self) -> Vec<f64> {
+        vec3_to_array(self.inner.max())
+    }
+
+    #[napi]
+    pub fn center(&self) -> Vec<f64> {
+        vec3_to_array(self.inner.center())
+    }
+
+    /// Returns the extent along each axis.
+    #[napi]
+    pub fn size(&self) -> Vec<f64> {
+        vec3_to_array(self.inner.size())
+    }
+
+    /// Returns whether the point lies inside or on the boundary of the box.
+    #[napi]
+    pub fn contains(&self, x: f64, y: f64, z: f64) -> bool {
+        self.inner
+            .is_inside(three_d::vec3(x as f32, y as f32, z as f32))
+    }
+
+    /// Returns the smallest box containing both boxes.
+    #[napi]
+    pub fn merge(&self, other: &AxisAlignedBoundingBox) -> Self {
+        let mut inner = self.inner;
+        inner.expand_with_aabb(other.inner);
+        AxisAlignedBoundingBox { inner }
+    }
+}
+
+fn vec3_to_array(v: ThreeDVector3<f32>) -> Vec<f64> {
+    vec![v.x as f64, v.y as f64, v.z as f64]
+}
+
+/// Represents an IEEE 754 binary16 half-precision floating point number.
+/// The value is stored as its 16-bit pattern, so assigning a number rounds
+/// it to the nearest representable half.
+#[napi]
+#[derive(Debug, Clone, Copy)]
 pub struct NF16 {
-    pub value: f64,
+    bits: u16,
 }
 
 #[napi]
 impl NF16 {
     #[napi(constructor)]
     pub fn new(value: f64) -> Self {
-        NF16 { value }
+        NF16 {
+            bits: f16::from_f64(value).to_bits(),
+        }
+    }
+
+    /// Creates a half from its 16-bit pattern.
+    #[napi(factory)]
+    pub fn from_bits(bits: u32) -> napi::Result<Self> {
+        let bits = u16::try_from(bits).map_err(|_| {
+            napi::Error::new(
+                napi::Status::InvalidArg,
+                format!("{bits:#x} is not a 16-bit pattern"),
+            )
+        })?;
+        Ok(NF16 { bits })
+    }
+
+    /// Returns the 16-bit pattern.
+    #[napi]
+    pub fn to_bits(&self) -> u16 {
+        self.bits
+    }
+
+    #[napi(getter)]
+    pub fn value(&self) -> f64 {
+        f16::from_bits(self.bits).to_f64()
+    }
+
+    #[napi(setter)]
+    pub fn set_value(&mut self, value: f64) {
+        self.bits = f16::from_f64(value).to_bits();
+    }
+
+    /// Converts floats to half bit patterns, rounding to nearest even.
+    #[napi]
+    pub fn from_float32_array(values: Float32Array) -> Uint16Array {
+        let bits: Vec<u16> = values.iter().map(|&v| f16::from_f32(v).to_bits()).collect();
+        Uint16Array::new(bits)
+    }
+
+    /// Converts half bit patterns to floats. The conversion is exact.
+    #[napi]
+    pub fn to_float32_array(bits: Uint16Array) -> Float32Array {
+        let values: Vec<f32> = bits.iter().map(|&b| f16::from_bits(b).to_f32()).collect();
+        Float32Array::new(values)
+    }
+}
+
+impl From<&NF16> for f16 {
+    fn from(h: &NF16) -> Self {
+        f16::from_bits(h.bits)
     }
 }
 
@@ -0,0 +1,647 @@
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use super::camera_path::CameraPath;
+use crate::core::viewport::Viewport;
+use crate::enums::{ColorMapping, ToneMapping};
+use crate::prelude::{AxisAlignedBoundingBox, Matrix4};
+
+#[derive(Debug, Clone, Copy)]
+enum Projection {
+    /// Vertical field of view in degrees.
+    Perspective(f32),
+    /// Height of the view volume per unit of distance to the target.
+    Orthographic(f32),
+}
+
+/// A ray in world space with a normalized direction.
+#[napi(object)]
+pub struct Ray {
+    pub origin: Vec<f64>,
+    pub direction: Vec<f64>,
+}
+
+/// The six clip planes of a camera, in the order left, right, bottom, top,
+/// near and far. Each plane is `[a, b, c, d]` with a unit normal pointing
+/// into the frustum, so `a*x + b*y + c*z + d` is the signed distance of a
+/// point from it.
+#[napi]
+pub struct Frustum {
+    inner: three_d::Frustum,
+    planes: [three_d::Vec4; 6],
+}
+
+#[napi]
+impl Frustum {
+    #[napi]
+    pub fn planes(&self) -> Vec<Vec<f64>> {
+        self.planes
+            .iter()
+            .map(|p| vec![p.x as f64, p.y as f64, p.z as f64, p.w as f64])
+            .collect()
+    }
+
+    /// Returns false only if the whole box is outside one of the planes, so
+    /// some boxes near the corners of the frustum are kept even though they
+    /// are not visible.
+    #[napi]
+    pub fn intersects_aabb(&self, aabb: &AxisAlignedBoundingBox) -> bool {
+        self.inner.contains(aabb.inner)
+    }
+
+    #[napi]
+    pub fn contains_point(&self, x: f64, y: f64, z: f64) -> bool {
+        use three_d::InnerSpace;
+        let point = three_d::vec4(x as f32, y as f32, z as f32, 1.0);
+        self.planes.iter().all(|plane| plane.dot(point) >= 0.0)
+    }
+}
+
+impl Frustum {
+    fn new(view_projection: three_d::Mat4) -> Self {
+        use three_d::InnerSpace;
+        let m = view_projection;
+        let row = |i: usize| three_d::vec4(m.x[i], m.y[i], m.z[i], m.w[i]);
+        let planes = [
+            row(3) + row(0),
+            row(3) - row(0),
+            row(3) + row(1),
+            row(3) - row(1),
+            row(3) + row(2),
+            row(3) - row(2),
+        ]
+        .map(|p| p / p.truncate().magnitude());
+        Frustum {
+            inner: three_d::Frustum::new(view_projection),
+            planes,
+        }
+    }
+}
+
+// Wrapper for Camera
+#[napi]
+#[derive(Clone)]
+pub struct Camera {
+    pub(crate) inner: three_d::Camera,
+    projection: Projection,
+    output: Output,
+}
+
+/// How a camera maps the HDR colors of lit materials to the final image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Output {
+    pub(crate) tone_mapping: ToneMapping,
+    pub(crate) color_mapping: ColorMapping,
+    pub(crate) exposure: f32,
+}
+
+impl Default for Output {
+    /// ACES filmic tone mapping to sRGB without exposure adjustment, as in
+    /// three-d.
+    fn default() -> Self {
+        Output {
+            tone_mapping: ToneMapping::ACESFilmic,
+            color_mapping: ColorMapping::Srgb,
+            exposure: 1.0,
+        }
+    }
+}
+
+impl Output {
+    /// The equivalent three-d settings, or `None` if the operator or the
+    /// exposure has to be applied in a separate pass.
+    pub(crate) fn native(&self) -> Option<(three_d::ToneMapping, three_d::ColorMapping)> {
+        let tone_mapping = match self.tone_mapping {
+            ToneMapping::None => three_d::ToneMapping::None,
+            ToneMapping::Reinhard => three_d::ToneMapping::Reinhard,
+            ToneMapping::ACESFilmic => three_d::ToneMapping::Aces,
+            ToneMapping::Filmic => three_d::ToneMapping::Filmic,
+            ToneMapping::Lottes | ToneMapping::Uchimura | ToneMapping::Unreal => return None,
+        };
+        (self.exposure == 1.0).then_some((tone_mapping, self.color_mapping.into()))
+    }
+}
+
+impl From<ColorMapping> for three_d::ColorMapping {
+    fn from(mapping: ColorMapping) -> Self {
+        match mapping {
+            ColorMapping::Linear => three_d::ColorMapping::None,
+            ColorMapping::Srgb => three_d::ColorMapping::ComputeToSrgb,
+        }
+    }
+}
+
+#[napi]
+impl Camera {
+    /// Creates a perspective camera at the given position looking at `target`,
+    /// with a vertical field of view in degrees and near and far clip planes.
+    /// The viewport is 1x1 until `setViewport` or `Renderer.render` sets it,
+    /// so call one of them before mapping between pixels and the world.
+    #[napi(constructor)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        px: f64,
+        py: f64,
+        pz: f64,
+        tx: f64,
+        ty: f64,
+        tz: f64,
+        ux: f64,
+        uy: f64,
+        uz: f64,
+        fov: f64,
+        near: f64,
+        far: f64,
+    ) -> Result<Self> {
+        let (position, target, up) = view_vectors([px, py, pz], [tx, ty, tz], [ux, uy, uz])?;
+        check_fov(fov)?;
+        check_perspective_planes(near, far)?;
+        Ok(Camera {
+            inner: three_d::Camera::new_perspective(
+                three_d::Viewport::new_at_origo(1, 1),
+                position,
+                target,
+                up,
+                three_d::degrees(fov as f32),
+                near as f32,
+                far as f32,
+            ),
+            projection: Projection::Perspective(fov as f32),
+            output: Output::default(),
+        })
+    }
+
+    /// Creates an orthographic camera. As in three-d, the view volume is
+    /// `height` times the distance to the target tall, so moving the camera
+    /// closer zooms in; the width follows from the viewport's aspect ratio.
+    /// The viewport is 1x1 until set, as for the perspective constructor.
+    #[napi(factory)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn orthographic(
+        px: f64,
+        py: f64,
+        pz: f64,
+        tx: f64,
+        ty: f64,
+        tz: f64,
+        ux: f64,
+        uy: f64,
+        uz: f64,
+        height: f64,
+        near: f64,
+        far: f64,
+    ) -> Result<Self> {
+        let (position, target, up) = view_vectors([px, py, pz], [tx, ty, tz], [ux, uy, uz])?;
+        check_height(height)?;
+        check_orthographic_planes(near, far)?;
+        Ok(Camera {
+            inner: three_d::Camera::new_orthographic(
+                three_d::Viewport::new_at_origo(1, 1),
+                position,
+                target,
+                up,
+                height as f32,
+                near as f32,
+                far as f32,
+            ),
+            projection: Projection::Orthographic(height as f32),
+            output: Output::default(),
+        })
+    }
+
+    /// Returns the camera position as `[x, y, z]`.
+    #[napi]
+    pub fn get_position(&self) -> Vec<f64> {
+        to_array(self.inner.position())
+    }
+
+    /// Returns the point the camera looks at as `[x, y, z]`.
+    #[napi]
+    pub fn get_target(&self) -> Vec<f64> {
+        to_array(self.inner.target())
+    }
+
+    /// Returns the up direction as `[x, y, z]`.
+    #[napi]
+    pub fn get_up(&self) -> Vec<f64> {
+        to_array(self.inner.up())
+    }
+
+    /// Returns the normalized direction from the position towards the target.
+    #[napi]
+    pub fn get_view_direction(&self) -> Vec<f64> {
+        to_array(self.inner.view_direction())
+    }
+
+    /// Moves the camera, keeping its target and up direction.
+    #[napi]
+    pub fn set_position(&mut self, x: f64, y: f64, z: f64) -> Result<()> {
+        let target = self.get_target();
+        let up = self.get_up();
+        self.set_view(
+            x, y, z, target[0], target[1], target[2], up[0], up[1], up[2],
+        )
+    }
+
+    /// Points the camera at a new target, keeping its position and up direction.
+    #[napi]
+    pub fn set_target(&mut self, x: f64, y: f64, z: f64) -> Result<()> {
+        let position = self.get_position();
+        let up = self.get_up();
+        self.set_view(
+            position[0],
+            position[1],
+            position[2],
+            x,
+            y,
+            z,
+            up[0],
+            up[1],
+            up[2],
+        )
+    }
+
+    /// Changes the up direction, keeping the position and target.
+    #[napi]
+    pub fn set_up(&mut self, x: f64, y: f64, z: f64) -> Result<()> {
+        let position = self.get_position();
+        let target = self.get_target();
+        self.set_view(
+            position[0],
+            position[1],
+            position[2],
+            target[0],
+            target[1],
+            target[2],
+            x,
+            y,
+            z,
+        )
+    }
+
+    /// Sets position, target and up direction at once.
+    #[napi]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_view(
+        &mut self,
+        px: f64,
+        py: f64,
+        pz: f64,
+        tx: f64,
+        ty: f64,
+        tz: f64,
+        ux: f64,
+        uy: f64,
+        uz: f64,
+    ) -> Result<()> {
+        let (position, target, up) = view_vectors([px, py, pz], [tx, ty, tz], [ux, uy, uz])?;
+        self.inner.set_view(position, target, up);
+        Ok(())
+    }
+
+    /// Switches to a perspective projection.
+    #[napi]
+    pub fn set_perspective_projection(&mut self, fov: f64, near: f64, far: f64) -> Result<()> {
+        check_fov(fov)?;
+        check_perspective_planes(near, far)?;
+        self.inner.set_perspective_projection(
+            three_d::degrees(fov as f32),
+            near as f32,
+            far as f32,
+        );
+        self.projection = Projection::Perspective(fov as f32);
+        Ok(())
+    }
+
+    /// Switches to an orthographic projection `height` times the distance to
+    /// the target tall.
+    #[napi]
+    pub fn set_orthographic_projection(&mut self, height: f64, near: f64, far: f64) -> Result<()> {
+        check_height(height)?;
+        check_orthographic_planes(near, far)?;
+        self.inner
+            .set_orthographic_projection(height as f32, near as f32, far as f32);
+        self.projection = Projection::Orthographic(height as f32);
+        Ok(())
+    }
+
+    /// Changes the vertical field of view of a perspective camera.
+    #[napi]
+    pub fn set_fov(&mut self, fov: f64) -> Result<()> {
+        match self.projection {
+            Projection::Perspective(_) => {
+                self.set_perspective_projection(fov, self.z_near(), self.z_far())
+            }
+            Projection::Orthographic(_) => Err(Error::new(
+                Status::InvalidArg,
+                "An orthographic camera has no field of view".to_string(),
+            )),
+        }
+    }
+
+    /// Moves the near and far clip planes, keeping the rest of the projection.
+    #[napi]
+    pub fn set_clip_planes(&mut self, near: f64, far: f64) -> Result<()> {
+        match self.projection {
+            Projection::Perspective(fov) => self.set_perspective_projection(fov as f64, near, far),
+            Projection::Orthographic(height) => {
+                self.set_orthographic_projection(height as f64, near, far)
+            }
+        }
+    }
+
+    /// Sets the region of the render target the camera draws into, which
+    /// also determines the aspect ratio of the projection.
+    #[napi]
+    pub fn set_viewport(&mut self, viewport: &Viewport) -> Result<()> {
+        if viewport.width == 0 || viewport.height == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Viewport must not be empty, got {}x{}",
+                    viewport.width, viewport.height
+                ),
+            ));
+        }
+        self.inner.set_viewport(viewport.into());
+        Ok(())
+    }
+
+    #[napi]
+    pub fn get_viewport(&self) -> Viewport {
+        self.inner.viewport().into()
+    }
+
+    /// Vertical field of view in degrees, or null for an orthographic camera.
+    #[napi(getter)]
+    pub fn fov(&self) -> Option<f64> {
+        match self.projection {
+            Projection::Perspective(fov) => Some(fov as f64),
+            Projection::Orthographic(_) => None,
+        }
+    }
+
+    #[napi(getter)]
+    pub fn z_near(&self) -> f64 {
+        self.inner.z_near() as f64
+    }
+
+    #[napi(getter)]
+    pub fn z_far(&self) -> f64 {
+        self.inner.z_far() as f64
+    }
+
+    #[napi(getter)]
+    pub fn is_orthographic(&self) -> bool {
+        matches!(self.projection, Projection::Orthographic(_))
+    }
+
+    /// Returns the world-to-view matrix.
+    #[napi]
+    pub fn view_matrix(&self) -> Matrix4 {
+        Matrix4::from_matrix4(&self.inner.view())
+    }
+
+    /// Returns the view-to-clip matrix.
+    #[napi]
+    pub fn projection_matrix(&self) -> Matrix4 {
+        Matrix4::from_matrix4(&self.inner.projection())
+    }
+
+    /// Returns the projection matrix multiplied by the view matrix.
+    #[napi]
+    pub fn view_projection_matrix(&self) -> Matrix4 {
+        Matrix4::from_matrix4(&(self.inner.projection() * self.inner.view()))
+    }
+
+    /// Returns the view frustum for culling against bounding boxes.
+    #[napi]
+    pub fn frustum(&self) -> Frustum {
+        Frustum::new(self.inner.projection() * self.inner.view())
+    }
+
+    /// Returns a path that moves the camera over `durationMs` milliseconds
+    /// until `aabb` fills the view, keeping the view and up directions. The
+    /// fit uses the aspect ratio of the camera's viewport. The duration must
+    /// be positive.
+    #[napi]
+    pub fn fly_to(&self, aabb: &AxisAlignedBoundingBox, duration_ms: f64) -> Result<CameraPath> {
+        CameraPath::fly_to(self, aabb, duration_ms)
+    }
+
+    /// Returns the ray through the pixel `(x, y)`, measured from the bottom
+    /// left corner of the render target. The origin is the camera position
+    /// for a perspective camera and lies on the image plane for an
+    /// orthographic one. Pixels are those of the camera's viewport, which
+    /// `setViewport` or `Renderer.render` sets.
+    #[napi]
+    pub fn ray_from_pixel(&self, x: f64, y: f64) -> Ray {
+        let pixel = (x as f32, y as f32);
+        Ray {
+            origin: to_array(self.inner.position_at_pixel(pixel)),
+            direction: to_array(self.inner.view_direction_at_pixel(pixel)),
+        }
+    }
+
+    /// Projects a world position to `[x, y, depth]`, where `x` and `y` are
+    /// pixel coordinates from the bottom left corner and `depth` is the
+    /// distance in front of the camera along the view direction, the same
+    /// linear depth `readDepth(camera)` returns.
+    #[napi]
+    pub fn project(&self, point: Vec<f64>) -> Result<Vec<f64>> {
+        use three_d::InnerSpace;
+        let point = to_vec3("Point", &point)?;
+        let pixel = self.inner.pixel_at_position(point);
+        let depth = (point - self.inner.position()).dot(self.inner.view_direction());
+        Ok(vec![pixel.x as f64, pixel.y as f64, depth as f64])
+    }
+
+    /// Returns the world position at pixel `[x, y]` and the given linear
+    /// depth; the inverse of `project`.
+    #[napi]
+    pub fn unproject(&self, pixel: Vec<f64>, depth: f64) -> Result<Vec<f64>> {
+        use three_d::InnerSpace;
+        if pixel.len() != 2 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Pixel must have 2 components, got {}", pixel.len()),
+            ));
+        }
+        let pixel = (pixel[0] as f32, pixel[1] as f32);
+        let origin = self.inner.position_at_pixel(pixel);
+        let direction = self.inner.view_direction_at_pixel(pixel);
+        let distance = depth as f32 / direction.dot(self.inner.view_direction());
+        Ok(to_array(origin + direction * distance))
+    }
+
+    /// Operator mapping HDR colors of lit materials into `[0, 1]`.
+    /// Defaults to `ACESFilmic`. `Lottes`, `Uchimura` and `Unreal` are
+    /// applied by `Renderer.render` in a pass over the whole frame; other
+    /// render functions draw with them as `None`.
+    #[napi(getter)]
+    pub fn tone_mapping(&self) -> ToneMapping {
+        self.output.tone_mapping
+    }
+
+    #[napi(setter)]
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping) {
+        self.output.tone_mapping = tone_mapping;
+        self.sync_output();
+    }
+
+    /// Color space of the final image. Defaults to `Srgb`.
+    #[napi(getter)]
+    pub fn color_mapping(&self) -> ColorMapping {
+        self.output.color_mapping
+    }
+
+    #[napi(setter)]
+    pub fn set_color_mapping(&mut self, color_mapping: ColorMapping) {
+        self.output.color_mapping = color_mapping;
+        self.sync_output();
+    }
+
+    /// Factor HDR colors are scaled by before tone mapping. Defaults to 1.
+    /// Like the operators three-d lacks, any other exposure is only applied
+    /// by `Renderer.render`.
+    #[napi(getter)]
+    pub fn exposure(&self) -> f64 {
+        self.output.exposure as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_exposure(&mut self, exposure: f64) -> Result<()> {
+        if !(exposure > 0.0 && exposure.is_finite()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Exposure must be positive, got {exposure}"),
+            ));
+        }
+        self.output.exposure = exposure as f32;
+        Ok(())
+    }
+}
+
+impl Camera {
+    pub(crate) fn output(&self) -> Output {
+        self.output
+    }
+
+    /// Applies the output settings three-d supports to the inner camera.
+    fn sync_output(&mut self) {
+        let native = Output {
+            exposure: 1.0,
+            ..self.output
+        }
+        .native();
+        let (tone_mapping, color_mapping) =
+            native.unwrap_or((three_d::ToneMapping::None, self.output.color_mapping.into()));
+        self.inner.tone_mapping = tone_mapping;
+        self.inner.color_mapping = color_mapping;
+    }
+
+    /// Height of the orthographic view volume per unit of distance to the
+    /// target, or `None` for a perspective camera.
+    pub(crate) fn orthographic_height(&self) -> Option<f64> {
+        match self.projection {
+            Projection::Perspective(_) => None,
+            Projection::Orthographic(height) => Some(height as f64),
+        }
+    }
+}
+
+impl Default for Camera {
+    /// A perspective camera at `(0, 0, 1)` looking at the origin with +Y up, a
+    /// 45° vertical field of view and near and far planes at 0.1 and 1000.
+    fn default() -> Self {
+        Self::new(
+            0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 45.0, 0.1, 1000.0,
+        )
+        .expect("default camera parameters are valid")
+    }
+}
+
+pub(crate) fn to_array(v: three_d::Vec3) -> Vec<f64> {
+    vec![v.x as f64, v.y as f64, v.z as f64]
+}
+
+fn to_vec3(name: &str, v: &[f64]) -> Result<three_d::Vec3> {
+    let [x, y, z] = components(name, v)?;
+    Ok(three_d::vec3(x as f32, y as f32, z as f32))
+}
+
+pub(crate) fn components(name: &str, v: &[f64]) -> Result<[f64; 3]> {
+    v.try_into().map_err(|_| {
+        Error::new(
+            Status::InvalidArg,
+            format!("{name} must have 3 components, got {}", v.len()),
+        )
+    })
+}
+
+pub(crate) fn view_vectors(
+    position: [f64; 3],
+    target: [f64; 3],
+    up: [f64; 3],
+) -> Result<(three_d::Vec3, three_d::Vec3, three_d::Vec3)> {
+    use three_d::InnerSpace;
+    let [position, target, up] =
+        [position, target, up].map(|[x, y, z]| three_d::vec3(x as f32, y as f32, z as f32));
+    let direction = target - position;
+    if direction.magnitude2() == 0.0 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "Camera position and target must differ".to_string(),
+        ));
+    }
+    if direction.cross(up).magnitude2() == 0.0 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "Camera up direction must not be parallel to the view direction".to_string(),
+        ));
+    }
+    Ok((position, target, up))
+}
+
+pub(crate) fn check_fov(fov: f64) -> Result<()> {
+    if fov > 0.0 && fov < 180.0 {
+        Ok(())
+    } else {
+        Err(Error::new(
+            Status::InvalidArg,
+            format!("Field of view must be between 0 and 180 degrees, got {fov}"),
+        ))
+    }
+}
+
+fn check_height(height: f64) -> Result<()> {
+    if height > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::new(
+            Status::InvalidArg,
+            format!("Orthographic height must be positive, got {height}"),
+        ))
+    }
+}
+
+fn check_perspective_planes(near: f64, far: f64) -> Result<()> {
+    if near <= 0.0 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("Near plane must be positive, got {near}"),
+        ));
+    }
+    check_orthographic_planes(near, far)
+}
+
+fn check_orthographic_planes(near: f64, far: f64) -> Result<()> {
+    if far > near {
+        Ok(())
+    } else {
+        Err(Error::new(
+            Status::InvalidArg,
+            format!("Far plane ({far}) must be beyond the near plane ({near})"),
+        ))
+    }
+}
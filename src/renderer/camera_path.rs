@@ -0,0 +1,315 @@
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use three_d::{InnerSpace, Matrix3, MetricSpace, Quaternion, Rotation, Vec3};
+
+use std::ops::{Add, Mul, Sub};
+
+use super::camera::{check_fov, components, to_array, view_vectors, Camera};
+use crate::enums::Easing;
+use crate::prelude::AxisAlignedBoundingBox;
+
+/// A camera pose at a point in time. `up` defaults to +Y and `fov`, in
+/// degrees, is only applied to perspective cameras.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct CameraKeyframe {
+    pub time: f64,
+    pub position: Vec<f64>,
+    pub target: Vec<f64>,
+    pub up: Option<Vec<f64>>,
+    pub fov: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Keyframe {
+    time: f64,
+    position: Vec3,
+    target: Vec3,
+    orientation: Quaternion<f32>,
+    fov: Option<f32>,
+}
+
+/// A keyframed camera animation. Positions, targets and field of view follow
+/// Hermite splines through the keyframes, the orientation around the view
+/// direction is interpolated with quaternion slerp, and the easing curve
+/// remaps time over the whole path. Sampling depends only on the time passed
+/// in, so the same path renders the same frames every run.
+#[napi]
+pub struct CameraPath {
+    keyframes: Vec<Keyframe>,
+    easing: Easing,
+}
+
+#[napi]
+impl CameraPath {
+    #[napi(constructor)]
+    pub fn new(easing: Option<Easing>) -> Self {
+        CameraPath {
+            keyframes: Vec::new(),
+            easing: easing.unwrap_or(Easing::Linear),
+        }
+    }
+
+    /// Inserts a keyframe, keeping the keyframes sorted by time.
+    #[napi]
+    pub fn add_keyframe(&mut self, keyframe: CameraKeyframe) -> Result<()> {
+        if !keyframe.time.is_finite() || keyframe.time < 0.0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Keyframe time must be a non-negative number of milliseconds, got {}",
+                    keyframe.time
+                ),
+            ));
+        }
+        let (position, target, up) = view_vectors(
+            components("Position", &keyframe.position)?,
+            components("Target", &keyframe.target)?,
+            components("Up", keyframe.up.as_deref().unwrap_or(&[0.0, 1.0, 0.0]))?,
+        )?;
+        if let Some(fov) = keyframe.fov {
+            check_fov(fov)?;
+        }
+        if self
+            .keyframes
+            .first()
+            .is_some_and(|k| k.fov.is_some() != keyframe.fov.is_some())
+        {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Keyframes must either all set a field of view or none".to_string(),
+            ));
+        }
+        let index = self.keyframes.partition_point(|k| k.time < keyframe.time);
+        if self
+            .keyframes
+            .get(index)
+            .is_some_and(|k| k.time == keyframe.time)
+        {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("A keyframe at {} ms already exists", keyframe.time),
+            ));
+        }
+        self.keyframes.insert(
+            index,
+            Keyframe {
+                time: keyframe.time,
+                position,
+                target,
+                orientation: orientation(target - position, up),
+                fov: keyframe.fov.map(|fov| fov as f32),
+            },
+        );
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn keyframe_count(&self) -> u32 {
+        self.keyframes.len() as u32
+    }
+
+    /// Time of the last keyframe in milliseconds.
+    #[napi(getter)]
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Returns the interpolated pose at `time` milliseconds. Times outside
+    /// the keyframes are clamped to the first or last one.
+    #[napi]
+    pub fn sample(&self, time: f64) -> Result<CameraKeyframe> {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                return Err(Error::new(
+                    Status::GenericFailure,
+                    "Camera path has no keyframes".to_string(),
+                ))
+            }
+        };
+        let time = time.clamp(first.time, last.time);
+        let span = last.time - first.time;
+        let eased = if span > 0.0 {
+            first.time + ease(self.easing, (time - first.time) / span) * span
+        } else {
+            first.time
+        };
+
+        let i = self
+            .keyframes
+            .partition_point(|k| k.time <= eased)
+            .clamp(1, self.keyframes.len())
+            - 1;
+        let k1 = &self.keyframes[i];
+        let Some(k2) = self.keyframes.get(i + 1) else {
+            return Ok(CameraKeyframe {
+                time,
+                position: to_array(k1.position),
+                target: to_array(k1.target),
+                up: Some(to_array(k1.up())),
+                fov: k1.fov.map(|fov| fov as f64),
+            });
+        };
+        let u = ((eased - k1.time) / (k2.time - k1.time)) as f32;
+        let k = [&self.keyframes[i.saturating_sub(1)], k1, k2];
+        let k = [k[0], k[1], k[2], self.keyframes.get(i + 2).unwrap_or(k2)];
+
+        let position = hermite(k, u, |k| k.position);
+        let target = hermite(k, u, |k| k.target);
+        let (q1, q2) = (k1.orientation, k2.orientation);
+        let q2 = if q1.dot(q2) < 0.0 { -q2 } else { q2 };
+        let up = q1.slerp(q2, u).rotate_vector(three_d::vec3(0.0, 1.0, 0.0));
+        // The view direction follows the splines rather than the slerp, so
+        // turn the up direction perpendicular to it, falling back to the
+        // keyframes' up directions where it is parallel.
+        let direction = target - position;
+        let up = [up, k1.up(), k2.up()]
+            .into_iter()
+            .find_map(|up| perpendicular(up, direction))
+            .unwrap_or(up);
+        let fov = k1.fov.is_some().then(|| {
+            hermite(k, u, |k| k.fov.unwrap_or_default()).clamp(f32::EPSILON, 180.0 - f32::EPSILON)
+        });
+        Ok(CameraKeyframe {
+            time,
+            position: to_array(position),
+            target: to_array(target),
+            up: Some(to_array(up)),
+            fov: fov.map(|fov| fov as f64),
+        })
+    }
+
+    /// Moves the camera to the pose at `time` milliseconds.
+    #[napi]
+    pub fn apply(&self, camera: &mut Camera, time: f64) -> Result<()> {
+        let pose = self.sample(time)?;
+        let [px, py, pz] = components("Position", &pose.position)?;
+        let [tx, ty, tz] = components("Target", &pose.target)?;
+        let [ux, uy, uz] = components("Up", pose.up.as_deref().unwrap_or_default())?;
+        camera.set_view(px, py, pz, tx, ty, tz, ux, uy, uz)?;
+        if let (Some(fov), false) = (pose.fov, camera.is_orthographic()) {
+            camera.set_fov(fov)?;
+        }
+        Ok(())
+    }
+}
+
+impl CameraPath {
+    /// A path from the camera's current pose to one where `aabb` fills the
+    /// view, keeping the view direction and up direction.
+    pub(crate) fn fly_to(
+        camera: &Camera,
+        aabb: &AxisAlignedBoundingBox,
+        duration_ms: f64,
+    ) -> Result<Self> {
+        if !(duration_ms.is_finite() && duration_ms > 0.0) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Flight duration must be positive and finite, got {duration_ms}"),
+            ));
+        }
+        if aabb.inner.is_empty() || aabb.inner.is_infinite() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Cannot fly to an empty or infinite bounding box".to_string(),
+            ));
+        }
+        let inner = &camera.inner;
+        let radius = 0.5 * aabb.inner.size().magnitude();
+        let aspect = inner.viewport().aspect().min(1.0);
+        let distance = if radius == 0.0 {
+            inner.position().distance(inner.target())
+        } else {
+            match (camera.fov(), camera.orthographic_height()) {
+                (Some(fov), _) => {
+                    let half_fov = 0.5 * (fov as f32).to_radians();
+                    let half_fov = half_fov.min((half_fov.tan() * aspect).atan());
+                    radius / half_fov.sin()
+                }
+                // The orthographic view height is scaled by the distance to
+                // the target.
+                (None, Some(height)) => 2.0 * radius / (height as f32 * aspect),
+                (None, None) => unreachable!("a camera is either perspective or orthographic"),
+            }
+        };
+        let center = aabb.inner.center();
+        let destination = center - inner.view_direction() * distance;
+
+        let mut path = CameraPath::new(Some(Easing::EaseInOut));
+        let fov = camera.fov();
+        let up = Some(to_array(inner.up()));
+        path.add_keyframe(CameraKeyframe {
+            time: 0.0,
+            position: to_array(inner.position()),
+            target: to_array(inner.target()),
+            up: up.clone(),
+            fov,
+        })?;
+        path.add_keyframe(CameraKeyframe {
+            time: duration_ms,
+            position: to_array(destination),
+            target: to_array(center),
+            up,
+            fov,
+        })?;
+        Ok(path)
+    }
+}
+
+impl Keyframe {
+    fn up(&self) -> Vec3 {
+        self.orientation.rotate_vector(three_d::vec3(0.0, 1.0, 0.0))
+    }
+}
+
+/// Rotation taking the camera's local axes, +Y up and -Z forward, to the
+/// given view and up directions.
+fn orientation(direction: Vec3, up: Vec3) -> Quaternion<f32> {
+    let back = -direction.normalize();
+    let right = up.cross(back).normalize();
+    let up = back.cross(right);
+    Quaternion::from(Matrix3::from_cols(right, up, back))
+}
+
+/// The unit vector along the part of `up` perpendicular to `direction`, or
+/// `None` if the two are (nearly) parallel or `direction` is zero.
+fn perpendicular(up: Vec3, direction: Vec3) -> Option<Vec3> {
+    let direction = direction.normalize();
+    let up = up - direction * up.dot(direction);
+    (up.magnitude2() > 1e-6).then(|| up.normalize())
+}
+
+/// Cubic Hermite interpolation between `k[1]` and `k[2]`, with tangents from
+/// the neighbouring keyframes scaled by their spacing in time. At the ends of
+/// the path the missing neighbour is the endpoint itself.
+fn hermite<V>(k: [&Keyframe; 4], u: f32, value: impl Fn(&Keyframe) -> V) -> V
+where
+    V: Copy + Add<Output = V> + Sub<Output = V> + Mul<f32, Output = V>,
+{
+    let dt = (k[2].time - k[1].time) as f32;
+    let tangent =
+        |a: &Keyframe, b: &Keyframe| (value(b) - value(a)) * (dt / (b.time - a.time) as f32);
+    let m1 = tangent(k[0], k[2]);
+    let m2 = tangent(k[1], k[3]);
+    let (u2, u3) = (u * u, u * u * u);
+    value(k[1]) * (2.0 * u3 - 3.0 * u2 + 1.0)
+        + m1 * (u3 - 2.0 * u2 + u)
+        + value(k[2]) * (-2.0 * u3 + 3.0 * u2)
+        + m2 * (u3 - u2)
+}
+
+fn ease(easing: Easing, t: f64) -> f64 {
+    match easing {
+        Easing::Linear => t,
+        Easing::EaseIn => t * t * t,
+        Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+        Easing::EaseInOut => {
+            if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            }
+        }
+    }
+}
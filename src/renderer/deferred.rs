@@ -0,0 +1,235 @@
+//! G-buffer of the deferred render path, written by `DeferredPhysicalMaterial`
+//! and lit by three-d's lighting pass.
+
+use napi::{Error, Result, Status};
+use three_d::{
+    f16, ColorTexture, DepthTexture, Effect, EffectMaterialId, Interpolation, Light, Program,
+    RenderStates, Viewer, Wrapping,
+};
+
+use super::material::material_id;
+use crate::enums::GBufferTexture;
+
+/// Layers of the G-buffer array, as written by three-d's
+/// `deferred_physical_material.frag`:
+/// 0. albedo in RGB and metallic in alpha,
+/// 1. the packed normal with occlusion in blue and roughness in alpha,
+/// 2. emissive color in RGB, read only by the lighting pass.
+const LAYERS: [u32; 3] = [0, 1, 2];
+
+/// Decodes one texture of the G-buffer from layers 0 and 1, matching
+/// three-d's `deferred_lighting.frag`.
+const DECODE_SHADER: &str = "
+uniform int gbufferTexture;
+in vec2 uvs;
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 c = sample_layer(uvs, 0);
+    vec4 n = sample_layer(uvs, 1);
+    float depth = sample_depth(uvs);
+    if (gbufferTexture == 0) {
+        outColor = vec4(c.rgb, 1.0);
+    } else if (gbufferTexture == 1) {
+        vec2 n2 = n.xy * 2.0 - 1.0;
+        float z = sqrt(max(1.0 - n2.x * n2.x - n2.y * n2.y, 0.0));
+        bool positive = (int(floor(n.z * 255.0)) & 128) == 128;
+        outColor = vec4(normalize(vec3(n2, positive ? z : -z)), 0.0);
+    } else if (gbufferTexture == 2) {
+        float occlusion = float(int(floor(n.z * 255.0)) & 127) / 127.0;
+        outColor = vec4(occlusion, n.w, c.w, 1.0);
+    } else {
+        outColor = vec4(depth, depth, depth, 1.0);
+    }
+    if (depth > 0.99999 && gbufferTexture != 3) {
+        outColor = vec4(0.0);
+    }
+}
+";
+
+/// The G-buffer of the last frame with deferred objects.
+pub(crate) struct GBuffer {
+    layers: three_d::Texture2DArray,
+    depth: three_d::DepthTexture2D,
+}
+
+impl GBuffer {
+    pub(crate) fn new(context: &three_d::Context, width: u32, height: u32) -> Self {
+        GBuffer {
+            layers: three_d::Texture2DArray::new_empty::<[u8; 4]>(
+                context,
+                width,
+                height,
+                LAYERS.len() as u32,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            depth: three_d::DepthTexture2D::new::<f32>(
+                context,
+                width,
+                height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+        }
+    }
+
+    pub(crate) fn size(&self) -> (u32, u32) {
+        (self.depth.width(), self.depth.height())
+    }
+
+    /// Clears the G-buffer and writes `objects` to it.
+    pub(crate) fn geometry_pass(
+        &mut self,
+        camera: &three_d::Camera,
+        objects: &[&dyn three_d::Object],
+    ) -> Result<()> {
+        three_d::RenderTarget::new(
+            self.layers.as_color_target(&LAYERS, None),
+            self.depth.as_depth_target(),
+        )
+        .clear(three_d::ClearState::default())
+        .write::<three_d::RendererError>(|| {
+            for object in objects {
+                object.render(camera, &[]);
+            }
+            Ok(())
+        })
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        Ok(())
+    }
+
+    /// Lights the G-buffer into `target`, writing its depth as well.
+    pub(crate) fn lighting_pass(
+        &self,
+        context: &three_d::Context,
+        target: &three_d::RenderTarget,
+        camera: &three_d::Camera,
+        lights: &[&dyn Light],
+    ) -> Result<()> {
+        target
+            .write::<three_d::RendererError>(|| {
+                three_d::DeferredPhysicalMaterial::lighting_pass(
+                    context,
+                    camera,
+                    self.color_texture(),
+                    DepthTexture::Single(&self.depth),
+                    lights,
+                );
+                Ok(())
+            })
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        Ok(())
+    }
+
+    /// Reads `texture` as RGBA with a 32-bit float per channel, top row first.
+    pub(crate) fn read(
+        &self,
+        context: &three_d::Context,
+        texture: GBufferTexture,
+    ) -> Result<Vec<[f32; 4]>> {
+        let mut output = three_d::Texture2D::new_empty::<[f16; 4]>(
+            context,
+            self.depth.width(),
+            self.depth.height(),
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let source = format!(
+            "{}\n{}\n{DECODE_SHADER}",
+            self.color_texture().fragment_shader_source(),
+            DepthTexture::Single(&self.depth).fragment_shader_source()
+        );
+        let effect = DecodeEffect {
+            id: material_id(&source)?,
+            source,
+            texture,
+        };
+        let target = output.as_color_target(None);
+        let pixels = target
+            .clear(three_d::ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .apply_screen_effect(
+                &effect,
+                three_d::Camera::new_2d(three_d::Viewport::new_at_origo(
+                    self.depth.width(),
+                    self.depth.height(),
+                )),
+                &[],
+                Some(self.color_texture()),
+                Some(DepthTexture::Single(&self.depth)),
+            )
+            .read::<[f32; 4]>();
+        Ok(pixels)
+    }
+
+    fn color_texture(&self) -> ColorTexture<'_> {
+        ColorTexture::Array {
+            texture: &self.layers,
+            layers: &LAYERS,
+        }
+    }
+}
+
+/// Writes one decoded texture of the G-buffer.
+struct DecodeEffect {
+    id: u16,
+    source: String,
+    texture: GBufferTexture,
+}
+
+impl Effect for DecodeEffect {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        self.source.clone()
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _viewer: &dyn Viewer,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if let Some(texture) = color_texture {
+            texture.use_uniforms(program);
+        }
+        if let Some(texture) = depth_texture {
+            texture.use_uniforms(program);
+        }
+        let texture = match self.texture {
+            GBufferTexture::Albedo => 0,
+            GBufferTexture::Normal => 1,
+            GBufferTexture::ORM => 2,
+            GBufferTexture::Depth => 3,
+        };
+        program.use_uniform("gbufferTexture", texture);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: three_d::DepthTest::Always,
+            write_mask: three_d::WriteMask::COLOR,
+            ..Default::default()
+        }
+    }
+}
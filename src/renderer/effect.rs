@@ -1,4 +1,12 @@
+use napi::Result;
 use napi_derive::napi;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use three_d::{ColorTexture, DepthTexture, EffectMaterialId, Light, Program, RenderStates, Viewer};
+
+use super::material::{check_shader, material_id, SamplerInput, ShaderInputs};
+use crate::core::texture::{DepthTexture2D, Texture2D};
 
 #[napi]
 pub struct FogEffect {}
@@ -6,8 +14,183 @@ pub struct FogEffect {}
 #[napi]
 pub struct WaterEffect {}
 
+/// Shared state of a [`ScreenEffect`].
+pub(crate) struct ScreenEffectState {
+    fragment_source: String,
+    inputs: ShaderInputs,
+    // Vertex shader and effect id pairs the shader is known to link with.
+    checked: HashSet<(String, u16)>,
+}
+
+/// An effect defined by a user supplied GLSL fragment shader, drawn over the
+/// whole render target or over geometries.
+///
+/// The shader must write to `layout (location = 0) out vec4 outColor;`. When
+/// a color texture is passed in, it can call `vec4 sample_color(vec2 uv)`,
+/// and with a depth texture `float sample_depth(vec2 uv)`. Full screen passes
+/// read `in vec2 uvs;`, geometry passes the outputs of the geometry such as
+/// `in vec3 pos;`.
+#[napi]
+pub struct ScreenEffect {
+    pub(crate) inner: Rc<RefCell<ScreenEffectState>>,
+}
+
 #[napi]
-pub struct ScreenEffect {}
+impl ScreenEffect {
+    #[napi(constructor)]
+    pub fn new(fragment_source: String) -> Self {
+        ScreenEffect {
+            inner: Rc::new(RefCell::new(ScreenEffectState {
+                fragment_source,
+                inputs: ShaderInputs::default(),
+                checked: HashSet::new(),
+            })),
+        }
+    }
+
+    /// Binds a 2D texture to a `sampler2D` uniform.
+    #[napi]
+    pub fn set_texture(&self, name: String, texture: &Texture2D) {
+        self.inner
+            .borrow_mut()
+            .inputs
+            .set_sampler(name, SamplerInput::Texture2D(texture.inner.clone()));
+    }
+
+    /// Binds a depth texture to a `sampler2D` uniform.
+    #[napi]
+    pub fn set_depth_texture(&self, name: String, texture: &DepthTexture2D) {
+        self.inner
+            .borrow_mut()
+            .inputs
+            .set_sampler(name, SamplerInput::DepthTexture2D(texture.inner.clone()));
+    }
+
+    /// Sets a `float`, `vec2`, `vec3`, `vec4` or column-major `mat4` uniform,
+    /// chosen by the number of values.
+    #[napi]
+    pub fn set_uniform(&self, name: String, values: Vec<f64>) -> Result<()> {
+        self.inner.borrow_mut().inputs.set_uniform(name, values)
+    }
+
+    /// Removes a texture or uniform binding.
+    #[napi]
+    pub fn remove(&self, name: String) {
+        self.inner.borrow_mut().inputs.remove(&name);
+    }
+}
+
+impl ScreenEffectState {
+    /// Checks that the shader links with each of `vertex_sources` for the
+    /// given inputs, so errors are reported instead of panicking inside
+    /// three-d's program cache.
+    pub(crate) fn check(
+        &mut self,
+        context: &three_d::Context,
+        vertex_sources: &[String],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> Result<()> {
+        let source = self.source(color_texture, depth_texture);
+        let id = material_id(&source)?;
+        for vertex_source in vertex_sources {
+            let key = (vertex_source.clone(), id);
+            if !self.checked.contains(&key) {
+                check_shader(context, vertex_source, &source, "effect")?;
+                self.checked.insert(key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the effect for the given inputs. Full screen passes ignore and
+    /// keep the depth of the target, geometry passes are depth tested.
+    pub(crate) fn prepare(
+        &self,
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+        full_screen: bool,
+    ) -> Result<PreparedEffect<'_>> {
+        let source = self.source(color_texture, depth_texture);
+        let render_states = if full_screen {
+            RenderStates {
+                depth_test: three_d::DepthTest::Always,
+                write_mask: three_d::WriteMask::COLOR,
+                ..Default::default()
+            }
+        } else {
+            RenderStates::default()
+        };
+        Ok(PreparedEffect {
+            state: self,
+            id: material_id(&source)?,
+            source,
+            render_states,
+        })
+    }
+
+    /// The fragment shader with the sampling functions of the inputs.
+    fn source(
+        &self,
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> String {
+        format!(
+            "{}\n{}\n{}",
+            color_texture.map_or(String::new(), |t| t.fragment_shader_source()),
+            depth_texture.map_or(String::new(), |t| t.fragment_shader_source()),
+            self.fragment_source
+        )
+    }
+}
+
+/// A [`ScreenEffect`] compiled for one combination of inputs.
+pub(crate) struct PreparedEffect<'a> {
+    state: &'a ScreenEffectState,
+    source: String,
+    id: u16,
+    render_states: RenderStates,
+}
+
+impl three_d::Effect for PreparedEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        self.source.clone()
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _viewer: &dyn Viewer,
+        _lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if let Some(texture) = color_texture {
+            texture.use_uniforms(program);
+        }
+        if let Some(texture) = depth_texture {
+            texture.use_uniforms(program);
+        }
+        self.state.inputs.use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+}
 
 #[napi]
 pub struct CopyEffect {}
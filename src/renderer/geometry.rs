@@ -1,7 +1,54 @@
+use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::cell::RefCell;
+use std::rc::Rc;
+use three_d::{Geometry, Indices, Positions, Srgba};
+
+use crate::context::Context;
+use crate::prelude::{AxisAlignedBoundingBox, Matrix4};
+use crate::types::{GeometryBuffer, IndexBuffer};
+
+/// A triangle mesh on the GPU with a transformation applied when rendering.
+/// Shared by every `Gm` created from it.
+#[napi]
+pub struct Mesh {
+    pub(crate) inner: Rc<RefCell<three_d::Mesh>>,
+}
 
 #[napi]
-pub struct Mesh {}
+impl Mesh {
+    /// Uploads a CPU mesh to the GPU.
+    #[napi(constructor)]
+    pub fn new(context: &Context, cpu_mesh: &CpuMesh) -> Self {
+        Mesh {
+            inner: Rc::new(RefCell::new(three_d::Mesh::new(
+                &context.inner,
+                &cpu_mesh.inner,
+            ))),
+        }
+    }
+
+    /// Sets the transformation from local to world space.
+    #[napi]
+    pub fn set_transformation(&self, transformation: &Matrix4) {
+        self.inner
+            .borrow_mut()
+            .set_transformation(transformation.to_matrix4());
+    }
+
+    #[napi]
+    pub fn transformation(&self) -> Matrix4 {
+        Matrix4::from_matrix4(&self.inner.borrow().transformation())
+    }
+
+    /// Bounding box in world space, including the transformation.
+    #[napi]
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox {
+            inner: self.inner.borrow().aabb(),
+        }
+    }
+}
 
 #[napi]
 pub struct InstancedMesh {}
@@ -24,5 +71,177 @@ pub struct Rectangle {}
 #[napi]
 pub struct PointCloud {}
 
+/// A triangle mesh in CPU memory, uploaded to the GPU with `new Mesh`.
 #[napi]
-pub struct CpuMesh {}
+#[derive(Clone)]
+pub struct CpuMesh {
+    pub(crate) inner: three_d::CpuMesh,
+}
+
+#[napi]
+impl CpuMesh {
+    /// Creates a mesh from flat `x, y, z` positions. Without indices every
+    /// three vertices form a triangle. Normals have three values per vertex,
+    /// uvs two and colors four RGBA values in `[0, 1]`.
+    #[napi(constructor)]
+    pub fn new(
+        positions: GeometryBuffer,
+        indices: Option<IndexBuffer>,
+        normals: Option<GeometryBuffer>,
+        uvs: Option<GeometryBuffer>,
+        colors: Option<GeometryBuffer>,
+    ) -> Result<Self> {
+        let vertices = positions.len() / 3;
+        let check = |name: &str, values: Option<&GeometryBuffer>, per_vertex: usize| match values {
+            Some(values) if values.len() != vertices * per_vertex => Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Expected {} {name} values for {vertices} vertices, got {}",
+                    vertices * per_vertex,
+                    values.len()
+                ),
+            )),
+            _ => Ok(()),
+        };
+        if !positions.len().is_multiple_of(3) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!(
+                    "Positions must have 3 values per vertex, got {}",
+                    positions.len()
+                ),
+            ));
+        }
+        check("normal", normals.as_ref(), 3)?;
+        check("uv", uvs.as_ref(), 2)?;
+        check("color", colors.as_ref(), 4)?;
+        if indices.is_none() && !vertices.is_multiple_of(3) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Expected a multiple of 3 vertices without indices, got {vertices}"),
+            ));
+        }
+
+        let vec3s = |values: &[f64]| {
+            values
+                .chunks_exact(3)
+                .map(|v| three_d::vec3(v[0] as f32, v[1] as f32, v[2] as f32))
+                .collect::<Vec<_>>()
+        };
+        let inner = three_d::CpuMesh {
+            positions: Positions::F32(vec3s(&positions)),
+            indices: indices.map_or(Indices::None, Indices::U32),
+            normals: normals.as_deref().map(vec3s),
+            uvs: uvs.map(|uvs| {
+                uvs.chunks_exact(2)
+                    .map(|v| three_d::vec2(v[0] as f32, v[1] as f32))
+                    .collect()
+            }),
+            colors: colors.map(|colors| {
+                colors
+                    .chunks_exact(4)
+                    .map(|c| {
+                        let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+                        Srgba::new(channel(c[0]), channel(c[1]), channel(c[2]), channel(c[3]))
+                    })
+                    .collect()
+            }),
+            ..Default::default()
+        };
+        inner
+            .validate()
+            .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid mesh: {e}")))?;
+        Ok(CpuMesh { inner })
+    }
+
+    /// A unit square in the xy plane from -1 to 1, facing +Z.
+    #[napi(factory)]
+    pub fn square() -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::square(),
+        }
+    }
+
+    /// A unit circle in the xy plane, facing +Z.
+    #[napi(factory)]
+    pub fn circle(angle_subdivisions: u32) -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::circle(angle_subdivisions),
+        }
+    }
+
+    /// A unit sphere centered at the origin.
+    #[napi(factory)]
+    pub fn sphere(angle_subdivisions: u32) -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::sphere(angle_subdivisions),
+        }
+    }
+
+    /// A cube from -1 to 1 on every axis.
+    #[napi(factory)]
+    pub fn cube() -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::cube(),
+        }
+    }
+
+    /// A cylinder of radius 1 from 0 to 1 along the x axis, without caps.
+    #[napi(factory)]
+    pub fn cylinder(angle_subdivisions: u32) -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::cylinder(angle_subdivisions),
+        }
+    }
+
+    /// A cone of radius 1 from 0 to 1 along the x axis, without a base.
+    #[napi(factory)]
+    pub fn cone(angle_subdivisions: u32) -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::cone(angle_subdivisions),
+        }
+    }
+
+    /// An arrow of length 1 along the x axis.
+    #[napi(factory)]
+    pub fn arrow(tail_length: f64, tail_radius: f64, angle_subdivisions: u32) -> Self {
+        CpuMesh {
+            inner: three_d::CpuMesh::arrow(
+                tail_length as f32,
+                tail_radius as f32,
+                angle_subdivisions,
+            ),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn vertex_count(&self) -> u32 {
+        self.inner.vertex_count() as u32
+    }
+
+    #[napi(getter)]
+    pub fn triangle_count(&self) -> u32 {
+        self.inner.triangle_count() as u32
+    }
+
+    /// Computes smooth per-vertex normals from the triangles.
+    #[napi]
+    pub fn compute_normals(&mut self) {
+        self.inner.compute_normals();
+    }
+
+    /// Transforms the positions, normals and tangents in place.
+    #[napi]
+    pub fn transform(&mut self, transformation: &Matrix4) -> Result<()> {
+        self.inner
+            .transform(transformation.to_matrix4())
+            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+    }
+
+    #[napi]
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox {
+            inner: self.inner.compute_aabb(),
+        }
+    }
+}
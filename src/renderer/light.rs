@@ -1,16 +1,294 @@
+use napi::bindgen_prelude::Either4;
+use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+use three_d::{InnerSpace, Srgba, Vec3};
+
+use super::camera::{components, to_array};
+use crate::context::Context;
+use crate::prelude::NSrgba;
+
+/// Light handle shared between the JS light objects and the renderer,
+/// borrowed while rendering.
+#[derive(Clone)]
+pub(crate) enum LightHandle {
+    Ambient(Rc<RefCell<three_d::AmbientLight>>),
+    Directional(Rc<RefCell<three_d::DirectionalLight>>),
+    Point(Rc<RefCell<three_d::PointLight>>),
+    Spot(Rc<RefCell<three_d::SpotLight>>),
+}
+
+/// Any light accepted by the render functions.
+pub type LightInput<'a> =
+    Either4<&'a AmbientLight, &'a DirectionalLight, &'a PointLight, &'a SpotLight>;
+
+impl From<LightInput<'_>> for LightHandle {
+    fn from(light: LightInput) -> Self {
+        match light {
+            Either4::A(l) => LightHandle::Ambient(l.inner.clone()),
+            Either4::B(l) => LightHandle::Directional(l.inner.clone()),
+            Either4::C(l) => LightHandle::Point(l.inner.clone()),
+            Either4::D(l) => LightHandle::Spot(l.inner.clone()),
+        }
+    }
+}
+
+impl LightHandle {
+    pub(crate) fn borrow(&self) -> Ref<'_, dyn three_d::Light> {
+        match self {
+            LightHandle::Ambient(l) => Ref::map(l.borrow(), |l| l as &dyn three_d::Light),
+            LightHandle::Directional(l) => Ref::map(l.borrow(), |l| l as &dyn three_d::Light),
+            LightHandle::Point(l) => Ref::map(l.borrow(), |l| l as &dyn three_d::Light),
+            LightHandle::Spot(l) => Ref::map(l.borrow(), |l| l as &dyn three_d::Light),
+        }
+    }
+}
+
+/// Falloff of a point or spot light with distance `d`:
+/// `1 / (constant + linear * d + quadratic * d^2)`. Defaults to no falloff.
+#[napi(object)]
+#[derive(Clone)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+impl From<&Attenuation> for three_d::Attenuation {
+    fn from(a: &Attenuation) -> Self {
+        three_d::Attenuation {
+            constant: a.constant as f32,
+            linear: a.linear as f32,
+            quadratic: a.quadratic as f32,
+        }
+    }
+}
+
+/// Light shining equally on every surface from every direction.
+#[napi]
+pub struct AmbientLight {
+    pub(crate) inner: Rc<RefCell<three_d::AmbientLight>>,
+}
 
 #[napi]
-pub struct AmbientLight {}
+impl AmbientLight {
+    #[napi(constructor)]
+    pub fn new(context: &Context, intensity: f64, color: &NSrgba) -> Self {
+        AmbientLight {
+            inner: Rc::new(RefCell::new(three_d::AmbientLight::new(
+                &context.inner,
+                intensity as f32,
+                Srgba::from(color),
+            ))),
+        }
+    }
 
+    #[napi(getter)]
+    pub fn intensity(&self) -> f64 {
+        self.inner.borrow().intensity as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_intensity(&self, intensity: f64) {
+        self.inner.borrow_mut().intensity = intensity as f32;
+    }
+
+    #[napi]
+    pub fn set_color(&self, color: &NSrgba) {
+        self.inner.borrow_mut().color = Srgba::from(color);
+    }
+}
+
+/// Light shining in one direction from infinitely far away, like the sun.
 #[napi]
-pub struct DirectionalLight {}
+pub struct DirectionalLight {
+    pub(crate) inner: Rc<RefCell<three_d::DirectionalLight>>,
+}
 
 #[napi]
-pub struct PointLight {}
+impl DirectionalLight {
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        intensity: f64,
+        color: &NSrgba,
+        direction: Vec<f64>,
+    ) -> Result<Self> {
+        Ok(DirectionalLight {
+            inner: Rc::new(RefCell::new(three_d::DirectionalLight::new(
+                &context.inner,
+                intensity as f32,
+                Srgba::from(color),
+                light_direction(&direction)?,
+            ))),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn intensity(&self) -> f64 {
+        self.inner.borrow().intensity as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_intensity(&self, intensity: f64) {
+        self.inner.borrow_mut().intensity = intensity as f32;
+    }
 
+    #[napi]
+    pub fn set_color(&self, color: &NSrgba) {
+        self.inner.borrow_mut().color = Srgba::from(color);
+    }
+
+    #[napi]
+    pub fn get_direction(&self) -> Vec<f64> {
+        to_array(self.inner.borrow().direction)
+    }
+
+    #[napi]
+    pub fn set_direction(&self, direction: Vec<f64>) -> Result<()> {
+        self.inner.borrow_mut().direction = light_direction(&direction)?;
+        Ok(())
+    }
+}
+
+/// Light shining in every direction from a position.
 #[napi]
-pub struct SpotLight {}
+pub struct PointLight {
+    pub(crate) inner: Rc<RefCell<three_d::PointLight>>,
+}
+
+#[napi]
+impl PointLight {
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        intensity: f64,
+        color: &NSrgba,
+        position: Vec<f64>,
+        attenuation: Option<Attenuation>,
+    ) -> Result<Self> {
+        Ok(PointLight {
+            inner: Rc::new(RefCell::new(three_d::PointLight::new(
+                &context.inner,
+                intensity as f32,
+                Srgba::from(color),
+                light_position(&position)?,
+                attenuation.as_ref().map(Into::into).unwrap_or_default(),
+            ))),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn intensity(&self) -> f64 {
+        self.inner.borrow().intensity as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_intensity(&self, intensity: f64) {
+        self.inner.borrow_mut().intensity = intensity as f32;
+    }
+
+    #[napi]
+    pub fn set_color(&self, color: &NSrgba) {
+        self.inner.borrow_mut().color = Srgba::from(color);
+    }
+
+    #[napi]
+    pub fn get_position(&self) -> Vec<f64> {
+        to_array(self.inner.borrow().position)
+    }
+
+    #[napi]
+    pub fn set_position(&self, position: Vec<f64>) -> Result<()> {
+        self.inner.borrow_mut().position = light_position(&position)?;
+        Ok(())
+    }
+}
+
+/// Light shining in a cone from a position. `cutoff` is the angle in degrees
+/// between the direction and the edge of the cone.
+#[napi]
+pub struct SpotLight {
+    pub(crate) inner: Rc<RefCell<three_d::SpotLight>>,
+}
+
+#[napi]
+impl SpotLight {
+    #[allow(clippy::too_many_arguments)]
+    #[napi(constructor)]
+    pub fn new(
+        context: &Context,
+        intensity: f64,
+        color: &NSrgba,
+        position: Vec<f64>,
+        direction: Vec<f64>,
+        cutoff: f64,
+        attenuation: Option<Attenuation>,
+    ) -> Result<Self> {
+        if !(cutoff > 0.0 && cutoff <= 90.0) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Spot light cutoff must be in (0, 90] degrees, got {cutoff}"),
+            ));
+        }
+        Ok(SpotLight {
+            inner: Rc::new(RefCell::new(three_d::SpotLight::new(
+                &context.inner,
+                intensity as f32,
+                Srgba::from(color),
+                light_position(&position)?,
+                light_direction(&direction)?,
+                three_d::degrees(cutoff as f32),
+                attenuation.as_ref().map(Into::into).unwrap_or_default(),
+            ))),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn intensity(&self) -> f64 {
+        self.inner.borrow().intensity as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_intensity(&self, intensity: f64) {
+        self.inner.borrow_mut().intensity = intensity as f32;
+    }
+
+    #[napi]
+    pub fn set_color(&self, color: &NSrgba) {
+        self.inner.borrow_mut().color = Srgba::from(color);
+    }
+
+    #[napi]
+    pub fn set_position(&self, position: Vec<f64>) -> Result<()> {
+        self.inner.borrow_mut().position = light_position(&position)?;
+        Ok(())
+    }
+
+    #[napi]
+    pub fn set_direction(&self, direction: Vec<f64>) -> Result<()> {
+        self.inner.borrow_mut().direction = light_direction(&direction)?;
+        Ok(())
+    }
+}
 
 #[napi]
 pub struct Environment {}
+
+fn light_position(position: &[f64]) -> Result<Vec3> {
+    let [x, y, z] = components("Position", position)?;
+    Ok(three_d::vec3(x as f32, y as f32, z as f32))
+}
+
+fn light_direction(direction: &[f64]) -> Result<Vec3> {
+    let [x, y, z] = components("Direction", direction)?;
+    let direction = three_d::vec3(x as f32, y as f32, z as f32);
+    if !(direction.magnitude2() > 0.0 && direction.magnitude2().is_finite()) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "Light direction must be a non-zero vector".to_string(),
+        ));
+    }
+    Ok(direction.normalize())
+}
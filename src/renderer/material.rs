@@ -1,13 +1,340 @@
+use crate::core::compressed::{CompressedStorage, CompressedTexture2D, NativeCompressedTexture};
+use crate::core::texture::{
+    DepthTexture2D, DepthTexture2DArray, DepthTextureCubeMap, Texture2D, Texture2DArray, Texture3D,
+    TextureCubeMap,
+};
+use crate::enums::Transparency;
+use crate::prelude::NSrgba;
+use napi::bindgen_prelude::Either4;
+use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::cell::{Ref, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Arc;
+use three_d::{
+    Blend, BlendEquationType, BlendMultiplierType, EffectMaterialId, Light, MaterialType, Program,
+    RenderStates, SquareMatrix, Srgba, Viewer, WriteMask,
+};
 
+/// Material handle shared between the JS material objects and the `Gm`s
+/// using them, borrowed while rendering.
+#[derive(Clone)]
+pub(crate) enum MaterialHandle {
+    Color(Rc<RefCell<three_d::ColorMaterial>>),
+    Physical(Rc<RefCell<three_d::PhysicalMaterial>>),
+    Custom(Rc<RefCell<CustomMaterialState>>),
+    Deferred(Rc<RefCell<three_d::DeferredPhysicalMaterial>>),
+}
+
+/// Any material accepted by `Gm` and the render functions.
+pub type MaterialInput<'a> = Either4<
+    &'a ColorMaterial,
+    &'a PhysicalMaterial,
+    &'a CustomMaterial,
+    &'a DeferredPhysicalMaterial,
+>;
+
+impl From<MaterialInput<'_>> for MaterialHandle {
+    fn from(material: MaterialInput) -> Self {
+        match material {
+            Either4::A(m) => MaterialHandle::Color(m.inner.clone()),
+            Either4::B(m) => MaterialHandle::Physical(m.inner.clone()),
+            Either4::C(m) => MaterialHandle::Custom(m.inner.clone()),
+            Either4::D(m) => MaterialHandle::Deferred(m.inner.clone()),
+        }
+    }
+}
+
+impl MaterialHandle {
+    /// Checks that a custom shader compiles with `geometry`, see
+    /// [`CustomMaterialState::check_program`].
+    pub(crate) fn check(
+        &self,
+        context: &three_d::Context,
+        geometry: &dyn three_d::Geometry,
+    ) -> Result<()> {
+        match self {
+            MaterialHandle::Custom(m) => m
+                .borrow_mut()
+                .check_program(context, &geometry.vertex_shader_source()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of textures the material binds when drawn.
+    pub(crate) fn texture_count(&self) -> u32 {
+        let count = |bound: [bool; 5]| bound.into_iter().filter(|&bound| bound).count() as u32;
+        match self {
+            MaterialHandle::Color(m) => m.borrow().texture.is_some() as u32,
+            MaterialHandle::Physical(m) => {
+                let m = m.borrow();
+                count([
+                    m.albedo_texture.is_some(),
+                    m.metallic_roughness_texture.is_some(),
+                    m.occlusion_texture.is_some(),
+                    m.normal_texture.is_some(),
+                    m.emissive_texture.is_some(),
+                ])
+            }
+            MaterialHandle::Custom(m) => m.borrow().inputs.samplers.len() as u32,
+            MaterialHandle::Deferred(m) => {
+                let m = m.borrow();
+                count([
+                    m.albedo_texture.is_some(),
+                    m.metallic_roughness_texture.is_some(),
+                    m.occlusion_texture.is_some(),
+                    m.normal_texture.is_some(),
+                    m.emissive_texture.is_some(),
+                ])
+            }
+        }
+    }
+
+    pub(crate) fn borrow(&self) -> Ref<'_, dyn three_d::Material> {
+        match self {
+            MaterialHandle::Color(m) => Ref::map(m.borrow(), |m| m as &dyn three_d::Material),
+            MaterialHandle::Physical(m) => Ref::map(m.borrow(), |m| m as &dyn three_d::Material),
+            MaterialHandle::Custom(m) => Ref::map(m.borrow(), |m| m as &dyn three_d::Material),
+            MaterialHandle::Deferred(m) => Ref::map(m.borrow(), |m| m as &dyn three_d::Material),
+        }
+    }
+}
+
+/// Render states for a material blended with `transparency`, and whether it
+/// is drawn after the opaque objects, back to front.
+fn blend_states(transparency: Transparency) -> (RenderStates, bool) {
+    let blend = |source, destination| Blend::Enabled {
+        source_rgb_multiplier: source,
+        source_alpha_multiplier: BlendMultiplierType::Zero,
+        destination_rgb_multiplier: destination,
+        destination_alpha_multiplier: BlendMultiplierType::One,
+        rgb_equation: BlendEquationType::Add,
+        alpha_equation: BlendEquationType::Add,
+    };
+    let blend = match transparency {
+        Transparency::Opaque => return (RenderStates::default(), false),
+        Transparency::Alpha => Blend::TRANSPARENCY,
+        Transparency::Additive => blend(BlendMultiplierType::SrcAlpha, BlendMultiplierType::One),
+        Transparency::Multiply => blend(BlendMultiplierType::DstColor, BlendMultiplierType::Zero),
+    };
+    let states = RenderStates {
+        write_mask: WriteMask::COLOR,
+        blend,
+        ..Default::default()
+    };
+    (states, true)
+}
+
+/// The transparency of a material without an explicit one: `Alpha` for
+/// translucent colors and `Opaque` otherwise.
+fn automatic_transparency(color: Srgba) -> Transparency {
+    if color.a < 255 {
+        Transparency::Alpha
+    } else {
+        Transparency::Opaque
+    }
+}
+
+/// A material with a single color, optionally multiplied by a texture and
+/// per vertex colors. Not affected by lights.
 #[napi]
-pub struct ColorMaterial {}
+pub struct ColorMaterial {
+    pub(crate) inner: Rc<RefCell<three_d::ColorMaterial>>,
+    transparency: Option<Transparency>,
+}
 
 #[napi]
-pub struct PhysicalMaterial {}
+impl ColorMaterial {
+    #[napi(constructor)]
+    pub fn new(color: &NSrgba, texture: Option<&Texture2D>) -> Self {
+        let material = ColorMaterial {
+            inner: Rc::new(RefCell::new(three_d::ColorMaterial {
+                texture: texture.map(|texture| texture.inner.clone().into()),
+                ..Default::default()
+            })),
+            transparency: None,
+        };
+        material.set_color(color);
+        material
+    }
+
+    #[napi]
+    pub fn set_color(&self, color: &NSrgba) {
+        self.inner.borrow_mut().color = Srgba::from(color);
+        self.update_blending();
+    }
 
+    /// How the material blends with what is behind it. Unless set, `Alpha`
+    /// for translucent colors and `Opaque` otherwise.
+    #[napi(getter)]
+    pub fn transparency(&self) -> Transparency {
+        self.transparency
+            .unwrap_or_else(|| automatic_transparency(self.inner.borrow().color))
+    }
+
+    #[napi(setter)]
+    pub fn set_transparency(&mut self, transparency: Transparency) {
+        self.transparency = Some(transparency);
+        self.update_blending();
+    }
+}
+
+impl ColorMaterial {
+    fn update_blending(&self) {
+        let (render_states, is_transparent) = blend_states(self.transparency());
+        let mut inner = self.inner.borrow_mut();
+        inner.render_states = render_states;
+        inner.is_transparent = is_transparent;
+    }
+}
+
+/// A physically based material lit by the lights passed to the renderer.
+/// `metallic` and `roughness` are in `[0, 1]` and default to 0 and 1.
 #[napi]
-pub struct DeferredPhysicalMaterial {}
+pub struct PhysicalMaterial {
+    pub(crate) inner: Rc<RefCell<three_d::PhysicalMaterial>>,
+    transparency: Option<Transparency>,
+}
+
+#[napi]
+impl PhysicalMaterial {
+    #[napi(constructor)]
+    pub fn new(
+        albedo: &NSrgba,
+        metallic: Option<f64>,
+        roughness: Option<f64>,
+        emissive: Option<&NSrgba>,
+    ) -> Result<Self> {
+        let material = PhysicalMaterial {
+            inner: Rc::new(RefCell::new(three_d::PhysicalMaterial {
+                albedo: Srgba::from(albedo),
+                emissive: emissive.map_or(Srgba::BLACK, Srgba::from),
+                ..Default::default()
+            })),
+            transparency: None,
+        };
+        material.update_blending();
+        material.set_metallic(metallic.unwrap_or(0.0))?;
+        material.set_roughness(roughness.unwrap_or(1.0))?;
+        Ok(material)
+    }
+
+    #[napi(getter)]
+    pub fn metallic(&self) -> f64 {
+        self.inner.borrow().metallic as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_metallic(&self, metallic: f64) -> Result<()> {
+        self.inner.borrow_mut().metallic = unit_interval("Metallic", metallic)?;
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn roughness(&self) -> f64 {
+        self.inner.borrow().roughness as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_roughness(&self, roughness: f64) -> Result<()> {
+        self.inner.borrow_mut().roughness = unit_interval("Roughness", roughness)?;
+        Ok(())
+    }
+
+    /// How the material blends with what is behind it. Unless set, `Alpha`
+    /// for a translucent albedo and `Opaque` otherwise.
+    #[napi(getter)]
+    pub fn transparency(&self) -> Transparency {
+        self.transparency
+            .unwrap_or_else(|| automatic_transparency(self.inner.borrow().albedo))
+    }
+
+    #[napi(setter)]
+    pub fn set_transparency(&mut self, transparency: Transparency) {
+        self.transparency = Some(transparency);
+        self.update_blending();
+    }
+}
+
+impl PhysicalMaterial {
+    fn update_blending(&self) {
+        let (render_states, is_transparent) = blend_states(self.transparency());
+        let mut inner = self.inner.borrow_mut();
+        inner.render_states = render_states;
+        inner.is_transparent = is_transparent;
+    }
+}
+
+fn unit_interval(name: &str, value: f64) -> Result<f32> {
+    if !(0.0..=1.0).contains(&value) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name} must be in [0, 1], got {value}"),
+        ));
+    }
+    Ok(value as f32)
+}
+
+/// A physically based material shaded in two passes by `Renderer.render`:
+/// the surface is written to a G-buffer and lit once per pixel, so the cost
+/// of many lights does not grow with overdraw. It cannot be transparent and
+/// is only drawn by `Renderer.render`. Lighting uses the Cook-Torrance model,
+/// so highlights are sharper than those of `PhysicalMaterial`.
+#[napi]
+pub struct DeferredPhysicalMaterial {
+    pub(crate) inner: Rc<RefCell<three_d::DeferredPhysicalMaterial>>,
+}
+
+#[napi]
+impl DeferredPhysicalMaterial {
+    /// `metallic` and `roughness` are in `[0, 1]` and default to 0 and 1.
+    /// The alpha of `albedo` is ignored.
+    #[napi(constructor)]
+    pub fn new(
+        albedo: &NSrgba,
+        metallic: Option<f64>,
+        roughness: Option<f64>,
+        emissive: Option<&NSrgba>,
+    ) -> Result<Self> {
+        let physical = PhysicalMaterial::new(albedo, metallic, roughness, emissive)?;
+        physical.inner.borrow_mut().is_transparent = false;
+        Ok(Self::from_physical_material(&physical))
+    }
+
+    /// Copies a physical material. Translucent materials are cut out where
+    /// their alpha is below 0.5 instead of blended.
+    #[napi(factory)]
+    pub fn from_physical_material(material: &PhysicalMaterial) -> Self {
+        DeferredPhysicalMaterial {
+            inner: Rc::new(RefCell::new(
+                three_d::DeferredPhysicalMaterial::from_physical_material(&material.inner.borrow()),
+            )),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn metallic(&self) -> f64 {
+        self.inner.borrow().metallic as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_metallic(&self, metallic: f64) -> Result<()> {
+        self.inner.borrow_mut().metallic = unit_interval("Metallic", metallic)?;
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn roughness(&self) -> f64 {
+        self.inner.borrow().roughness as f64
+    }
+
+    #[napi(setter)]
+    pub fn set_roughness(&self, roughness: f64) -> Result<()> {
+        self.inner.borrow_mut().roughness = unit_interval("Roughness", roughness)?;
+        Ok(())
+    }
+}
 
 #[napi]
 pub struct DepthMaterial {}
@@ -23,3 +350,335 @@ pub struct PositionMaterial {}
 
 #[napi]
 pub struct UVMaterial {}
+
+/// Texture bound to a sampler uniform of a [`CustomMaterial`].
+#[derive(Clone)]
+pub(crate) enum SamplerInput {
+    Texture2D(Arc<three_d::Texture2D>),
+    Texture2DArray(Arc<three_d::Texture2DArray>),
+    Texture3D(Arc<three_d::Texture3D>),
+    TextureCubeMap(Arc<three_d::TextureCubeMap>),
+    Compressed(Arc<NativeCompressedTexture>),
+    DepthTexture2D(Arc<three_d::DepthTexture2D>),
+    DepthTexture2DArray(Arc<three_d::DepthTexture2DArray>),
+    DepthTextureCubeMap(Arc<three_d::DepthTextureCubeMap>),
+}
+
+/// Textures and uniform values bound by name to a user supplied shader.
+#[derive(Default)]
+pub(crate) struct ShaderInputs {
+    samplers: HashMap<String, SamplerInput>,
+    uniforms: HashMap<String, Vec<f32>>,
+}
+
+/// Shared state of a [`CustomMaterial`], implementing three-d's `Material`.
+pub(crate) struct CustomMaterialState {
+    fragment_source: String,
+    id: u16,
+    inputs: ShaderInputs,
+    transparency: Transparency,
+    // Vertex shaders the fragment shader is known to link with.
+    checked: HashSet<String>,
+}
+
+thread_local! {
+    // Programs are cached by material id, so every distinct shader source needs its own id.
+    static MATERIAL_IDS: RefCell<HashMap<String, u16>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the id for a custom fragment shader, allocated from three-d's public id range.
+pub(crate) fn material_id(fragment_source: &str) -> Result<u16> {
+    const PUBLIC_IDS: u16 = 0x5000;
+    MATERIAL_IDS.with(|ids| {
+        let mut ids = ids.borrow_mut();
+        if let Some(id) = ids.get(fragment_source) {
+            return Ok(*id);
+        }
+        let id = ids.len() as u16;
+        if id >= PUBLIC_IDS {
+            return Err(Error::new(
+                Status::GenericFailure,
+                "Too many distinct custom material shaders",
+            ));
+        }
+        ids.insert(fragment_source.to_owned(), id);
+        Ok(id)
+    })
+}
+
+/// Vertex shader three-d uses for full screen passes, see `apply_screen_material`.
+pub(crate) const SCREEN_VERTEX_SHADER: &str = "
+    out vec2 uvs;
+    out vec4 col;
+    void main()
+    {
+        vec3 vertices[3] = vec3[3](vec3(-3.0, -1.0, 0.0), vec3(3.0, -1.0, 0.0), vec3(0.0, 2.0, 0.0));
+        vec3 position = vertices[gl_VertexID];
+        uvs = 0.5 * position.xy + 0.5;
+        col = vec4(1.0);
+        gl_Position = vec4(position, 1.0);
+    }
+";
+
+impl CustomMaterialState {
+    /// Compiles the shader for a full screen pass once, so that errors are
+    /// reported instead of panicking inside three-d's program cache.
+    pub(crate) fn check_screen_program(&mut self, context: &three_d::Context) -> Result<()> {
+        self.check_program(context, SCREEN_VERTEX_SHADER)
+    }
+
+    /// Compiles the shader with the vertex shader of a geometry once, see
+    /// [`Self::check_screen_program`].
+    pub(crate) fn check_program(
+        &mut self,
+        context: &three_d::Context,
+        vertex_source: &str,
+    ) -> Result<()> {
+        if !self.checked.contains(vertex_source) {
+            check_shader(context, vertex_source, &self.fragment_source, "material")?;
+            self.checked.insert(vertex_source.to_owned());
+        }
+        Ok(())
+    }
+}
+
+/// Compiles and links a program, reporting errors for the `kind` of shader.
+pub(crate) fn check_shader(
+    context: &three_d::Context,
+    vertex_source: &str,
+    fragment_source: &str,
+    kind: &str,
+) -> Result<()> {
+    Program::from_source(context, vertex_source, fragment_source)
+        .map(drop)
+        .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid {kind} shader: {e}")))
+}
+
+impl ShaderInputs {
+    pub(crate) fn set_sampler(&mut self, name: String, sampler: SamplerInput) {
+        self.samplers.insert(name, sampler);
+    }
+
+    /// Sets a `float`, `vec2`, `vec3`, `vec4` or column-major `mat4` uniform,
+    /// chosen by the number of values.
+    pub(crate) fn set_uniform(&mut self, name: String, values: Vec<f64>) -> Result<()> {
+        if !matches!(values.len(), 1..=4 | 16) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Expected 1, 2, 3, 4 or 16 values, got {}", values.len()),
+            ));
+        }
+        let values = values.iter().map(|&v| v as f32).collect();
+        self.uniforms.insert(name, values);
+        Ok(())
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) {
+        self.samplers.remove(name);
+        self.uniforms.remove(name);
+    }
+
+    /// Sends the bound values to `program`.
+    pub(crate) fn use_uniforms(&self, program: &Program) {
+        // Unused uniforms are optimized away by the shader compiler and three-d
+        // panics when sending them, so only send what the program declares.
+        // three-d assigns texture units from zero upwards, so natively compressed
+        // textures, which it cannot bind, take units from the top instead.
+        let mut compressed_unit = None;
+        for (name, sampler) in self.samplers.iter() {
+            if !program.requires_uniform(name) {
+                continue;
+            }
+            match sampler {
+                SamplerInput::Texture2D(texture) => program.use_texture(name, texture),
+                SamplerInput::Texture2DArray(texture) => program.use_texture_array(name, texture),
+                SamplerInput::Texture3D(texture) => program.use_texture_3d(name, texture),
+                SamplerInput::TextureCubeMap(texture) => program.use_texture_cube(name, texture),
+                SamplerInput::DepthTexture2D(texture) => program.use_depth_texture(name, texture),
+                SamplerInput::DepthTexture2DArray(texture) => {
+                    program.use_depth_texture_array(name, texture)
+                }
+                SamplerInput::DepthTextureCubeMap(texture) => {
+                    program.use_depth_texture_cube(name, texture)
+                }
+                SamplerInput::Compressed(texture) => {
+                    use three_d::context::HasContext;
+                    // SAFETY: the texture belongs to the context the program renders with.
+                    unsafe {
+                        let unit = compressed_unit.unwrap_or_else(|| {
+                            texture.context.get_parameter_i32(
+                                three_d::context::MAX_COMBINED_TEXTURE_IMAGE_UNITS,
+                            )
+                        }) - 1;
+                        compressed_unit = Some(unit);
+                        program.use_uniform(name, unit);
+                        texture
+                            .context
+                            .active_texture(three_d::context::TEXTURE0 + unit as u32);
+                        texture
+                            .context
+                            .bind_texture(three_d::context::TEXTURE_2D, Some(texture.id));
+                    }
+                }
+            }
+        }
+        for (name, values) in self.uniforms.iter() {
+            if !program.requires_uniform(name) {
+                continue;
+            }
+            match values.as_slice() {
+                [x] => program.use_uniform(name, *x),
+                [x, y] => program.use_uniform(name, three_d::vec2(*x, *y)),
+                [x, y, z] => program.use_uniform(name, three_d::vec3(*x, *y, *z)),
+                [x, y, z, w] => program.use_uniform(name, three_d::vec4(*x, *y, *z, *w)),
+                values if values.len() == 16 => {
+                    let mut matrix = three_d::Mat4::identity();
+                    for (i, value) in values.iter().enumerate() {
+                        matrix[i / 4][i % 4] = *value;
+                    }
+                    program.use_uniform(name, matrix)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl three_d::Material for CustomMaterialState {
+    fn fragment_shader_source(&self, _lights: &[&dyn Light]) -> String {
+        self.fragment_source.clone()
+    }
+
+    fn id(&self) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(&self, program: &Program, _viewer: &dyn Viewer, _lights: &[&dyn Light]) {
+        self.inputs.use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        blend_states(self.transparency).0
+    }
+
+    fn material_type(&self) -> MaterialType {
+        match blend_states(self.transparency).1 {
+            true => MaterialType::Transparent,
+            false => MaterialType::Opaque,
+        }
+    }
+}
+
+/// A material defined by a user supplied GLSL fragment shader.
+///
+/// The shader must write to `layout (location = 0) out vec4 outColor;` and can read
+/// the geometry outputs provided by three-d, such as `in vec3 pos;` and `in vec2 uvs;`.
+/// Textures are bound to `sampler2D`, `sampler2DArray` and `sampler3D` uniforms by name.
+#[napi]
+pub struct CustomMaterial {
+    pub(crate) inner: Rc<RefCell<CustomMaterialState>>,
+}
+
+#[napi]
+impl CustomMaterial {
+    #[napi(constructor)]
+    pub fn new(fragment_source: String) -> Result<Self> {
+        let id = material_id(&fragment_source)?;
+        Ok(CustomMaterial {
+            inner: Rc::new(RefCell::new(CustomMaterialState {
+                fragment_source,
+                id,
+                inputs: ShaderInputs::default(),
+                transparency: Transparency::Opaque,
+                checked: HashSet::new(),
+            })),
+        })
+    }
+
+    /// Binds a 2D texture to a `sampler2D` uniform.
+    #[napi]
+    pub fn set_texture(&self, name: String, texture: &Texture2D) {
+        self.set_sampler(name, SamplerInput::Texture2D(texture.inner.clone()));
+    }
+
+    /// Binds a compressed 2D texture to a `sampler2D` uniform.
+    #[napi]
+    pub fn set_compressed_texture(&self, name: String, texture: &CompressedTexture2D) {
+        let sampler = match &texture.storage {
+            CompressedStorage::Native(texture) => SamplerInput::Compressed(texture.clone()),
+            CompressedStorage::Decompressed(texture) => SamplerInput::Texture2D(texture.clone()),
+        };
+        self.set_sampler(name, sampler);
+    }
+
+    /// Binds a texture array to a `sampler2DArray` uniform.
+    #[napi]
+    pub fn set_texture_array(&self, name: String, texture: &Texture2DArray) {
+        self.set_sampler(name, SamplerInput::Texture2DArray(texture.inner.clone()));
+    }
+
+    /// Binds a 3D texture to a `sampler3D` uniform.
+    #[napi(js_name = "setTexture3D")]
+    pub fn set_texture_3d(&self, name: String, texture: &Texture3D) {
+        self.set_sampler(name, SamplerInput::Texture3D(texture.inner.clone()));
+    }
+
+    /// Binds a cube map to a `samplerCube` uniform.
+    #[napi]
+    pub fn set_texture_cube_map(&self, name: String, texture: &TextureCubeMap) {
+        self.set_sampler(name, SamplerInput::TextureCubeMap(texture.inner.clone()));
+    }
+
+    /// Binds a depth texture to a `sampler2D` uniform.
+    #[napi]
+    pub fn set_depth_texture(&self, name: String, texture: &DepthTexture2D) {
+        self.set_sampler(name, SamplerInput::DepthTexture2D(texture.inner.clone()));
+    }
+
+    /// Binds a depth texture array to a `sampler2DArray` uniform.
+    #[napi]
+    pub fn set_depth_texture_array(&self, name: String, texture: &DepthTexture2DArray) {
+        self.set_sampler(
+            name,
+            SamplerInput::DepthTexture2DArray(texture.inner.clone()),
+        );
+    }
+
+    /// Binds a depth cube map to a `samplerCube` uniform.
+    #[napi]
+    pub fn set_depth_texture_cube_map(&self, name: String, texture: &DepthTextureCubeMap) {
+        self.set_sampler(
+            name,
+            SamplerInput::DepthTextureCubeMap(texture.inner.clone()),
+        );
+    }
+
+    /// Sets a `float`, `vec2`, `vec3`, `vec4` or column-major `mat4` uniform,
+    /// chosen by the number of values.
+    #[napi]
+    pub fn set_uniform(&self, name: String, values: Vec<f64>) -> Result<()> {
+        self.inner.borrow_mut().inputs.set_uniform(name, values)
+    }
+
+    /// Removes a texture or uniform binding.
+    #[napi]
+    pub fn remove(&self, name: String) {
+        self.inner.borrow_mut().inputs.remove(&name);
+    }
+
+    /// How the shader output blends with what is behind it. Defaults to
+    /// `Opaque`.
+    #[napi(getter)]
+    pub fn transparency(&self) -> Transparency {
+        self.inner.borrow().transparency
+    }
+
+    #[napi(setter)]
+    pub fn set_transparency(&self, transparency: Transparency) {
+        self.inner.borrow_mut().transparency = transparency;
+    }
+
+    fn set_sampler(&self, name: String, sampler: SamplerInput) {
+        self.inner.borrow_mut().inputs.set_sampler(name, sampler);
+    }
+}
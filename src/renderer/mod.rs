@@ -1,54 +1,884 @@
+use napi::bindgen_prelude::{Buffer, ClassInstance, Either, Function, FunctionRef};
+use napi::{Env, Error, Result, Status};
 use napi_derive::napi;
+use three_d::{
+    f16, ColorTexture, DepthTexture, Geometry, InnerSpace, Interpolation, Object, Viewer, Wrapping,
+};
 
+use crate::context::Context;
+use crate::core::render_target::{
+    target_size, write_target, AnyRenderTarget, ColorTargetMultisample, DepthTargetMultisample,
+    RenderTargetMultisample,
+};
+use crate::core::texture::{DepthTexture2D, Texture2D};
+use crate::core::viewport::Viewport;
+use crate::enums::{DataType, GBufferTexture, TextureFormat};
+use crate::prelude::NSrgba;
+use camera::{components, to_array};
+use deferred::GBuffer;
+use effect::ScreenEffect;
+use geometry::Mesh;
+use light::{LightHandle, LightInput};
+use material::{MaterialHandle, MaterialInput};
+use object::{Gm, Skybox};
+use oit::OitBuffers;
+use scene::Scene;
+use stats::{DrawCost, FrameRecorder, FrameStats};
+use tone_mapping::ToneMapEffect;
+
+pub mod camera;
+pub mod camera_path;
 pub mod control;
+pub mod deferred;
 pub mod effect;
 pub mod geometry;
 pub mod light;
 pub mod material;
 pub mod object;
+pub mod oit;
+pub mod scene;
+pub mod stats;
+pub mod stereo;
+pub mod tone_mapping;
+
+pub use camera::Camera;
 
-// Wrapper for Camera
+/// Offscreen renderer owning a context and a color and depth buffer of a
+/// fixed size. Draws objects with lights for a camera and reads the result
+/// back as pixels or a PNG.
 #[napi]
-pub struct Camera {
-    // impl details
+pub struct Renderer {
+    title: String,
+    width: u32,
+    height: u32,
+    frustum_culling: bool,
+    order_independent_transparency: bool,
+    gpu_timing: bool,
+    samples: u32,
+    clear_color: [f32; 4],
+    frame: Option<Frame>,
+    last_frame_stats: Option<FrameStats>,
+    stats_callback: Option<FunctionRef<FrameStats, ()>>,
+}
+
+/// GPU resources created by `Renderer::init`.
+struct Frame {
+    context: Context,
+    color: three_d::Texture2D,
+    depth: three_d::DepthTexture2D,
+    /// Linear frame for cameras whose output three-d cannot apply itself.
+    hdr: three_d::Texture2D,
+    /// Created on the first frame drawn with order-independent transparency.
+    oit: Option<OitBuffers>,
+    /// Written by the last frame if it had deferred objects.
+    gbuffer: Option<GBuffer>,
+    /// Created on the first frame drawn with more than one sample, with the
+    /// sample count it was asked for.
+    multisample: Option<(u32, RenderTargetMultisample)>,
 }
 
 #[napi]
-impl Camera {
+impl Renderer {
+    /// Creates a renderer for `width` x `height` pixel frames. Call `init`
+    /// before rendering.
     #[napi(constructor)]
-    pub fn new() -> Self {
-        Camera {}
+    pub fn new(width: u32, height: u32, title: Option<String>) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Renderer size must be positive, got {width}x{height}"),
+            ));
+        }
+        Ok(Renderer {
+            title: title.unwrap_or_else(|| "three-d".to_string()),
+            width,
+            height,
+            frustum_culling: true,
+            order_independent_transparency: false,
+            gpu_timing: false,
+            samples: 1,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            frame: None,
+            last_frame_stats: None,
+            stats_callback: None,
+        })
+    }
+
+    /// Creates the color and depth buffers on `context`, or on a new headless
+    /// context if none is given. Calling it again recreates the buffers.
+    #[napi]
+    pub fn init(&mut self, context: Option<&Context>) -> Result<()> {
+        let context = match context {
+            Some(context) => context.clone(),
+            None => Context::new()?,
+        };
+        let color_texture = || {
+            three_d::Texture2D::new_empty::<[f16; 4]>(
+                &context.inner,
+                self.width,
+                self.height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        let (color, hdr) = (color_texture(), color_texture());
+        let depth = three_d::DepthTexture2D::new::<f32>(
+            &context.inner,
+            self.width,
+            self.height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        self.frame = Some(Frame {
+            context,
+            color,
+            depth,
+            hdr,
+            oit: None,
+            gbuffer: None,
+            multisample: None,
+        });
+        Ok(())
+    }
+
+    /// The context the renderer draws with.
+    #[napi(getter)]
+    pub fn context(&self) -> Result<Context> {
+        Ok(self.frame()?.context.clone())
+    }
+
+    #[napi(getter)]
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    #[napi(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[napi(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns a short description of the renderer and its context.
+    #[napi]
+    pub fn get_info(&self) -> String {
+        let context = self
+            .frame
+            .as_ref()
+            .map_or("not initialized".to_string(), |frame| {
+                frame.context.get_info()
+            });
+        format!(
+            "Renderer {{ title: {}, width: {}, height: {}, context: {context} }}",
+            self.title, self.width, self.height
+        )
+    }
+
+    /// Sets the color each frame is cleared to. Defaults to opaque black.
+    #[napi]
+    pub fn set_clear_color(&mut self, color: &NSrgba) {
+        self.clear_color = [color.r, color.g, color.b, color.a].map(|c| c as f32);
+    }
+
+    /// Whether objects whose bounding boxes lie outside the camera frustum
+    /// are skipped. Enabled by default.
+    #[napi(getter)]
+    pub fn frustum_culling(&self) -> bool {
+        self.frustum_culling
+    }
+
+    #[napi(setter)]
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled;
+    }
+
+    /// Whether the passes of each frame are timed on the GPU for `gpuTime`
+    /// in the frame statistics. Reading the times makes every frame wait for
+    /// the GPU to finish it, so it is disabled by default.
+    #[napi(getter)]
+    pub fn gpu_timing(&self) -> bool {
+        self.gpu_timing
+    }
+
+    #[napi(setter)]
+    pub fn set_gpu_timing(&mut self, enabled: bool) {
+        self.gpu_timing = enabled;
+    }
+
+    /// The number of samples per pixel of the frame, clamped to the
+    /// context's maximum. With more than one, the edges of the opaque
+    /// objects and the skybox drawn by `render` and `renderScene` are
+    /// antialiased. Objects with a `DeferredPhysicalMaterial` and
+    /// order-independent transparency are drawn without multisampling. 1 by
+    /// default.
+    #[napi(getter)]
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    #[napi(setter)]
+    pub fn set_samples(&mut self, samples: u32) -> Result<()> {
+        if samples == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Samples must be at least 1, got 0",
+            ));
+        }
+        self.samples = samples;
+        Ok(())
+    }
+
+    /// Whether objects with `Transparency.Alpha` materials are drawn with
+    /// weighted blended order-independent transparency instead of sorted
+    /// back to front. Gives stable results for intersecting and heavily
+    /// overlapping transparent objects, at the cost of approximating the
+    /// order of the layers by their depth. Disabled by default and can be
+    /// changed between frames.
+    #[napi(getter)]
+    pub fn order_independent_transparency(&self) -> bool {
+        self.order_independent_transparency
+    }
+
+    #[napi(setter)]
+    pub fn set_order_independent_transparency(&mut self, enabled: bool) {
+        self.order_independent_transparency = enabled;
+    }
+
+    /// Clears the frame and draws `objects` lit by `lights` as seen from
+    /// `camera`. Objects with a `DeferredPhysicalMaterial` are written to a
+    /// G-buffer and lit in one pass over the frame first. Opaque objects are
+    /// drawn front to back before transparent ones back to front, or blended
+    /// in any order with `orderIndependentTransparency`. The camera's
+    /// viewport is set to the frame size, so `rayFromPixel`, `project`,
+    /// `unproject` and `flyTo` match the frame afterwards.
+    ///
+    /// With `options` only a viewport of the frame is cleared and drawn, so
+    /// several cameras can be drawn side by side into one frame before it is
+    /// read. The camera's viewport is then set to that viewport.
+    ///
+    /// If the camera's tone mapping is `Lottes`, `Uchimura` or `Unreal`, or
+    /// its exposure is not 1, the objects are drawn in linear color and the
+    /// camera's output settings are applied to the whole frame afterwards,
+    /// including unlit materials and the clear color.
+    #[napi]
+    pub fn render(
+        &mut self,
+        objects: Vec<&Gm>,
+        camera: &mut Camera,
+        lights: Option<Vec<LightInput>>,
+        options: Option<ViewportOptions>,
+        env: Env,
+    ) -> Result<()> {
+        let objects: Vec<Gm> = objects.into_iter().cloned().collect();
+        let stats = self.draw(&objects, camera, &light_handles(lights), options)?;
+        self.report(&env, stats)
+    }
+
+    /// Draws every object of `scene` lit by every light attached to it, like
+    /// `render`.
+    #[napi]
+    pub fn render_scene(
+        &mut self,
+        scene: &Scene,
+        camera: &mut Camera,
+        options: Option<ViewportOptions>,
+        env: Env,
+    ) -> Result<()> {
+        let (objects, lights) = scene.gather();
+        let stats = self.draw(&objects, camera, &lights, options)?;
+        self.report(&env, stats)
+    }
+
+    /// Statistics of the last frame drawn by `render` or `renderScene`, or
+    /// `null` before the first. GPU times are only measured with
+    /// `gpuTiming` enabled.
+    #[napi]
+    pub fn last_frame_stats(&self) -> Option<FrameStats> {
+        self.last_frame_stats.clone()
+    }
+
+    /// Calls `callback` with the statistics of every frame drawn from now on,
+    /// or stops calling the previous one for `null`.
+    #[napi]
+    pub fn set_stats_callback(&mut self, callback: Option<Function<FrameStats, ()>>) -> Result<()> {
+        self.stats_callback = callback.map(|callback| callback.create_ref()).transpose()?;
+        Ok(())
+    }
+
+    /// Reads the last frame, top row first, as RGBA with one byte per
+    /// channel, or with one 32-bit float per channel for `DataType.Float`.
+    #[napi]
+    pub fn read_pixels(&mut self, data_type: Option<DataType>) -> Result<Buffer> {
+        let frame = self.frame_mut()?;
+        let target = three_d::RenderTarget::new(
+            frame.color.as_color_target(None),
+            frame.depth.as_depth_target(),
+        );
+        let bytes = match data_type.unwrap_or(DataType::UnsignedByte) {
+            DataType::UnsignedByte => target.read_color::<[u8; 4]>().into_flattened(),
+            DataType::Float => target
+                .read_color::<[f32; 4]>()
+                .into_flattened()
+                .into_iter()
+                .flat_map(f32::to_ne_bytes)
+                .collect(),
+            data_type => {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!("Pixels can be read as UnsignedByte or Float, got {data_type:?}"),
+                ))
+            }
+        };
+        Ok(bytes.into())
+    }
+
+    /// Reads a texture of the G-buffer of the last frame, top row first, as
+    /// RGBA with one 32-bit float per channel. Pixels without deferred
+    /// objects are zero, except for `Depth`, which is 1 there. The texture
+    /// has the size of the viewport drawn. Fails if the last frame had no
+    /// objects with a `DeferredPhysicalMaterial`.
+    #[napi(js_name = "readGBuffer")]
+    pub fn read_gbuffer(&self, texture: GBufferTexture) -> Result<Buffer> {
+        let frame = self.frame()?;
+        let gbuffer = frame.gbuffer.as_ref().ok_or_else(|| {
+            Error::new(
+                Status::GenericFailure,
+                "The last frame has no deferred objects",
+            )
+        })?;
+        let pixels = gbuffer.read(&frame.context.inner, texture)?;
+        let bytes: Vec<u8> = pixels
+            .into_flattened()
+            .into_iter()
+            .flat_map(f32::to_ne_bytes)
+            .collect();
+        Ok(bytes.into())
+    }
+
+    /// Finds the object drawn at pixel `(x, y)`, measured from the bottom left
+    /// corner of the frame like `Camera.rayFromPixel`, by rendering the
+    /// objects' geometry on the GPU. Returns `null` if nothing is hit between
+    /// the camera's near and far planes. Pass the `viewport` the camera was
+    /// rendered into with `ViewportOptions`; it defaults to the full frame.
+    #[napi]
+    pub fn pick(
+        &self,
+        camera: &Camera,
+        x: f64,
+        y: f64,
+        objects: Vec<&Gm>,
+        viewport: Option<&Viewport>,
+    ) -> Result<Option<IntersectionResult>> {
+        let frame = self.frame()?;
+        let viewport = match viewport {
+            Some(viewport) => frame_viewport(viewport, self.width, self.height)?,
+            None => three_d::Viewport::new_at_origo(self.width, self.height),
+        };
+        let mut camera = camera.inner.clone();
+        camera.set_viewport(viewport);
+        let geometries: Vec<_> = objects.iter().map(|o| o.geometry.borrow()).collect();
+        let result = three_d::pick(
+            &frame.context.inner,
+            &camera,
+            (x as f32, y as f32),
+            geometries.iter().map(|g| &**g),
+        );
+        Ok(result.map(IntersectionResult::from))
+    }
+
+    /// Finds the first object hit by the ray from `origin` along `direction`
+    /// within `maxDepth`, by rendering the objects' geometry on the GPU.
+    #[napi]
+    pub fn ray_intersect(
+        &self,
+        origin: Vec<f64>,
+        direction: Vec<f64>,
+        max_depth: f64,
+        objects: Vec<&Gm>,
+    ) -> Result<Option<IntersectionResult>> {
+        let frame = self.frame()?;
+        let [ox, oy, oz] = components("Origin", &origin)?;
+        let [dx, dy, dz] = components("Direction", &direction)?;
+        let direction = three_d::vec3(dx as f32, dy as f32, dz as f32);
+        if !(direction.magnitude2() > 0.0 && direction.magnitude2().is_finite()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Ray direction must be a non-zero vector".to_string(),
+            ));
+        }
+        if !(max_depth > 0.0 && max_depth.is_finite()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Maximum depth must be positive, got {max_depth}"),
+            ));
+        }
+        let geometries: Vec<_> = objects.iter().map(|o| o.geometry.borrow()).collect();
+        let result = three_d::ray_intersect(
+            &frame.context.inner,
+            three_d::vec3(ox as f32, oy as f32, oz as f32),
+            direction.normalize(),
+            max_depth as f32,
+            geometries.iter().map(|g| &**g),
+        );
+        Ok(result.map(IntersectionResult::from))
+    }
+
+    /// Writes the last frame to `path` as an RGBA PNG.
+    #[napi]
+    pub fn save_screenshot(&mut self, path: String) -> Result<()> {
+        let pixels = self.read_pixels(None)?;
+        let png = crate::io::png::encode(self.width, self.height, 4, &pixels)?;
+        std::fs::write(&path, png).map_err(|e| {
+            Error::new(
+                Status::GenericFailure,
+                format!("Failed to write screenshot to {path}: {e}"),
+            )
+        })
     }
 }
 
-impl Default for Camera {
-    fn default() -> Self {
-        Self::new()
+impl Renderer {
+    /// Draws a frame for `render` and `renderScene`.
+    fn draw(
+        &mut self,
+        objects: &[Gm],
+        camera: &mut Camera,
+        lights: &[LightHandle],
+        options: Option<ViewportOptions>,
+    ) -> Result<FrameStats> {
+        let (width, height) = (self.width, self.height);
+        let frustum_culling = self.frustum_culling;
+        let order_independent = self.order_independent_transparency;
+        let gpu_timing = self.gpu_timing;
+        let samples = self.samples;
+        let options = options.unwrap_or_default();
+        let viewport = match &options.viewport {
+            Some(viewport) => frame_viewport(viewport, width, height)?,
+            None => three_d::Viewport::new_at_origo(width, height),
+        };
+        let [red, green, blue, alpha] = options
+            .background
+            .as_ref()
+            .map_or(self.clear_color, |c| [c.r, c.g, c.b, c.a].map(|c| c as f32));
+        let layers = options.layers.unwrap_or(u32::MAX);
+        let frame = self.frame_mut()?;
+        let output = camera.output();
+        let native = output.native();
+        // The caller's camera keeps the viewport, so picking and projecting
+        // with it match the frame.
+        camera.inner.set_viewport(viewport);
+        let mut camera = camera.inner.clone();
+        if native.is_none() {
+            camera.tone_mapping = three_d::ToneMapping::None;
+            camera.color_mapping = three_d::ColorMapping::None;
+        }
+
+        let lights: Vec<_> = lights.iter().map(|light| light.borrow()).collect();
+        let lights: Vec<&dyn three_d::Light> = lights.iter().map(|light| &**light).collect();
+
+        let objects: Vec<_> = objects
+            .iter()
+            .filter(|object| object.layers.get() & layers != 0)
+            .collect();
+        for object in &objects {
+            object
+                .material
+                .check(&frame.context.inner, &*object.geometry.borrow())?;
+        }
+        let costs: Vec<_> = objects
+            .iter()
+            .map(|o| DrawCost::new(&mut o.geometry.borrow_mut(), o.material.texture_count()))
+            .collect();
+        let geometries: Vec<_> = objects.iter().map(|o| o.geometry.borrow()).collect();
+        let materials: Vec<_> = objects.iter().map(|o| o.material.borrow()).collect();
+        let mut gms: Vec<_> = geometries
+            .iter()
+            .zip(&materials)
+            .zip(costs)
+            .map(|((geometry, material), cost)| (three_d::Gm::new(&**geometry, &**material), cost))
+            .collect();
+        let mut recorder = FrameRecorder::new(&frame.context, gpu_timing);
+        if frustum_culling {
+            let frustum = three_d::Frustum::new(camera.projection() * camera.view());
+            let count = gms.len();
+            gms.retain(|(gm, _)| frustum.contains(gm.aabb()));
+            recorder.culled((count - gms.len()) as u32);
+        }
+        gms.sort_by(|(a, _), (b, _)| three_d::cmp_render_order(&camera, a, b));
+        let (deferred, gms): (Vec<_>, Vec<_>) = gms
+            .into_iter()
+            .partition(|(gm, _)| gm.material.material_type() == three_d::MaterialType::Deferred);
+        let (oit, gms): (Vec<_>, Vec<_>) = gms.into_iter().partition(|(gm, _)| {
+            order_independent && gm.material.render_states().blend == three_d::Blend::TRANSPARENCY
+        });
+
+        let Frame {
+            context,
+            color,
+            depth,
+            hdr,
+            oit: oit_buffers,
+            gbuffer,
+            multisample,
+        } = frame;
+        let (scene, [red, green, blue]) = match native {
+            Some(_) => (&mut *color, [red, green, blue]),
+            None => (&mut *hdr, [red, green, blue].map(srgb_to_linear)),
+        };
+        if samples == 1 {
+            *multisample = None;
+        } else if multisample.as_ref().map(|(samples, _)| *samples) != Some(samples) {
+            *multisample = Some((
+                samples,
+                multisample_target(context, width, height, samples)?,
+            ));
+        }
+        let clear = options.clear.unwrap_or(true);
+        let skybox = options.skybox.as_ref().map(|skybox| &skybox.inner);
+        // Draws the deferred and forward passes into `target`, starting from
+        // the cleared viewport or from a copy of the frame.
+        let mut draw_opaque = |target: &three_d::RenderTarget,
+                               frame: Option<(&three_d::Texture2D, &three_d::DepthTexture2D)>|
+         -> Result<()> {
+            if clear {
+                target.clear_partially(
+                    viewport.into(),
+                    three_d::ClearState::color_and_depth(red, green, blue, alpha, 1.0),
+                );
+            } else if let Some((color, depth)) = frame {
+                let mut frame_camera = camera.clone();
+                frame_camera.set_viewport(three_d::Viewport::new_at_origo(width, height));
+                recorder.pass("multisample copy", |counter| {
+                    counter.screen(2);
+                    target.apply_screen_effect_partially(
+                        viewport.into(),
+                        &three_d::CopyEffect::default(),
+                        &frame_camera,
+                        &[],
+                        Some(ColorTexture::Single(color)),
+                        Some(DepthTexture::Single(depth)),
+                    );
+                });
+            }
+            if deferred.is_empty() {
+                *gbuffer = None;
+            } else {
+                // The lighting pass samples the G-buffer across the viewport, so
+                // the G-buffer has the size of the viewport.
+                let objects: Vec<&dyn three_d::Object> =
+                    deferred.iter().map(|(gm, _)| gm as _).collect();
+                let size = (viewport.width, viewport.height);
+                if gbuffer.as_ref().map(GBuffer::size) != Some(size) {
+                    *gbuffer = None;
+                }
+                let gbuffer =
+                    gbuffer.get_or_insert_with(|| GBuffer::new(&context.inner, size.0, size.1));
+                let mut gbuffer_camera = camera.clone();
+                gbuffer_camera.set_viewport(three_d::Viewport::new_at_origo(size.0, size.1));
+                recorder.pass("deferred geometry", |counter| {
+                    for (gm, cost) in &deferred {
+                        counter.draw(*cost, gm.material);
+                    }
+                    gbuffer.geometry_pass(&gbuffer_camera, &objects)
+                })?;
+                recorder.pass("deferred lighting", |counter| {
+                    // The layers and the depth of the G-buffer.
+                    counter.screen(2);
+                    gbuffer.lighting_pass(&context.inner, target, &camera, &lights)
+                })?;
+            }
+            recorder.pass("forward", |counter| {
+                target
+                    .write::<three_d::RendererError>(|| {
+                        if let Some(skybox) = skybox {
+                            counter.skybox();
+                            skybox.render(&camera, &[]);
+                        }
+                        for (gm, cost) in &gms {
+                            counter.draw(*cost, gm.material);
+                            gm.render(&camera, &lights);
+                        }
+                        Ok(())
+                    })
+                    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))
+            })?;
+            Ok(())
+        };
+        match multisample {
+            Some((_, multisample)) => {
+                let mut result = Ok(());
+                multisample.write(|target| result = draw_opaque(target, Some((scene, depth))));
+                result?;
+                let target = three_d::RenderTarget::new(
+                    scene.as_color_target(None),
+                    depth.as_depth_target(),
+                );
+                recorder.pass("resolve", |_| {
+                    multisample.resolve_partially(&target, viewport.into())
+                })?;
+            }
+            None => draw_opaque(
+                &three_d::RenderTarget::new(scene.as_color_target(None), depth.as_depth_target()),
+                None,
+            )?,
+        }
+        if !oit.is_empty() {
+            let objects: Vec<(&dyn three_d::Geometry, _)> = oit
+                .iter()
+                .map(|(gm, _)| (gm.geometry as _, gm.material))
+                .collect();
+            recorder.pass("transparency", |counter| {
+                // Once into each buffer, then composited with both.
+                for _ in 0..2 {
+                    for (gm, cost) in &oit {
+                        counter.draw(*cost, gm.material);
+                    }
+                }
+                counter.screen(2);
+                oit_buffers
+                    .get_or_insert_with(|| OitBuffers::new(&context.inner, width, height))
+                    .render(scene, depth, &camera, &objects, &lights)
+            })?;
+        }
+        if native.is_none() {
+            let effect = ToneMapEffect::new(hdr, output)?;
+            recorder.pass("tone mapping", |counter| {
+                counter.screen(1);
+                three_d::RenderTarget::new(color.as_color_target(None), depth.as_depth_target())
+                    .apply_screen_effect(&effect, &camera, &[], None, None);
+            });
+        }
+        Ok(recorder.finish())
+    }
+
+    /// Keeps the statistics of a frame and passes them to the callback.
+    fn report(&mut self, env: &Env, stats: FrameStats) -> Result<()> {
+        self.last_frame_stats = Some(stats.clone());
+        match &self.stats_callback {
+            Some(callback) => callback.borrow_back(env)?.call(stats),
+            None => Ok(()),
+        }
+    }
+
+    fn frame(&self) -> Result<&Frame> {
+        self.frame.as_ref().ok_or_else(not_initialized)
+    }
+
+    fn frame_mut(&mut self) -> Result<&mut Frame> {
+        self.frame.as_mut().ok_or_else(not_initialized)
     }
 }
 
-// Wrapper for Renderer
-#[napi]
-pub struct Renderer {
-    // impl details
+/// Where and how `Renderer.render` and `Renderer.renderScene` draw into the
+/// frame.
+#[napi(object)]
+#[derive(Default)]
+pub struct ViewportOptions<'a> {
+    /// Region of the frame to draw into, replacing the camera's viewport.
+    /// Defaults to the whole frame.
+    pub viewport: Option<ClassInstance<'a, Viewport>>,
+    /// Whether to clear the color and depth of the region first. Defaults to
+    /// true.
+    pub clear: Option<bool>,
+    /// Color the region is cleared to. Defaults to the renderer's clear
+    /// color.
+    pub background: Option<ClassInstance<'a, NSrgba>>,
+    /// Only objects with one of these layers set are drawn. Defaults to all
+    /// layers.
+    pub layers: Option<u32>,
+    /// Drawn behind the objects, wherever nothing else is.
+    pub skybox: Option<ClassInstance<'a, Skybox>>,
 }
 
-#[napi]
-impl Renderer {
-    #[napi(constructor)]
-    pub fn new() -> Self {
-        Renderer {}
+/// The closest hit of `Renderer.pick` or `Renderer.rayIntersect`.
+/// `objectIndex` is the index into the objects passed in and `instanceId`
+/// the instance hit, 0 for objects that are not instanced.
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct IntersectionResult {
+    pub position: Vec<f64>,
+    pub object_index: u32,
+    pub instance_id: u32,
+}
+
+impl From<three_d::IntersectionResult> for IntersectionResult {
+    fn from(result: three_d::IntersectionResult) -> Self {
+        IntersectionResult {
+            position: to_array(result.position),
+            object_index: result.geometry_id,
+            instance_id: result.instance_id,
+        }
     }
 }
 
-impl Default for Renderer {
-    fn default() -> Self {
-        Self::new()
+/// Decodes an sRGB channel in `[0, 1]` to linear.
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
     }
 }
 
+/// Checks that `viewport` lies inside a frame of `width` by `height`.
+fn frame_viewport(viewport: &Viewport, width: u32, height: u32) -> Result<three_d::Viewport> {
+    let fits = viewport.x >= 0
+        && viewport.y >= 0
+        && viewport.width > 0
+        && viewport.height > 0
+        && viewport.x as u64 + viewport.width as u64 <= width as u64
+        && viewport.y as u64 + viewport.height as u64 <= height as u64;
+    if !fits {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!(
+                "Viewport ({}, {}, {}, {}) must lie inside the {width}x{height} frame",
+                viewport.x, viewport.y, viewport.width, viewport.height
+            ),
+        ));
+    }
+    Ok(viewport.into())
+}
+
+/// A multisampled render target with the formats of the frame's color and
+/// depth textures, so it can be resolved into them.
+fn multisample_target(
+    context: &Context,
+    width: u32,
+    height: u32,
+    samples: u32,
+) -> Result<RenderTargetMultisample> {
+    let color = ColorTargetMultisample::new(
+        context,
+        width,
+        height,
+        samples,
+        Some(TextureFormat::R16G16B16A16F),
+    )?;
+    let depth = DepthTargetMultisample::new(
+        context,
+        width,
+        height,
+        samples,
+        Some(TextureFormat::Depth32F),
+    )?;
+    RenderTargetMultisample::new(context, Some(&color), Some(&depth))
+}
+
+fn not_initialized() -> Error {
+    Error::new(
+        Status::GenericFailure,
+        "Renderer is not initialized, call init() first",
+    )
+}
+
+/// Draws `geometries` with `material` into `target`, without clearing it.
+/// Unlike `Renderer.render` the geometries are drawn in the given order and
+/// not culled. The camera's viewport is replaced by the size of the target.
+/// Drawing into a `RenderTargetMultisample` and resolving it antialiases the
+/// edges.
 #[napi]
-pub fn render_with_material() {}
+pub fn render_with_material(
+    target: AnyRenderTarget,
+    camera: &Camera,
+    geometries: Vec<&Mesh>,
+    material: MaterialInput,
+    lights: Option<Vec<LightInput>>,
+) -> Result<()> {
+    let material = MaterialHandle::from(material);
+    if let MaterialHandle::Deferred(_) = material {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "DeferredPhysicalMaterial can only be drawn by Renderer.render",
+        ));
+    }
+    let (context, width, height) = target_size(&target);
+    let geometries: Vec<_> = geometries.iter().map(|g| g.inner.borrow()).collect();
+    for geometry in &geometries {
+        material.check(&context, &**geometry)?;
+    }
+    let lights = light_handles(lights);
+    let lights: Vec<_> = lights.iter().map(|light| light.borrow()).collect();
+    let lights: Vec<&dyn three_d::Light> = lights.iter().map(|light| &**light).collect();
+    let camera = target_camera(width, height, camera);
+    let material = material.borrow();
+    write_target(&target, |target| {
+        target.render_with_material(
+            &*material,
+            &camera,
+            geometries.iter().map(|g| &**g),
+            &lights,
+        );
+    });
+    Ok(())
+}
 
+/// Draws `geometries` into `target` with `effect`, which can sample
+/// `colorTexture` and `depthTexture`, for example a previous pass of the same
+/// scene. The camera's viewport is replaced by the size of the target, which
+/// can be multisampled like for `renderWithMaterial`.
+#[allow(clippy::too_many_arguments)]
 #[napi]
-pub fn render_with_effect() {}
+pub fn render_with_effect(
+    target: AnyRenderTarget,
+    camera: &Camera,
+    geometries: Vec<&Mesh>,
+    effect: &ScreenEffect,
+    color_texture: Option<&Texture2D>,
+    depth_texture: Option<&DepthTexture2D>,
+    lights: Option<Vec<LightInput>>,
+) -> Result<()> {
+    if let Either::A(target) = &target {
+        target.check_not_attached(color_texture, depth_texture)?;
+    }
+    let color_texture = color_texture.map(|t| ColorTexture::Single(&t.inner));
+    let depth_texture = depth_texture.map(|t| DepthTexture::Single(&t.inner));
+    let (context, width, height) = target_size(&target);
+    let geometries: Vec<_> = geometries.iter().map(|g| g.inner.borrow()).collect();
+    let vertex_sources: Vec<_> = geometries
+        .iter()
+        .map(|g| g.vertex_shader_source())
+        .collect();
+    effect
+        .inner
+        .borrow_mut()
+        .check(&context, &vertex_sources, color_texture, depth_texture)?;
+    let lights = light_handles(lights);
+    let lights: Vec<_> = lights.iter().map(|light| light.borrow()).collect();
+    let lights: Vec<&dyn three_d::Light> = lights.iter().map(|light| &**light).collect();
+    let camera = target_camera(width, height, camera);
+    let state = effect.inner.borrow();
+    let effect = state.prepare(color_texture, depth_texture, false)?;
+    write_target(&target, |target| {
+        target.render_with_effect(
+            &effect,
+            &camera,
+            geometries.iter().map(|g| &**g),
+            &lights,
+            color_texture,
+            depth_texture,
+        );
+    });
+    Ok(())
+}
+
+fn light_handles(lights: Option<Vec<LightInput>>) -> Vec<LightHandle> {
+    lights
+        .unwrap_or_default()
+        .into_iter()
+        .map(LightHandle::from)
+        .collect()
+}
+
+/// A copy of `camera` with its viewport covering a `width` x `height` target.
+fn target_camera(width: u32, height: u32, camera: &Camera) -> three_d::Camera {
+    let mut camera = camera.inner.clone();
+    camera.set_viewport(three_d::Viewport::new_at_origo(width, height));
+    camera
+}
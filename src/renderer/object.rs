@@ -1,13 +1,92 @@
+use napi::{Error, Result, Status};
 use napi_derive::napi;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use three_d::Geometry;
+
+use super::geometry::Mesh;
+use super::material::{MaterialHandle, MaterialInput};
+use crate::context::Context;
+use crate::core::texture::TextureCubeMap;
+use crate::prelude::{AxisAlignedBoundingBox, Matrix4};
+
+/// A model uploaded to the GPU: one physically based `Gm` per triangle
+/// primitive, placed by the primitive's transformation. Primitives sharing a
+/// material share the uploaded material.
+#[napi]
+pub struct Model {
+    objects: Vec<Gm>,
+}
 
 #[napi]
-pub struct Model {}
+impl Model {
+    /// Uploads a model loaded with `loadModel` or `loadModelAsync`. Point
+    /// primitives are skipped.
+    #[napi(constructor)]
+    pub fn new(context: &Context, cpu_model: &CpuModel) -> Result<Self> {
+        let materials = cpu_model
+            .inner
+            .materials
+            .iter()
+            .map(|m| {
+                Rc::new(RefCell::new(three_d::PhysicalMaterial::new(
+                    &context.inner,
+                    m,
+                )))
+            })
+            .collect::<Vec<_>>();
+        let mut objects = Vec::new();
+        for primitive in &cpu_model.inner.geometries {
+            let three_d::CpuGeometry::Triangles(mesh) = &primitive.geometry else {
+                continue;
+            };
+            let material = match primitive.material_index {
+                Some(index) => materials.get(index).cloned().ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!("Primitive {} uses missing material {index}", primitive.name),
+                    )
+                })?,
+                None => Rc::new(RefCell::new(three_d::PhysicalMaterial::default())),
+            };
+            let mut geometry = three_d::Mesh::new(&context.inner, mesh);
+            geometry.set_transformation(primitive.transformation);
+            objects.push(Gm {
+                geometry: Rc::new(RefCell::new(geometry)),
+                material: MaterialHandle::Physical(material),
+                layers: Rc::new(Cell::new(1)),
+            });
+        }
+        Ok(Model { objects })
+    }
+
+    /// The objects of the model, sharing its meshes and materials.
+    #[napi]
+    pub fn objects(&self) -> Vec<Gm> {
+        self.objects.clone()
+    }
+}
 
 #[napi]
 pub struct InstancedModel {}
 
+/// A cube map drawn behind everything else, passed to `Renderer.render` or
+/// `Renderer.renderScene` in `ViewportOptions.skybox`.
 #[napi]
-pub struct Skybox {}
+pub struct Skybox {
+    pub(crate) inner: three_d::Skybox,
+}
+
+#[napi]
+impl Skybox {
+    /// A skybox showing `texture`, which stays shared with the cube map.
+    #[napi(constructor)]
+    pub fn new(context: &Context, texture: &TextureCubeMap) -> Self {
+        Skybox {
+            inner: three_d::Skybox::new_with_texture(&context.inner, texture.inner.clone()),
+        }
+    }
+}
 
 #[napi]
 pub struct Axes {}
@@ -18,11 +97,86 @@ pub struct Terrain {}
 #[napi]
 pub struct Water {}
 
+/// A mesh drawn with a material. The mesh and material are shared, so
+/// changes to either show up in every `Gm` using them.
+#[napi]
+#[derive(Clone)]
+pub struct Gm {
+    pub(crate) geometry: Rc<RefCell<three_d::Mesh>>,
+    pub(crate) material: MaterialHandle,
+    /// Shared with the copies held by scene nodes.
+    pub(crate) layers: Rc<Cell<u32>>,
+}
+
 #[napi]
-pub struct Gm {}
+impl Gm {
+    #[napi(constructor)]
+    pub fn new(geometry: &Mesh, material: MaterialInput) -> Self {
+        Gm {
+            geometry: geometry.inner.clone(),
+            material: material.into(),
+            layers: Rc::new(Cell::new(1)),
+        }
+    }
 
+    /// Bit mask of the layers this object is in, matched against the
+    /// `layers` of `ViewportOptions`. Defaults to 1.
+    #[napi(getter)]
+    pub fn layers(&self) -> u32 {
+        self.layers.get()
+    }
+
+    #[napi(setter)]
+    pub fn set_layers(&self, layers: u32) {
+        self.layers.set(layers);
+    }
+
+    /// Sets the transformation of the shared mesh.
+    #[napi]
+    pub fn set_transformation(&self, transformation: &Matrix4) {
+        self.geometry
+            .borrow_mut()
+            .set_transformation(transformation.to_matrix4());
+    }
+
+    /// Bounding box in world space.
+    #[napi]
+    pub fn aabb(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox {
+            inner: self.geometry.borrow().aabb(),
+        }
+    }
+}
+
+/// A model loaded on the CPU: triangle and point primitives with their
+/// physically based materials.
 #[napi]
-pub struct CpuModel {}
+pub struct CpuModel {
+    pub(crate) inner: three_d::CpuModel,
+}
+
+#[napi]
+impl CpuModel {
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    /// Number of triangle primitives, each uploaded as one object.
+    #[napi(getter)]
+    pub fn mesh_count(&self) -> u32 {
+        self.inner
+            .geometries
+            .iter()
+            .filter(|p| matches!(p.geometry, three_d::CpuGeometry::Triangles(_)))
+            .count() as u32
+    }
+
+    #[napi(getter)]
+    pub fn material_count(&self) -> u32 {
+        self.inner.materials.len() as u32
+    }
+}
 
 #[napi]
 pub struct VoxelGrid {}
@@ -0,0 +1,236 @@
+//! Weighted blended order-independent transparency, after McGuire and
+//! Bavoil, "Weighted Blended Order-Independent Transparency", JCGT 2013.
+//!
+//! Alpha blended objects are drawn twice over the depth of the opaque
+//! objects, with their material's shader wrapped to write weighted colors
+//! into an accumulation buffer and the product of `1 - alpha` into a
+//! revealage buffer. A composite pass then blends the weighted average over
+//! the frame.
+
+use napi::Result;
+use three_d::{
+    f16, Blend, BlendEquationType, BlendMultiplierType, ColorTexture, DepthTexture, Effect,
+    EffectMaterialId, Interpolation, Light, Material, MaterialType, Program, RenderStates, Viewer,
+    Wrapping, WriteMask,
+};
+
+use super::material::material_id;
+
+const ACCUMULATION_SHADER: &str = "
+void main()
+{
+    oit_material_main();
+    float a = outColor.a;
+    float w = clamp(pow(min(1.0, a * 10.0) + 0.01, 3.0) * 1e8 * pow(1.0 - gl_FragCoord.z * 0.9, 3.0), 1e-2, 3e3);
+    outColor = vec4(outColor.rgb * a, a) * w;
+}
+";
+
+const REVEALAGE_SHADER: &str = "
+void main()
+{
+    oit_material_main();
+    // Only red is read. Keeping the color in the other channels keeps the
+    // material's uniforms in use, as three-d fails on unused uniforms.
+    outColor = vec4(outColor.a, outColor.rgb);
+}
+";
+
+const COMPOSITE_SHADER: &str = "
+uniform sampler2D accumulationTexture;
+uniform sampler2D revealageTexture;
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // Fetched by pixel, as the pass may cover only a viewport of the frame.
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    float revealage = texelFetch(revealageTexture, pixel, 0).r;
+    if (revealage >= 1.0) {
+        discard;
+    }
+    vec4 accumulation = texelFetch(accumulationTexture, pixel, 0);
+    outColor = vec4(accumulation.rgb / max(accumulation.a, 1e-5), 1.0 - revealage);
+}
+";
+
+/// The accumulation and revealage buffers of a frame.
+pub(crate) struct OitBuffers {
+    accumulation: three_d::Texture2D,
+    revealage: three_d::Texture2D,
+}
+
+impl OitBuffers {
+    pub(crate) fn new(context: &three_d::Context, width: u32, height: u32) -> Self {
+        let texture = || {
+            three_d::Texture2D::new_empty::<[f16; 4]>(
+                context,
+                width,
+                height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        OitBuffers {
+            accumulation: texture(),
+            revealage: texture(),
+        }
+    }
+
+    /// Draws `objects` into the buffers, depth tested against `depth`, and
+    /// blends the result over `target`.
+    pub(crate) fn render(
+        &mut self,
+        target: &mut three_d::Texture2D,
+        depth: &mut three_d::DepthTexture2D,
+        camera: &three_d::Camera,
+        objects: &[(&dyn three_d::Geometry, &dyn Material)],
+        lights: &[&dyn Light],
+    ) -> Result<()> {
+        for (pass, buffer, clear) in [
+            (OitPass::Accumulation, &mut self.accumulation, 0.0),
+            (OitPass::Revealage, &mut self.revealage, 1.0),
+        ] {
+            let materials = objects
+                .iter()
+                .map(|(_, material)| OitMaterial::new(*material, pass))
+                .collect::<Result<Vec<_>>>()?;
+            three_d::RenderTarget::new(buffer.as_color_target(None), depth.as_depth_target())
+                .clear(three_d::ClearState::color(clear, clear, clear, clear))
+                .write::<three_d::RendererError>(|| {
+                    for ((geometry, _), material) in objects.iter().zip(&materials) {
+                        geometry.render_with_material(material, camera, lights);
+                    }
+                    Ok(())
+                })
+                .map_err(|e| napi::Error::new(napi::Status::GenericFailure, e.to_string()))?;
+        }
+        let composite = CompositeEffect {
+            id: material_id(COMPOSITE_SHADER)?,
+            buffers: self,
+        };
+        three_d::RenderTarget::new(target.as_color_target(None), depth.as_depth_target())
+            .apply_screen_effect(&composite, camera, &[], None, None);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum OitPass {
+    Accumulation,
+    Revealage,
+}
+
+/// A material whose output is turned into the values of an OIT pass.
+struct OitMaterial<'a> {
+    material: &'a dyn Material,
+    pass: OitPass,
+    id: u16,
+}
+
+impl<'a> OitMaterial<'a> {
+    fn new(material: &'a dyn Material, pass: OitPass) -> Result<Self> {
+        // The wrapped shader differs for every material id and pass, so each
+        // combination needs an id of its own.
+        let id = material_id(&format!("oit {pass:?} {}", material.id().0))?;
+        Ok(OitMaterial { material, pass, id })
+    }
+}
+
+impl Material for OitMaterial<'_> {
+    fn fragment_shader_source(&self, lights: &[&dyn Light]) -> String {
+        let source = self.material.fragment_shader_source(lights).replacen(
+            "void main()",
+            "void oit_material_main()",
+            1,
+        );
+        let main = match self.pass {
+            OitPass::Accumulation => ACCUMULATION_SHADER,
+            OitPass::Revealage => REVEALAGE_SHADER,
+        };
+        format!("{source}\n{main}")
+    }
+
+    fn id(&self) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(&self, program: &Program, viewer: &dyn Viewer, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, viewer, lights);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        let (source, destination) = match self.pass {
+            OitPass::Accumulation => (BlendMultiplierType::One, BlendMultiplierType::One),
+            OitPass::Revealage => (
+                BlendMultiplierType::Zero,
+                BlendMultiplierType::OneMinusSrcColor,
+            ),
+        };
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            blend: Blend::Enabled {
+                source_rgb_multiplier: source,
+                source_alpha_multiplier: source,
+                destination_rgb_multiplier: destination,
+                destination_alpha_multiplier: destination,
+                rgb_equation: BlendEquationType::Add,
+                alpha_equation: BlendEquationType::Add,
+            },
+            ..self.material.render_states()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+/// Blends the weighted average color of the OIT buffers over the frame.
+struct CompositeEffect<'a> {
+    id: u16,
+    buffers: &'a OitBuffers,
+}
+
+impl Effect for CompositeEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        COMPOSITE_SHADER.to_string()
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _viewer: &dyn Viewer,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        program.use_texture("accumulationTexture", &self.buffers.accumulation);
+        program.use_texture("revealageTexture", &self.buffers.revealage);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: three_d::DepthTest::Always,
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,483 @@
+//! A hierarchy of nodes with local transforms. Every change to a node
+//! updates the world transform of its subtree and moves the objects and
+//! lights attached to it.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use three_d::{InnerSpace, Mat3, Mat4, Quat, SquareMatrix, Transform, Vec3};
+
+use super::light::{LightHandle, LightInput};
+use super::object::Gm;
+use crate::prelude::{Matrix4, NQuaternion, Vector3};
+
+type NodeRef = Rc<RefCell<NodeState>>;
+
+struct NodeState {
+    name: String,
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    /// Kept up to date by `update`, so children can build on it.
+    world: Mat4,
+    parent: Weak<RefCell<NodeState>>,
+    children: Vec<NodeRef>,
+    /// Objects with their transformation relative to the node.
+    objects: Vec<(Gm, Mat4)>,
+    lights: Vec<AttachedLight>,
+}
+
+/// A light with its position and direction relative to the node.
+struct AttachedLight {
+    light: LightHandle,
+    position: Vec3,
+    direction: Vec3,
+}
+
+impl NodeState {
+    fn local(&self) -> Mat4 {
+        Mat4::from_translation(self.translation)
+            * Mat4::from(self.rotation)
+            * Mat4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+/// A node of a scene graph. Its translation, rotation and scale are relative
+/// to its parent, and its world transform is applied to the attached `Gm`
+/// objects and lights whenever it changes.
+///
+/// The transformation of an object and the position and direction of a light
+/// at the time they are attached are kept as relative to the node. Setting
+/// them directly afterwards is overwritten by the next change to the node.
+#[napi]
+pub struct Node {
+    inner: NodeRef,
+}
+
+#[napi]
+impl Node {
+    #[napi(constructor)]
+    pub fn new(name: Option<String>) -> Self {
+        Node {
+            inner: Rc::new(RefCell::new(NodeState {
+                name: name.unwrap_or_default(),
+                translation: Vec3::new(0.0, 0.0, 0.0),
+                rotation: Quat::new(1.0, 0.0, 0.0, 0.0),
+                scale: Vec3::new(1.0, 1.0, 1.0),
+                world: Mat4::identity(),
+                parent: Weak::new(),
+                children: Vec::new(),
+                objects: Vec::new(),
+                lights: Vec::new(),
+            })),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.inner.borrow().name.clone()
+    }
+
+    #[napi(setter)]
+    pub fn set_name(&self, name: String) {
+        self.inner.borrow_mut().name = name;
+    }
+
+    #[napi]
+    pub fn get_translation(&self) -> Vector3 {
+        Vector3::from(&self.inner.borrow().translation)
+    }
+
+    #[napi]
+    pub fn set_translation(&self, translation: &Vector3) -> Result<()> {
+        let translation = finite("Translation", translation)?;
+        self.inner.borrow_mut().translation = translation;
+        update(&self.inner);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn get_rotation(&self) -> NQuaternion {
+        let q = self.inner.borrow().rotation;
+        NQuaternion::new(q.v.x as f64, q.v.y as f64, q.v.z as f64, q.s as f64)
+    }
+
+    /// Sets the rotation, normalizing the quaternion.
+    #[napi]
+    pub fn set_rotation(&self, rotation: &NQuaternion) -> Result<()> {
+        let rotation = Quat::from(rotation);
+        if !(rotation.magnitude2() > 0.0 && rotation.magnitude2().is_finite()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Rotation must be a non-zero quaternion".to_string(),
+            ));
+        }
+        self.inner.borrow_mut().rotation = rotation.normalize();
+        update(&self.inner);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn get_scale(&self) -> Vector3 {
+        Vector3::from(&self.inner.borrow().scale)
+    }
+
+    #[napi]
+    pub fn set_scale(&self, scale: &Vector3) -> Result<()> {
+        let scale = finite("Scale", scale)?;
+        self.inner.borrow_mut().scale = scale;
+        update(&self.inner);
+        Ok(())
+    }
+
+    /// Translation, rotation and scale combined, relative to the parent.
+    #[napi]
+    pub fn local_matrix(&self) -> Matrix4 {
+        Matrix4::from_matrix4(&self.inner.borrow().local())
+    }
+
+    /// The local matrix of this node and all its ancestors combined.
+    #[napi]
+    pub fn world_matrix(&self) -> Matrix4 {
+        Matrix4::from_matrix4(&self.inner.borrow().world)
+    }
+
+    #[napi(getter)]
+    pub fn parent(&self) -> Option<Node> {
+        self.inner
+            .borrow()
+            .parent
+            .upgrade()
+            .map(|inner| Node { inner })
+    }
+
+    #[napi]
+    pub fn children(&self) -> Vec<Node> {
+        self.inner
+            .borrow()
+            .children
+            .iter()
+            .map(|child| Node {
+                inner: child.clone(),
+            })
+            .collect()
+    }
+
+    /// Adds `child` as the last child of this node, keeping its local
+    /// transform. The child is removed from its previous parent.
+    #[napi]
+    pub fn add_child(&self, child: &Node) -> Result<()> {
+        attach(&self.inner, &child.inner)?;
+        update(&child.inner);
+        Ok(())
+    }
+
+    /// Moves this node to `parent`, or detaches it with `null`, and changes
+    /// its local transform so its world transform stays the same. A world
+    /// transform with shear, from a rotated child of a non-uniformly scaled
+    /// node, cannot be kept exactly.
+    #[napi]
+    pub fn set_parent(&self, parent: Option<&Node>) -> Result<()> {
+        let world = self.inner.borrow().world;
+        let parent_world = parent.map_or(Mat4::identity(), |p| p.inner.borrow().world);
+        let local = parent_world.invert().ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                "The world matrix of the new parent is not invertible".to_string(),
+            )
+        })? * world;
+        let (translation, rotation, scale) = decompose(local).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                "Cannot keep the world transform of a node with zero scale".to_string(),
+            )
+        })?;
+        match parent {
+            Some(parent) => attach(&parent.inner, &self.inner)?,
+            None => detach(&self.inner),
+        }
+        {
+            let mut node = self.inner.borrow_mut();
+            node.translation = translation;
+            node.rotation = rotation;
+            node.scale = scale;
+        }
+        update(&self.inner);
+        Ok(())
+    }
+
+    /// Attaches `gm` to this node. Its mesh is shared with every `Gm` made
+    /// from it, so only one node should move it.
+    #[napi]
+    pub fn add_object(&self, gm: &Gm) {
+        let local = gm.geometry.borrow().transformation();
+        self.inner.borrow_mut().objects.push((gm.clone(), local));
+        update(&self.inner);
+    }
+
+    /// Detaches `gm`, leaving its mesh where it is. Returns false if it was
+    /// not attached to this node.
+    #[napi]
+    pub fn remove_object(&self, gm: &Gm) -> bool {
+        let mut node = self.inner.borrow_mut();
+        let count = node.objects.len();
+        node.objects
+            .retain(|(object, _)| !Rc::ptr_eq(&object.geometry, &gm.geometry));
+        node.objects.len() != count
+    }
+
+    /// Objects attached to this node, not including its children.
+    #[napi]
+    pub fn objects(&self) -> Vec<Gm> {
+        self.inner
+            .borrow()
+            .objects
+            .iter()
+            .map(|(gm, _)| gm.clone())
+            .collect()
+    }
+
+    /// Attaches `light` to this node. Ambient lights are not moved.
+    #[napi]
+    pub fn add_light(&self, light: LightInput) {
+        let light = LightHandle::from(light);
+        let zero = Vec3::new(0.0, 0.0, 0.0);
+        let (position, direction) = match &light {
+            LightHandle::Ambient(_) => (zero, zero),
+            LightHandle::Directional(l) => (zero, l.borrow().direction),
+            LightHandle::Point(l) => (l.borrow().position, zero),
+            LightHandle::Spot(l) => {
+                let l = l.borrow();
+                (l.position, l.direction)
+            }
+        };
+        self.inner.borrow_mut().lights.push(AttachedLight {
+            light,
+            position,
+            direction,
+        });
+        update(&self.inner);
+    }
+
+    /// Detaches `light`, leaving it where it is. Returns false if it was not
+    /// attached to this node.
+    #[napi]
+    pub fn remove_light(&self, light: LightInput) -> bool {
+        let light = LightHandle::from(light);
+        let mut node = self.inner.borrow_mut();
+        let count = node.lights.len();
+        node.lights
+            .retain(|attached| !same_light(&attached.light, &light));
+        node.lights.len() != count
+    }
+
+    /// The first node named `name` in this subtree, searched depth first
+    /// starting with this node.
+    #[napi]
+    pub fn find(&self, name: String) -> Option<Node> {
+        let mut found = None;
+        visit(&self.inner, &mut |node| {
+            if found.is_none() && node.borrow().name == name {
+                found = Some(node.clone());
+            }
+        });
+        found.map(|inner| Node { inner })
+    }
+
+    /// This node and all its descendants, depth first with every node before
+    /// its children.
+    #[napi]
+    pub fn traverse(&self) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        visit(&self.inner, &mut |node| {
+            nodes.push(Node {
+                inner: node.clone(),
+            })
+        });
+        nodes
+    }
+}
+
+/// The root of a scene graph, drawn with `Renderer.renderScene`.
+#[napi]
+pub struct Scene {
+    root: Node,
+}
+
+#[napi]
+impl Scene {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        Scene {
+            root: Node::new(Some("root".to_string())),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn root(&self) -> Node {
+        Node {
+            inner: self.root.inner.clone(),
+        }
+    }
+
+    /// Adds `node` to the root, keeping its local transform.
+    #[napi]
+    pub fn add(&self, node: &Node) -> Result<()> {
+        self.root.add_child(node)
+    }
+
+    #[napi]
+    pub fn find(&self, name: String) -> Option<Node> {
+        self.root.find(name)
+    }
+
+    #[napi]
+    pub fn traverse(&self) -> Vec<Node> {
+        self.root.traverse()
+    }
+
+    /// Every object attached to a node of the scene.
+    #[napi]
+    pub fn objects(&self) -> Vec<Gm> {
+        self.gather().0
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene {
+    /// Every object and light attached to a node of the scene.
+    pub(crate) fn gather(&self) -> (Vec<Gm>, Vec<LightHandle>) {
+        let mut objects = Vec::new();
+        let mut lights = Vec::new();
+        visit(&self.root.inner, &mut |node| {
+            let node = node.borrow();
+            objects.extend(node.objects.iter().map(|(gm, _)| gm.clone()));
+            lights.extend(node.lights.iter().map(|attached| attached.light.clone()));
+        });
+        (objects, lights)
+    }
+}
+
+/// Makes `child` the last child of `parent`.
+fn attach(parent: &NodeRef, child: &NodeRef) -> Result<()> {
+    let mut ancestor = Some(parent.clone());
+    while let Some(node) = ancestor {
+        if Rc::ptr_eq(&node, child) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "A node cannot be added to its own subtree".to_string(),
+            ));
+        }
+        ancestor = node.borrow().parent.upgrade();
+    }
+    detach(child);
+    child.borrow_mut().parent = Rc::downgrade(parent);
+    parent.borrow_mut().children.push(child.clone());
+    Ok(())
+}
+
+fn detach(node: &NodeRef) {
+    let parent = node.borrow().parent.upgrade();
+    if let Some(parent) = parent {
+        parent
+            .borrow_mut()
+            .children
+            .retain(|child| !Rc::ptr_eq(child, node));
+    }
+    node.borrow_mut().parent = Weak::new();
+}
+
+/// Calls `f` for `node` and its descendants, depth first.
+fn visit(node: &NodeRef, f: &mut dyn FnMut(&NodeRef)) {
+    f(node);
+    let children = node.borrow().children.clone();
+    for child in &children {
+        visit(child, f);
+    }
+}
+
+/// Recomputes the world transform of `node` and its descendants and moves
+/// their objects and lights.
+fn update(node: &NodeRef) {
+    let parent_world = node
+        .borrow()
+        .parent
+        .upgrade()
+        .map_or(Mat4::identity(), |parent| parent.borrow().world);
+    update_subtree(node, parent_world);
+}
+
+fn update_subtree(node: &NodeRef, parent_world: Mat4) {
+    let world = {
+        let mut state = node.borrow_mut();
+        state.world = parent_world * state.local();
+        state.world
+    };
+    let state = node.borrow();
+    for (gm, local) in &state.objects {
+        gm.geometry.borrow_mut().set_transformation(world * local);
+    }
+    for attached in &state.lights {
+        let position = (world * attached.position.extend(1.0)).truncate();
+        let direction = world.transform_vector(attached.direction);
+        let direction = if direction.magnitude2() > 0.0 {
+            direction.normalize()
+        } else {
+            attached.direction
+        };
+        match &attached.light {
+            LightHandle::Ambient(_) => {}
+            LightHandle::Directional(l) => l.borrow_mut().direction = direction,
+            LightHandle::Point(l) => l.borrow_mut().position = position,
+            LightHandle::Spot(l) => {
+                let mut l = l.borrow_mut();
+                l.position = position;
+                l.direction = direction;
+            }
+        }
+    }
+    for child in &state.children {
+        update_subtree(child, world);
+    }
+}
+
+/// Splits `m` into translation, rotation and scale, or returns `None` if a
+/// scale is zero.
+fn decompose(m: Mat4) -> Option<(Vec3, Quat, Vec3)> {
+    let (x, y, z) = (m.x.truncate(), m.y.truncate(), m.z.truncate());
+    let mut scale = three_d::vec3(x.magnitude(), y.magnitude(), z.magnitude());
+    if !(scale.x > 0.0 && scale.y > 0.0 && scale.z > 0.0) {
+        return None;
+    }
+    if Mat3::from_cols(x, y, z).determinant() < 0.0 {
+        scale.x = -scale.x;
+    }
+    let rotation = Mat3::from_cols(x / scale.x, y / scale.y, z / scale.z);
+    Some((m.w.truncate(), Quat::from(rotation).normalize(), scale))
+}
+
+fn finite(name: &str, v: &Vector3) -> Result<Vec3> {
+    if !(v.x.is_finite() && v.y.is_finite() && v.z.is_finite()) {
+        return Err(Error::new(
+            Status::InvalidArg,
+            format!("{name} must be finite"),
+        ));
+    }
+    Ok(Vec3::from(v))
+}
+
+fn same_light(a: &LightHandle, b: &LightHandle) -> bool {
+    match (a, b) {
+        (LightHandle::Ambient(a), LightHandle::Ambient(b)) => Rc::ptr_eq(a, b),
+        (LightHandle::Directional(a), LightHandle::Directional(b)) => Rc::ptr_eq(a, b),
+        (LightHandle::Point(a), LightHandle::Point(b)) => Rc::ptr_eq(a, b),
+        (LightHandle::Spot(a), LightHandle::Spot(b)) => Rc::ptr_eq(a, b),
+        _ => false,
+    }
+}
@@ -0,0 +1,275 @@
+//! Statistics gathered while `Renderer` draws a frame.
+
+use napi_derive::napi;
+use std::time::Instant;
+use three_d::context::HasContext;
+use three_d::{Blend, Cull, DepthTest, EffectMaterialId, IndexBuffer, WriteMask};
+
+use crate::context::{Context, GetQueryObjectUi64v};
+
+/// Work done by one pass of a frame. Times are in milliseconds. `gpuTime`
+/// is left out unless `Renderer.gpuTiming` is enabled and the context can
+/// time the GPU.
+///
+/// `instances` counts one per draw call, as objects are drawn without
+/// instancing. `textureBinds` is an estimate: the textures each material
+/// declares, counted once per draw call whether or not they are still bound.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct PassStats {
+    pub name: String,
+    pub draw_calls: u32,
+    pub triangles: u32,
+    pub vertices: u32,
+    pub instances: u32,
+    /// Changes of program or render states between draw calls.
+    pub state_changes: u32,
+    /// Estimated from the textures the materials declare.
+    pub texture_binds: u32,
+    pub cpu_time: f64,
+    pub gpu_time: Option<f64>,
+}
+
+/// Work done by the last frame, summed over its passes, with the same
+/// estimates as `PassStats`. Objects outside the camera's frustum are
+/// counted in `culledObjects` and not drawn.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    pub draw_calls: u32,
+    pub triangles: u32,
+    pub vertices: u32,
+    pub instances: u32,
+    pub state_changes: u32,
+    pub culled_objects: u32,
+    pub texture_binds: u32,
+    pub cpu_time: f64,
+    pub gpu_time: Option<f64>,
+    pub passes: Vec<PassStats>,
+}
+
+/// What drawing an object once adds to the statistics.
+#[derive(Clone, Copy)]
+pub(crate) struct DrawCost {
+    vertices: u32,
+    triangles: u32,
+    textures: u32,
+}
+
+impl DrawCost {
+    /// The cost of drawing `mesh` with a material binding `textures`
+    /// textures.
+    pub(crate) fn new(mesh: &mut three_d::Mesh, textures: u32) -> Self {
+        let vertices = mesh.vertex_count();
+        // The index buffer is only reachable mutably.
+        let triangles = match mesh.indices_mut() {
+            IndexBuffer::None => vertices / 3,
+            IndexBuffer::U8(indices) => indices.triangle_count(),
+            IndexBuffer::U16(indices) => indices.triangle_count(),
+            IndexBuffer::U32(indices) => indices.triangle_count(),
+        };
+        DrawCost {
+            vertices,
+            triangles,
+            textures,
+        }
+    }
+}
+
+/// Program and render states of a draw call.
+type State = (EffectMaterialId, WriteMask, DepthTest, Blend, Cull);
+
+/// Counts the draw calls of one pass.
+pub(crate) struct PassCounter {
+    stats: PassStats,
+    state: Option<State>,
+}
+
+impl PassCounter {
+    pub(crate) fn draw(&mut self, cost: DrawCost, material: &dyn three_d::Material) {
+        self.stats.draw_calls += 1;
+        self.stats.vertices += cost.vertices;
+        self.stats.triangles += cost.triangles;
+        self.stats.instances += 1;
+        self.stats.texture_binds += cost.textures;
+        let states = material.render_states();
+        let state = (
+            material.id(),
+            states.write_mask,
+            states.depth_test,
+            states.blend,
+            states.cull,
+        );
+        if self.state.as_ref() != Some(&state) {
+            self.stats.state_changes += 1;
+            self.state = Some(state);
+        }
+    }
+
+    /// Counts a skybox, a cube of 36 vertices sampling its cube map.
+    pub(crate) fn skybox(&mut self) {
+        self.stats.draw_calls += 1;
+        self.stats.vertices += 36;
+        self.stats.triangles += 12;
+        self.stats.instances += 1;
+        self.stats.state_changes += 1;
+        self.stats.texture_binds += 1;
+        self.state = None;
+    }
+
+    /// Counts a full screen effect, one triangle, sampling `textures`
+    /// textures.
+    pub(crate) fn screen(&mut self, textures: u32) {
+        self.stats.draw_calls += 1;
+        self.stats.vertices += 3;
+        self.stats.triangles += 1;
+        self.stats.instances += 1;
+        self.stats.state_changes += 1;
+        self.stats.texture_binds += textures;
+        self.state = None;
+    }
+}
+
+/// Times the passes of a frame on the CPU and, if asked to and timer queries
+/// are supported, on the GPU.
+pub(crate) struct FrameRecorder {
+    context: three_d::Context,
+    /// Set if the passes are timed on the GPU.
+    get_query_object_u64: Option<GetQueryObjectUi64v>,
+    start: Instant,
+    culled_objects: u32,
+    passes: Vec<(PassStats, Option<three_d::context::Query>)>,
+}
+
+impl FrameRecorder {
+    pub(crate) fn new(context: &Context, gpu_timing: bool) -> Self {
+        let version = context.inner.version();
+        let extensions = context.inner.supported_extensions();
+        let timer_queries = gpu_timing
+            && ((!version.is_embedded && (version.major, version.minor) >= (3, 3))
+                || extensions.contains("GL_ARB_timer_query")
+                || extensions.contains("GL_EXT_disjoint_timer_query"));
+        FrameRecorder {
+            context: context.inner.clone(),
+            get_query_object_u64: context.get_query_object_u64.filter(|_| timer_queries),
+            start: Instant::now(),
+            culled_objects: 0,
+            passes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn culled(&mut self, objects: u32) {
+        self.culled_objects += objects;
+    }
+
+    /// Runs `pass`, counting its draw calls with the counter it is given.
+    pub(crate) fn pass<R>(&mut self, name: &str, pass: impl FnOnce(&mut PassCounter) -> R) -> R {
+        let timer = self
+            .get_query_object_u64
+            .and_then(|_| TimerQuery::begin(&self.context));
+        let start = Instant::now();
+        let mut counter = PassCounter {
+            stats: PassStats {
+                name: name.to_string(),
+                ..Default::default()
+            },
+            state: None,
+        };
+        let result = pass(&mut counter);
+        counter.stats.cpu_time = start.elapsed().as_secs_f64() * 1e3;
+        self.passes
+            .push((counter.stats, timer.map(TimerQuery::end)));
+        result
+    }
+
+    /// Waits for the GPU times of the passes and sums them up.
+    pub(crate) fn finish(mut self) -> FrameStats {
+        let mut frame = FrameStats {
+            culled_objects: self.culled_objects,
+            cpu_time: self.start.elapsed().as_secs_f64() * 1e3,
+            ..Default::default()
+        };
+        for (mut pass, query) in std::mem::take(&mut self.passes) {
+            if let (Some(query), Some(get_query_object_u64)) = (query, self.get_query_object_u64) {
+                // The 32-bit result wraps after about 4.3 seconds.
+                let mut nanoseconds = 0u64;
+                // SAFETY: the context is current on this thread for its whole
+                // lifetime, `query` is an ended query of it, and
+                // `get_query_object_u64` is its `glGetQueryObjectui64v`, which
+                // writes one value to the pointer.
+                unsafe {
+                    get_query_object_u64(
+                        query.0.get(),
+                        three_d::context::QUERY_RESULT,
+                        &mut nanoseconds,
+                    );
+                    self.context.delete_query(query);
+                }
+                pass.gpu_time = Some(nanoseconds as f64 / 1e6);
+            }
+            frame.draw_calls += pass.draw_calls;
+            frame.triangles += pass.triangles;
+            frame.vertices += pass.vertices;
+            frame.instances += pass.instances;
+            frame.state_changes += pass.state_changes;
+            frame.texture_binds += pass.texture_binds;
+            frame.passes.push(pass);
+        }
+        frame.gpu_time = frame.passes.iter().map(|pass| pass.gpu_time).sum();
+        frame
+    }
+}
+
+impl Drop for FrameRecorder {
+    /// Deletes the queries of a frame that failed before `finish`.
+    fn drop(&mut self) {
+        for query in self.passes.drain(..).filter_map(|(_, query)| query) {
+            // SAFETY: the context is current on this thread for its whole
+            // lifetime, and the query was created by it.
+            unsafe { self.context.delete_query(query) };
+        }
+    }
+}
+
+/// A `TIME_ELAPSED` query timing a pass. It is ended and deleted on drop,
+/// so a pass that unwinds leaves no query active.
+struct TimerQuery<'a> {
+    context: &'a three_d::Context,
+    query: three_d::context::Query,
+    ended: bool,
+}
+
+impl<'a> TimerQuery<'a> {
+    fn begin(context: &'a three_d::Context) -> Option<Self> {
+        // SAFETY: the context is current on this thread for its whole lifetime.
+        unsafe {
+            let query = context.create_query().ok()?;
+            context.begin_query(three_d::context::TIME_ELAPSED, query);
+            Some(TimerQuery {
+                context,
+                query,
+                ended: false,
+            })
+        }
+    }
+
+    /// Ends the query, returning it to read the result from.
+    fn end(mut self) -> three_d::context::Query {
+        // SAFETY: as in `begin`.
+        unsafe { self.context.end_query(three_d::context::TIME_ELAPSED) };
+        self.ended = true;
+        self.query
+    }
+}
+
+impl Drop for TimerQuery<'_> {
+    fn drop(&mut self) {
+        if !self.ended {
+            // SAFETY: as in `begin`.
+            unsafe {
+                self.context.end_query(three_d::context::TIME_ELAPSED);
+                self.context.delete_query(self.query);
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Stereoscopic eye cameras and compositing of the two eye images.
+
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use three_d::{
+    ColorTexture, DepthTexture, Effect, EffectMaterialId, Light, MetricSpace, Program,
+    RenderStates, Viewer,
+};
+
+use super::camera::Camera;
+use super::material::material_id;
+use crate::core::render_target::RenderTarget;
+use crate::core::texture::Texture2D;
+use crate::core::viewport::Viewport;
+use crate::enums::StereoMode;
+
+const EYE_SHADER: &str = "
+uniform sampler2D eyeTexture;
+in vec2 uvs;
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    outColor = texture(eyeTexture, uvs);
+}
+";
+
+const ANAGLYPH_SHADER: &str = "
+uniform sampler2D leftTexture;
+uniform sampler2D rightTexture;
+in vec2 uvs;
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    vec4 left = texture(leftTexture, uvs);
+    vec4 right = texture(rightTexture, uvs);
+    outColor = vec4(left.r, right.g, right.b, max(left.a, right.a));
+}
+";
+
+/// A pair of eye cameras derived from a center camera. The eyes sit
+/// `ipd / 2` to either side of the center camera and both look at the point
+/// `convergence` units ahead of it (toe-in), so objects at that distance
+/// appear on the screen plane.
+#[napi]
+pub struct StereoCamera {
+    camera: Camera,
+    ipd: f64,
+    convergence: f64,
+}
+
+#[napi]
+impl StereoCamera {
+    /// `ipd` defaults to 0.064, a typical interpupillary distance in meters,
+    /// and `convergence` to the distance from the camera to its target.
+    #[napi(constructor)]
+    pub fn new(camera: &Camera, ipd: Option<f64>, convergence: Option<f64>) -> Result<Self> {
+        let mut stereo = StereoCamera {
+            camera: camera.clone(),
+            ipd: 0.0,
+            convergence: 1.0,
+        };
+        stereo.set_ipd(ipd.unwrap_or(0.064))?;
+        stereo
+            .set_convergence(convergence.unwrap_or_else(|| {
+                camera.inner.position().distance(camera.inner.target()) as f64
+            }))?;
+        Ok(stereo)
+    }
+
+    /// Replaces the center camera, keeping the eye separation and convergence.
+    #[napi]
+    pub fn set_camera(&mut self, camera: &Camera) {
+        self.camera = camera.clone();
+    }
+
+    #[napi(getter)]
+    pub fn ipd(&self) -> f64 {
+        self.ipd
+    }
+
+    #[napi(setter)]
+    pub fn set_ipd(&mut self, ipd: f64) -> Result<()> {
+        if !(ipd >= 0.0 && ipd.is_finite()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Interpupillary distance must not be negative, got {ipd}"),
+            ));
+        }
+        self.ipd = ipd;
+        Ok(())
+    }
+
+    #[napi(getter)]
+    pub fn convergence(&self) -> f64 {
+        self.convergence
+    }
+
+    #[napi(setter)]
+    pub fn set_convergence(&mut self, convergence: f64) -> Result<()> {
+        if !(convergence > 0.0 && convergence.is_finite()) {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Convergence distance must be positive, got {convergence}"),
+            ));
+        }
+        self.convergence = convergence;
+        Ok(())
+    }
+
+    /// Size of each eye image for an output of `width` x `height` pixels.
+    /// Side by side halves the width and top/bottom halves the height, so
+    /// the eye images are not squeezed.
+    #[napi]
+    pub fn eye_viewport(&self, mode: StereoMode, width: u32, height: u32) -> Viewport {
+        match mode {
+            StereoMode::SideBySide => Viewport::at_origin(width / 2, height),
+            StereoMode::TopBottom => Viewport::at_origin(width, height / 2),
+            _ => Viewport::at_origin(width, height),
+        }
+    }
+
+    /// Returns the left eye camera with its viewport set to the eye image
+    /// size. In mono mode both eyes equal the center camera.
+    #[napi]
+    pub fn left_camera(&self, mode: StereoMode, width: u32, height: u32) -> Result<Camera> {
+        self.eye_camera(mode, width, height, -0.5)
+    }
+
+    /// Returns the right eye camera with its viewport set to the eye image size.
+    #[napi]
+    pub fn right_camera(&self, mode: StereoMode, width: u32, height: u32) -> Result<Camera> {
+        self.eye_camera(mode, width, height, 0.5)
+    }
+
+    /// Draws the eye images rendered with `leftCamera` and `rightCamera` into
+    /// `target` in the layout of `mode`: the left eye goes to the left or top
+    /// half. The eye textures must match `eyeViewport` for the target size;
+    /// `Mono` and `Left` only read the left texture and `Right` the right one.
+    #[napi]
+    pub fn composite(
+        &self,
+        target: &RenderTarget,
+        mode: StereoMode,
+        left: &Texture2D,
+        right: &Texture2D,
+    ) -> Result<()> {
+        let (width, height) = (target.width(), target.height());
+        let eye = self.eye_viewport(mode, width, height);
+        for (name, texture) in [("Left", left), ("Right", right)] {
+            if (texture.inner.width(), texture.inner.height()) != (eye.width, eye.height) {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    format!(
+                        "{name} eye texture is {}x{} but {mode:?} output of {width}x{height} needs {}x{}",
+                        texture.inner.width(),
+                        texture.inner.height(),
+                        eye.width,
+                        eye.height
+                    ),
+                ));
+            }
+        }
+        if eye.width == 0 || eye.height == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("A {width}x{height} render target is too small for {mode:?}"),
+            ));
+        }
+        let (left, right) = (&*left.inner, &*right.inner);
+        let eye_effect = |texture| EyeEffect::new(EyeImages::Eye(texture));
+        let draws = match mode {
+            StereoMode::Mono | StereoMode::Left => vec![(eye, eye_effect(left)?)],
+            StereoMode::Right => vec![(eye, eye_effect(right)?)],
+            StereoMode::SideBySide => vec![
+                (eye, eye_effect(left)?),
+                (
+                    Viewport::new(eye.width as i32, 0, eye.width, eye.height),
+                    eye_effect(right)?,
+                ),
+            ],
+            StereoMode::TopBottom => vec![
+                (
+                    Viewport::new(0, (height - eye.height) as i32, eye.width, eye.height),
+                    eye_effect(left)?,
+                ),
+                (eye, eye_effect(right)?),
+            ],
+            StereoMode::Anaglyph => vec![(eye, EyeEffect::new(EyeImages::Anaglyph(left, right))?)],
+        };
+        let mut camera = self.camera.inner.clone();
+        target.write(|target| {
+            for (region, effect) in &draws {
+                let viewport = three_d::Viewport::from(region);
+                camera.set_viewport(viewport);
+                target.apply_screen_effect_partially(
+                    viewport.into(),
+                    effect,
+                    &camera,
+                    &[],
+                    None,
+                    None,
+                );
+            }
+        });
+        Ok(())
+    }
+}
+
+impl StereoCamera {
+    /// The eye at `side` times the interpupillary distance along the right
+    /// direction of the center camera.
+    fn eye_camera(&self, mode: StereoMode, width: u32, height: u32, side: f32) -> Result<Camera> {
+        let viewport = self.eye_viewport(mode, width, height);
+        let mut camera = self.camera.clone();
+        camera.set_viewport(&viewport)?;
+        if mode != StereoMode::Mono {
+            let inner = &camera.inner;
+            let (position, direction) = (inner.position(), inner.view_direction());
+            let offset = inner.right_direction() * (side * self.ipd as f32);
+            let focus = position + direction * self.convergence as f32;
+            let up = inner.up();
+            camera.inner.set_view(position + offset, focus, up);
+        }
+        Ok(camera)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum EyeImages<'a> {
+    Eye(&'a three_d::Texture2D),
+    Anaglyph(&'a three_d::Texture2D, &'a three_d::Texture2D),
+}
+
+/// Draws one eye image, or both as an anaglyph, over the viewport.
+struct EyeEffect<'a> {
+    id: u16,
+    images: EyeImages<'a>,
+}
+
+impl<'a> EyeEffect<'a> {
+    fn new(images: EyeImages<'a>) -> Result<Self> {
+        Ok(EyeEffect {
+            id: material_id(Self::source(images))?,
+            images,
+        })
+    }
+
+    fn source(images: EyeImages) -> &'static str {
+        match images {
+            EyeImages::Eye(_) => EYE_SHADER,
+            EyeImages::Anaglyph(..) => ANAGLYPH_SHADER,
+        }
+    }
+}
+
+impl Effect for EyeEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        Self::source(self.images).to_string()
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _viewer: &dyn Viewer,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        match self.images {
+            EyeImages::Eye(texture) => program.use_texture("eyeTexture", texture),
+            EyeImages::Anaglyph(left, right) => {
+                program.use_texture("leftTexture", left);
+                program.use_texture("rightTexture", right);
+            }
+        }
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: three_d::DepthTest::Always,
+            write_mask: three_d::WriteMask::COLOR,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Tone mapping of a linear HDR frame for the operators three-d lacks.
+
+use napi::Result;
+use three_d::{
+    ColorTexture, DepthTexture, Effect, EffectMaterialId, Light, Program, RenderStates, Viewer,
+};
+
+use super::camera::Output;
+use super::material::material_id;
+use crate::enums::ToneMapping;
+
+const TONE_MAPPING_SHADER: &str = "
+uniform sampler2D hdrTexture;
+uniform int toneMapping;
+uniform float exposure;
+layout (location = 0) out vec4 outColor;
+
+vec3 filmic(vec3 color) {
+    const float A = 0.15;
+    const float B = 0.50;
+    const float C = 0.10;
+    const float D = 0.20;
+    const float E = 0.02;
+    const float F = 0.30;
+    const float W = 11.2;
+    vec4 x = vec4(color, W);
+    x = ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F;
+    return x.xyz / x.w;
+}
+
+// Timothy Lottes, \"Advanced Techniques and Optimization of HDR Color Pipelines\", GDC 2016.
+vec3 lottes(vec3 x) {
+    const vec3 a = vec3(1.6);
+    const vec3 d = vec3(0.977);
+    const vec3 hdrMax = vec3(8.0);
+    const vec3 midIn = vec3(0.18);
+    const vec3 midOut = vec3(0.267);
+    vec3 b = (-pow(midIn, a) + pow(hdrMax, a) * midOut)
+        / ((pow(hdrMax, a * d) - pow(midIn, a * d)) * midOut);
+    vec3 c = (pow(hdrMax, a * d) * pow(midIn, a) - pow(hdrMax, a) * pow(midIn, a * d) * midOut)
+        / ((pow(hdrMax, a * d) - pow(midIn, a * d)) * midOut);
+    return pow(x, a) / (pow(x, a * d) * b + c);
+}
+
+// Hajime Uchimura, \"HDR Theory and Practice\", CEDEC 2017, with the default
+// parameters of Gran Turismo Sport.
+vec3 uchimura(vec3 x) {
+    const float P = 1.0;
+    const float a = 1.0;
+    const float m = 0.22;
+    const float l = 0.4;
+    const float c = 1.33;
+    const float b = 0.0;
+    float l0 = ((P - m) * l) / a;
+    float S0 = m + l0;
+    float S1 = m + a * l0;
+    float C2 = (a * P) / (P - S1);
+    float CP = -C2 / P;
+    vec3 w0 = vec3(1.0) - smoothstep(vec3(0.0), vec3(m), x);
+    vec3 w2 = step(vec3(m + l0), x);
+    vec3 w1 = vec3(1.0) - w0 - w2;
+    vec3 T = m * pow(x / m, vec3(c)) + b;
+    vec3 S = P - (P - S1) * exp(CP * (x - S0));
+    vec3 L = m + a * (x - m);
+    return T * w0 + L * w1 + S * w2;
+}
+
+// The Unreal Engine 3 curve, which has a 2.2 gamma baked in that is removed
+// again so the color mapping applies to every operator alike.
+vec3 unreal(vec3 x) {
+    return pow(x / (x + 0.155) * 1.019, vec3(2.2));
+}
+
+void main()
+{
+    // Fetched by pixel, as the effect may cover only a viewport of the frame.
+    vec4 hdr = texelFetch(hdrTexture, ivec2(gl_FragCoord.xy), 0);
+    vec3 color = max(hdr.rgb * exposure, vec3(0.0));
+    if (toneMapping == 1) {
+        color = color / (color + vec3(1.0));
+    } else if (toneMapping == 2) {
+        color = color * (2.51 * color + 0.03) / (color * (2.43 * color + 0.59) + 0.14);
+    } else if (toneMapping == 3) {
+        color = filmic(color);
+    } else if (toneMapping == 4) {
+        color = lottes(color);
+    } else if (toneMapping == 5) {
+        color = uchimura(color);
+    } else if (toneMapping == 6) {
+        color = unreal(color);
+    }
+    outColor = vec4(color_mapping(clamp(color, 0.0, 1.0)), hdr.a);
+}
+";
+
+/// Maps a linear HDR frame to the final image with a camera's output
+/// settings.
+pub(crate) struct ToneMapEffect<'a> {
+    id: u16,
+    source: String,
+    hdr: &'a three_d::Texture2D,
+    output: Output,
+}
+
+impl<'a> ToneMapEffect<'a> {
+    pub(crate) fn new(hdr: &'a three_d::Texture2D, output: Output) -> Result<Self> {
+        let source = format!(
+            "{}\n{TONE_MAPPING_SHADER}",
+            three_d::ColorMapping::fragment_shader_source()
+        );
+        Ok(ToneMapEffect {
+            id: material_id(&source)?,
+            source,
+            hdr,
+            output,
+        })
+    }
+}
+
+impl Effect for ToneMapEffect<'_> {
+    fn fragment_shader_source(
+        &self,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> String {
+        self.source.clone()
+    }
+
+    fn id(
+        &self,
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) -> EffectMaterialId {
+        EffectMaterialId(self.id)
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        _viewer: &dyn Viewer,
+        _lights: &[&dyn Light],
+        _color_texture: Option<ColorTexture>,
+        _depth_texture: Option<DepthTexture>,
+    ) {
+        let operator = match self.output.tone_mapping {
+            ToneMapping::None => 0,
+            ToneMapping::Reinhard => 1,
+            ToneMapping::ACESFilmic => 2,
+            ToneMapping::Filmic => 3,
+            ToneMapping::Lottes => 4,
+            ToneMapping::Uchimura => 5,
+            ToneMapping::Unreal => 6,
+        };
+        program.use_texture("hdrTexture", self.hdr);
+        program.use_uniform("toneMapping", operator);
+        program.use_uniform("exposure", self.output.exposure);
+        three_d::ColorMapping::from(self.output.color_mapping).use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: three_d::DepthTest::Always,
+            write_mask: three_d::WriteMask::COLOR,
+            ..Default::default()
+        }
+    }
+}
@@ -3,17 +3,27 @@ use napi_derive::napi;
 // Re-export all enums from the enums module
 pub use crate::enums::{
     BackgroundMode, BlendEquation, BlendMultiplier, BufferBindingPoint, BufferStorage, BufferType,
-    BufferUsage, ClearFlag, Comparison, CompressionTextureType, CubeMapSide, Cull, CullFace,
-    DataType, DepthTest, ExecutionMode, FaceWinding, FenceStatus, FramebufferAttachment,
-    GeometryType, GpuQueryType, HardwareAcceleration, HeadlessError, IsophoticModel, LightType,
-    MapAccess, MaterialShadingModel, PrimitiveType, QueryResult, RenderStateError, RendererError,
-    ShaderType, StableMarkerType, StencilOperation, StereoMode, TessellationMode,
-    TextureArrayLayer, TextureFormat, TextureMagFilter, TextureMinFilter, TextureWrap,
-    ViewportScaling, WindowError,
+    BufferUsage, ClearFlag, ColorMapping, Comparison, CompressionTextureType, CubeMapSide, Cull,
+    CullFace, DataType, DepthTest, Easing, ExecutionMode, FaceWinding, FenceStatus,
+    FramebufferAttachment, GeometryType, GpuQueryType, HardwareAcceleration, HeadlessError,
+    IsophoticModel, LightType, MapAccess, MaterialShadingModel, PrimitiveType, QueryResult,
+    RenderStateError, RendererError, ShaderType, StableMarkerType, StencilOperation, StereoMode,
+    TessellationMode, TextureArrayLayer, TextureFormat, TextureMagFilter, TextureMinFilter,
+    TextureWrap, ToneMapping, ViewportScaling, WindowError,
 };
 
 // Re-export all core types from the core module
 pub use crate::core::buffer::{ElementBuffer, InstanceBuffer, UniformBuffer, VertexBuffer};
+pub use crate::core::compressed::{CompressedTexture2D, CpuCompressedTexture};
+pub use crate::core::render_target::{
+    ClearState, ColorTarget, ColorTargetMultisample, DepthTarget, DepthTargetMultisample,
+    RenderTarget, RenderTargetMultisample,
+};
+pub use crate::core::texture::{
+    CpuTexture, CpuTexture3D, DepthTexture2D, DepthTexture2DArray, DepthTextureCubeMap, Texture2D,
+    Texture2DArray, Texture3D,
+};
+pub use crate::core::viewport::Viewport;
 // Re-export core enums
 pub use crate::core::render_states::{Cull as CoreCull, DepthTest as CoreDepthTest};
 // Note: Cull and DepthTest in core/ are different from those in enums/
@@ -27,12 +37,26 @@ pub use crate::context::{
 };
 
 // Re-export all renderer types
-pub use crate::renderer::Renderer;
+pub use crate::renderer::camera::{Frustum, Ray};
+pub use crate::renderer::camera_path::{CameraKeyframe, CameraPath};
+pub use crate::renderer::effect::ScreenEffect;
+pub use crate::renderer::geometry::{CpuMesh, Mesh};
+pub use crate::renderer::light::{
+    AmbientLight, Attenuation, DirectionalLight, PointLight, SpotLight,
+};
+pub use crate::renderer::material::{
+    ColorMaterial, CustomMaterial, DeferredPhysicalMaterial, PhysicalMaterial,
+};
+pub use crate::renderer::object::Gm;
+pub use crate::renderer::scene::{Node, Scene};
+pub use crate::renderer::stats::{FrameStats, PassStats};
+pub use crate::renderer::stereo::StereoCamera;
+pub use crate::renderer::{Camera, IntersectionResult, Renderer, ViewportOptions};
 
 // Re-export all prelude types
 pub use crate::prelude::{
-    Matrix2, Matrix3, Matrix4, NDeg, NQuaternion, NRad, NSrgba, Point2, Point3, Vector2, Vector3,
-    Vector4, NF16,
+    AxisAlignedBoundingBox, Matrix2, Matrix3, Matrix4, NDeg, NQuaternion, NRad, NSrgba, Point2,
+    Point3, Vector2, Vector3, Vector4, NF16,
 };
 
 // ============================================================================
@@ -147,9 +171,9 @@ pub type Half = NF16;
 #[napi]
 pub type GeometryBounds = [f64; 6];
 
-/// Type alias for frustum culling bounds
+/// Type alias for the view frustum used for culling.
 #[napi]
-pub type FrustumBounds = [f64; 6];
+pub type FrustumBounds = Frustum;
 
 // ============================================================================
 // Buffer Type Aliases
@@ -4,11 +4,41 @@ import * as three_d from "../index";
 describe("Buffer System", () => {
   describe("VertexBuffer", () => {
     test("Constructor", () => {
-      const vbo = new three_d.VertexBuffer("positions", 1000, "static_draw");
+      const ctx = new three_d.Context();
+      const vbo = new three_d.VertexBuffer(ctx, new Float32Array(3000), 3);
       expect(vbo).toBeInstanceOf(three_d.VertexBuffer);
-      expect(vbo.getBufferType()).toBe("Vertex");
-      expect(vbo.estimateSizeBytes()).toBeGreaterThan(0);
-      expect(vbo.isValid()).toBe(true);
+      expect(vbo.components).toBe(3);
+      expect(vbo.vertexCount).toBe(1000);
+      expect(vbo.sizeBytes).toBe(12000);
+    });
+  });
+
+  describe("VertexBuffer with context", () => {
+    test("Half precision attributes", () => {
+      const ctx = new three_d.Context();
+      const normals = new Float32Array([0, 0, 1, 0, 1, 0, 1, 0, 0]);
+      const full = new three_d.VertexBuffer(ctx, normals, 3);
+      const half = new three_d.VertexBuffer(ctx, normals, 3, true);
+      expect(full.isHalf).toBe(false);
+      expect(half.isHalf).toBe(true);
+      expect(half.vertexCount).toBe(3);
+      expect(half.sizeBytes).toBe(full.sizeBytes / 2);
+
+      const bits = new three_d.VertexBuffer(ctx, three_d.Nf16.fromFloat32Array(normals), 3);
+      expect(bits.isHalf).toBe(true);
+      bits.fill(new Float32Array(12));
+      expect(bits.vertexCount).toBe(4);
+
+      const widened = new three_d.VertexBuffer(ctx, three_d.Nf16.fromFloat32Array(normals), 3, false);
+      expect(widened.isHalf).toBe(false);
+      expect(widened.sizeBytes).toBe(full.sizeBytes);
+      expect(ctx.isValid()).toBe(true);
+    });
+
+    test("Rejects partial vertices", () => {
+      const ctx = new three_d.Context();
+      expect(() => new three_d.VertexBuffer(ctx, new Float32Array(4), 3)).toThrow();
+      expect(() => new three_d.VertexBuffer(ctx, new Float32Array(4), 5)).toThrow();
     });
   });
 
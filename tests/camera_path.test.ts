@@ -0,0 +1,209 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+const close = (actual: number[], expected: number[], digits = 4) => {
+  expect(actual).toHaveLength(expected.length);
+  expected.forEach((e, i) => expect(actual[i]).toBeCloseTo(e, digits));
+};
+
+const camera = () => {
+  const cam = new three_d.Camera(0, 0, 10, 0, 0, 0, 0, 1, 0, 90, 0.1, 100);
+  cam.setViewport(three_d.Viewport.atOrigin(100, 100));
+  return cam;
+};
+
+describe("CameraPath", () => {
+  test("Interpolates between two keyframes", () => {
+    const path = new three_d.CameraPath();
+    path.addKeyframe({ time: 1000, position: [10, 0, 0], target: [10, 0, -5] });
+    path.addKeyframe({ time: 0, position: [0, 0, 0], target: [0, 0, -5] });
+    expect(path.keyframeCount).toBe(2);
+    expect(path.duration).toBe(1000);
+
+    const pose = path.sample(250);
+    expect(pose.time).toBe(250);
+    close(pose.position, [2.5, 0, 0]);
+    close(pose.target, [2.5, 0, -5]);
+    close(pose.up, [0, 1, 0]);
+    expect(pose.fov).toBeUndefined();
+
+    // Times outside the path are clamped.
+    close(path.sample(-100).position, [0, 0, 0]);
+    close(path.sample(5000).position, [10, 0, 0]);
+  });
+
+  test("Passes through every keyframe", () => {
+    const path = new three_d.CameraPath();
+    const keys = [
+      { time: 0, position: [0, 0, 0], target: [0, 0, -1], fov: 30 },
+      { time: 400, position: [4, 2, 0], target: [4, 2, -1], fov: 60 },
+      { time: 1000, position: [8, 0, 3], target: [8, 0, -1], fov: 45 },
+    ];
+    keys.forEach((k) => path.addKeyframe(k));
+    for (const k of keys) {
+      const pose = path.sample(k.time);
+      close(pose.position, k.position);
+      close(pose.target, k.target);
+      expect(pose.fov).toBeCloseTo(k.fov, 3);
+    }
+    // The spline keeps moving through the middle keyframe instead of stopping.
+    const before = path.sample(390).position;
+    const after = path.sample(410).position;
+    expect(after[0] - before[0]).toBeGreaterThan(0.1);
+  });
+
+  test("Eases over the whole path", () => {
+    const path = new three_d.CameraPath(three_d.Easing.EaseInOut);
+    path.addKeyframe({ time: 0, position: [0, 0, 0], target: [0, 0, -1] });
+    path.addKeyframe({ time: 1000, position: [100, 0, 0], target: [100, 0, -1] });
+    expect(path.sample(250).position[0]).toBeCloseTo(6.25, 3);
+    expect(path.sample(500).position[0]).toBeCloseTo(50, 3);
+    expect(path.sample(750).position[0]).toBeCloseTo(93.75, 3);
+  });
+
+  test("Slerps the up direction", () => {
+    const path = new three_d.CameraPath();
+    path.addKeyframe({ time: 0, position: [0, 0, 0], target: [0, 0, -1], up: [0, 1, 0] });
+    path.addKeyframe({ time: 100, position: [0, 0, 0], target: [0, 0, -1], up: [1, 0, 0] });
+    const s = Math.sqrt(0.5);
+    close(path.sample(50).up, [s, s, 0]);
+  });
+
+  test("Keeps the up direction perpendicular through large target swings", () => {
+    const swing = new three_d.CameraPath();
+    swing.addKeyframe({ time: 0, position: [0, 0, 0], target: [0, 0, -5] });
+    swing.addKeyframe({ time: 1000, position: [0, 0, 0], target: [0, 5, 0.5], up: [1, 0, 0] });
+    swing.addKeyframe({ time: 2000, position: [0, 0, 0], target: [0, 0, 5] });
+    // The slerped up direction passes within about a degree of the view
+    // direction here.
+    const turn = new three_d.CameraPath();
+    turn.addKeyframe({ time: 0, position: [0, 0, 0], target: [3, -5, 3], up: [2, 0, 3] });
+    turn.addKeyframe({ time: 1000, position: [0, 0, 0], target: [-1, 1, -1], up: [-2, -5, -3] });
+
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+    for (const path of [swing, turn]) {
+      for (let t = 0; t <= path.duration; t += 25) {
+        const { position, target, up } = path.sample(t);
+        const direction = target.map((v, i) => v - position[i]);
+        const dot = direction.reduce((sum, v, i) => sum + v * (up as number[])[i], 0);
+        expect(Math.abs(dot / Math.hypot(...direction))).toBeLessThan(1e-4);
+        expect(Math.hypot(...(up as number[]))).toBeCloseTo(1, 4);
+        path.apply(cam, t);
+      }
+    }
+  });
+
+  test("Sampling is repeatable", () => {
+    const path = new three_d.CameraPath(three_d.Easing.EaseOut);
+    path.addKeyframe({ time: 0, position: [1, 2, 3], target: [0, 0, 0] });
+    path.addKeyframe({ time: 300, position: [-3, 1, 2], target: [1, 0, 0] });
+    path.addKeyframe({ time: 900, position: [0, 5, -4], target: [0, 1, 0] });
+    for (const t of [0, 123, 456, 789, 900]) {
+      expect(path.sample(t)).toEqual(path.sample(t));
+    }
+  });
+
+  test("Applies a pose to a camera", () => {
+    const path = new three_d.CameraPath();
+    path.addKeyframe({ time: 0, position: [0, 0, 5], target: [0, 0, 0], fov: 40 });
+    path.addKeyframe({ time: 100, position: [0, 0, 15], target: [0, 0, 0], fov: 80 });
+    const cam = camera();
+    path.apply(cam, 50);
+    close(cam.getPosition(), [0, 0, 10]);
+    expect(cam.fov).toBeCloseTo(60, 3);
+
+    // The field of view is ignored by orthographic cameras.
+    const ortho = three_d.Camera.orthographic(0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 0.1, 100);
+    path.apply(ortho, 100);
+    close(ortho.getPosition(), [0, 0, 15]);
+  });
+
+  test("Rejects invalid keyframes", () => {
+    const path = new three_d.CameraPath();
+    expect(() => path.sample(0)).toThrow("no keyframes");
+    expect(() => path.addKeyframe({ time: -1, position: [0, 0, 1], target: [0, 0, 0] })).toThrow(
+      "non-negative",
+    );
+    expect(() => path.addKeyframe({ time: 0, position: [0, 0], target: [0, 0, 0] })).toThrow(
+      "3 components",
+    );
+    expect(() => path.addKeyframe({ time: 0, position: [0, 0, 0], target: [0, 0, 0] })).toThrow(
+      "must differ",
+    );
+    path.addKeyframe({ time: 0, position: [0, 0, 1], target: [0, 0, 0] });
+    expect(() => path.addKeyframe({ time: 0, position: [0, 0, 2], target: [0, 0, 0] })).toThrow(
+      "already exists",
+    );
+    expect(() =>
+      path.addKeyframe({ time: 10, position: [0, 0, 2], target: [0, 0, 0], fov: 45 }),
+    ).toThrow("field of view");
+  });
+});
+
+describe("flyTo", () => {
+  test("Frames a bounding box with a perspective camera", () => {
+    const cam = camera();
+    const box = new three_d.AxisAlignedBoundingBox(9, -1, -1, 11, 1, 1);
+    const path = cam.flyTo(box, 2000);
+    expect(path.duration).toBe(2000);
+    close(path.sample(0).position, [0, 0, 10]);
+
+    path.apply(cam, 2000);
+    close(cam.getTarget(), [10, 0, 0]);
+    close(cam.getViewDirection(), [0, 0, -1]);
+    close(cam.getPosition(), [10, 0, Math.sqrt(6)]);
+    expect(cam.fov).toBeCloseTo(90, 3);
+
+    const frustum = cam.frustum();
+    for (const x of [9, 11])
+      for (const y of [-1, 1])
+        for (const z of [-1, 1]) expect(frustum.containsPoint(x, y, z)).toBe(true);
+  });
+
+  test("Eases in and out", () => {
+    const path = camera().flyTo(new three_d.AxisAlignedBoundingBox(9, -1, -1, 11, 1, 1), 1000);
+    expect(path.sample(100).position[0]).toBeCloseTo(0.04, 3);
+    expect(path.sample(500).position[0]).toBeCloseTo(5, 3);
+  });
+
+  test("Frames a bounding box with an orthographic camera", () => {
+    const cam = three_d.Camera.orthographic(0, 0, 10, 0, 0, 0, 0, 1, 0, 0.5, 0.1, 100);
+    cam.setViewport(three_d.Viewport.atOrigin(200, 100));
+    cam.flyTo(new three_d.AxisAlignedBoundingBox(-2, -2, -2, 2, 2, 2), 500).apply(cam, 500);
+
+    // A view height of 0.5 per unit distance needs a distance of 8 * sqrt(3)
+    // to fit the bounding sphere of radius 2 * sqrt(3).
+    close(cam.getPosition(), [0, 0, 8 * Math.sqrt(3)], 3);
+    const frustum = cam.frustum();
+    expect(frustum.containsPoint(2, 2, 2)).toBe(true);
+    expect(frustum.containsPoint(-2, -2, -2)).toBe(true);
+  });
+
+  test("Fits the aspect ratio of the frame rendered last", () => {
+    const box = new three_d.AxisAlignedBoundingBox(-1, -1, -1, 1, 1, 1);
+    const wide = camera();
+    wide.flyTo(box, 100).apply(wide, 100);
+
+    const tall = new three_d.Camera(0, 0, 10, 0, 0, 0, 0, 1, 0, 90, 0.1, 100);
+    const renderer = new three_d.Renderer(50, 100);
+    renderer.init();
+    renderer.render([], tall);
+    tall.flyTo(box, 100).apply(tall, 100);
+    expect(tall.getPosition()[2]).toBeGreaterThan(wide.getPosition()[2] + 0.5);
+    const frustum = tall.frustum();
+    for (const x of [-1, 1])
+      for (const y of [-1, 1])
+        for (const z of [-1, 1]) expect(frustum.containsPoint(x, y, z)).toBe(true);
+  });
+
+  test("Rejects empty boxes", () => {
+    expect(() => camera().flyTo(three_d.AxisAlignedBoundingBox.empty(), 100)).toThrow("empty");
+  });
+
+  test("Rejects durations that are not positive", () => {
+    const box = new three_d.AxisAlignedBoundingBox(-1, -1, -1, 1, 1, 1);
+    for (const duration of [0, -100, NaN, Infinity]) {
+      expect(() => camera().flyTo(box, duration)).toThrow("positive and finite");
+    }
+  });
+});
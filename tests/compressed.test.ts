@@ -0,0 +1,225 @@
+import { expect, test, describe } from "bun:test";
+import { deflateSync } from "node:zlib";
+import * as three_d from "../index";
+
+const KTX2_IDENTIFIER = [0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a];
+
+function ktx2(vkFormat: number, width: number, height: number, levels: Buffer[], zlib = false): Buffer {
+  const header = Buffer.alloc(80 + levels.length * 24);
+  header.set(KTX2_IDENTIFIER);
+  header.writeUInt32LE(vkFormat, 12);
+  header.writeUInt32LE(1, 16);
+  header.writeUInt32LE(width, 20);
+  header.writeUInt32LE(height, 24);
+  header.writeUInt32LE(1, 36);
+  header.writeUInt32LE(levels.length, 40);
+  header.writeUInt32LE(zlib ? 3 : 0, 44);
+  const stored = levels.map((level) => (zlib ? deflateSync(level) : level));
+  let offset = header.length;
+  stored.forEach((level, i) => {
+    header.writeBigUInt64LE(BigInt(offset), 80 + i * 24);
+    header.writeBigUInt64LE(BigInt(level.length), 88 + i * 24);
+    header.writeBigUInt64LE(BigInt(levels[i].length), 96 + i * 24);
+    offset += level.length;
+  });
+  return Buffer.concat([header, ...stored]);
+}
+
+function dds(fourCC: string, width: number, height: number, levels: Buffer[]): Buffer {
+  const header = Buffer.alloc(128);
+  header.write("DDS ", 0);
+  header.writeUInt32LE(124, 4);
+  header.writeUInt32LE(0x1007 | (levels.length > 1 ? 0x20000 : 0), 8);
+  header.writeUInt32LE(height, 12);
+  header.writeUInt32LE(width, 16);
+  header.writeUInt32LE(levels.length, 28);
+  header.writeUInt32LE(32, 76);
+  header.writeUInt32LE(0x4, 80);
+  header.write(fourCC, 84);
+  return Buffer.concat([header, ...levels]);
+}
+
+// A BC1 block with red and blue endpoints, every texel using the first one.
+function bc1Red(blocks: number): Buffer {
+  const data = Buffer.alloc(blocks * 8);
+  for (let i = 0; i < blocks; i++) {
+    data.writeUInt16LE(0xf800, i * 8);
+    data.writeUInt16LE(0x001f, i * 8 + 2);
+  }
+  return data;
+}
+
+// 8x8 textures with a red top half and a blue bottom half and four mip
+// levels, encoded by basisu as ETC1S and as Zstandard compressed UASTC.
+const ETC1S = Buffer.from(
+  "q0tUWCAyMLsNChoKAAAAAAEAAAAIAAAACAAAAAAAAAAAAAAAAQAAAAQAAAABAAAAsAAAACwAAADcAAAAJAAAAAABAAAAAAAA" +
+  "1QAAAAAAAADYAQAAAAAAAAMAAAAAAAAAAAAAAAAAAADXAQAAAAAAAAEAAAAAAAAAAAAAAAAAAADWAQAAAAAAAAEAAAAAAAAA" +
+  "AAAAAAAAAADVAQAAAAAAAAEAAAAAAAAAAAAAAAAAAAAsAAAAAAAAAAIAKACjAQIAAwMAAAAAAAAAAAAAAAA/AAAAAAAAAAAA" +
+  "/////x8AAABLVFh3cml0ZXIAQmFzaXMgVW5pdmVyc2FsIDEuMTYAAAUAAwA1AAAADQAAAC8AAAAAAAAAAAAAAAAAAAADAAAA" +
+  "AAAAAAAAAAAAAAAAAAAAAAEAAAAAAAAAAAAAAAAAAAAAAAAAAQAAAAAAAAAAAAAAAAAAAAAAAAABAAAAAAAAAAAAAAAgwJQA" +
+  "AAAAABDECLsgQNSAAQAAQFBxVvgAD2BKwAAAAAAIGyGQPwCmAAgAAACAoEYotHyWG1RVBQBQBQAAqKqqqgIAwUQAAAAAAAAS" +
+  "5b9NARABAAAAAAgySICoAQMAAICgidHoCQQAEwAAAAAAAAggAAMNKYb/AA==",
+  "base64",
+);
+const UASTC = Buffer.from(
+  "q0tUWCAyMLsNChoKAAAAAAEAAAAIAAAACAAAAAAAAAAAAAAAAQAAAAQAAAACAAAAsAAAACwAAADcAAAAJAAAAAAAAAAAAAAA" +
+  "AAAAAAAAAABLAQAAAAAAACIAAAAAAAAAQAAAAAAAAAAyAQAAAAAAABkAAAAAAAAAEAAAAAAAAAAZAQAAAAAAABkAAAAAAAAA" +
+  "EAAAAAAAAAAAAQAAAAAAABkAAAAAAAAAEAAAAAAAAAAsAAAAAAAAAAIAKACmAQIAAwMAAAAAAAAAAAAAAAB/AAAAAAAAAAAA" +
+  "/////x8AAABLVFh3cml0ZXIAQmFzaXMgVW5pdmVyc2FsIDEuMTYAACi1L/0gEIEAAJcXgPefa6ABAAAAAAAAAAAotS/9IBCB" +
+  "AACbkkujWhwArhQRu7u7u7u7KLUv/SAQgQAA+7bLYBkcAC4AAAAA/////yi1L/0gQM0AAHj3HwDgH3oAFwDg/x8C4AEDAADk" +
+  "mSgUwAg=",
+  "base64",
+);
+
+describe("loadCompressedTexture", () => {
+  test("DDS with a mip chain", () => {
+    const texture = three_d.loadCompressedTexture(dds("DXT1", 8, 8, [bc1Red(4), bc1Red(1), bc1Red(1), bc1Red(1)]));
+    expect(texture.width).toBe(8);
+    expect(texture.height).toBe(8);
+    expect(texture.mipLevels).toBe(4);
+    expect(texture.compression).toBe(three_d.CompressionTextureType.S3TC);
+    expect(texture.srgb).toBe(false);
+
+    const decompressed = texture.decompress();
+    expect(decompressed.format).toBe(three_d.TextureFormat.R8G8B8A8);
+    expect(Array.from(decompressed.pixels().slice(0, 4))).toEqual([255, 0, 0, 255]);
+  });
+
+  test("KTX2 with zlib supercompression and partial blocks", () => {
+    const data = Buffer.alloc(4 * 16, 0xff);
+    // ETC2 RGBA8 sRGB, 6x5 texels in 2x2 blocks.
+    const texture = three_d.loadCompressedTexture(ktx2(152, 6, 5, [data], true));
+    expect(texture.compression).toBe(three_d.CompressionTextureType.ETC2EAC);
+    expect(texture.srgb).toBe(true);
+    expect(texture.decompress().pixels().length).toBe(6 * 5 * 4);
+  });
+
+  test("Basis Universal textures are transcoded", () => {
+    for (const file of [ETC1S, UASTC]) {
+      const bptc = three_d.loadCompressedTexture(file);
+      expect(bptc.compression).toBe(three_d.CompressionTextureType.BPTC);
+      expect(bptc.mipLevels).toBe(4);
+      expect(bptc.srgb).toBe(true);
+      const pixels = bptc.decompress().pixels();
+      expect(pixels[0]).toBeGreaterThan(250);
+      expect(pixels[2]).toBe(0);
+      expect(pixels[pixels.length - 4]).toBe(0);
+      expect(pixels[pixels.length - 2]).toBeGreaterThan(250);
+
+      const etc = three_d.loadCompressedTexture(file, {}, three_d.CompressionTextureType.ETC2);
+      expect(etc.compression).toBe(three_d.CompressionTextureType.ETC2);
+      const astc = three_d.loadCompressedTexture(file, {}, three_d.CompressionTextureType.ASTC);
+      expect(astc.compression).toBe(three_d.CompressionTextureType.ASTC);
+      const rgba = astc.decompress().pixels();
+      expect(rgba[0]).toBeGreaterThan(250);
+      expect(rgba[rgba.length - 2]).toBeGreaterThan(250);
+    }
+    expect(() =>
+      three_d.loadCompressedTexture(ETC1S, {}, three_d.CompressionTextureType.SVGA),
+    ).toThrow(/cannot transcode/);
+  });
+
+  test("BC6H and ASTC decode on the CPU", () => {
+    // A BC6H mode 11 block with every endpoint at 1.0.
+    let bits = 3n;
+    for (let i = 0; i < 6; i++) bits |= 495n << BigInt(5 + i * 10);
+    const bc6h = Buffer.alloc(16);
+    bc6h.writeBigUInt64LE(bits & 0xffffffffffffffffn, 0);
+    bc6h.writeBigUInt64LE(bits >> 64n, 8);
+    const hdr = three_d.loadCompressedTexture(ktx2(143, 4, 4, [bc6h])).decompress();
+    expect(hdr.format).toBe(three_d.TextureFormat.R16G16B16F);
+    expect(Array.from(hdr.pixels().slice(0, 3))).toEqual([0x3c00, 0x3c00, 0x3c00]);
+
+    // An ASTC 4x4 void extent block of opaque red.
+    const astc = Buffer.from([
+      0xfc, 0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0xff, 0xff,
+    ]);
+    const ldr = three_d.loadCompressedTexture(ktx2(157, 4, 4, [astc])).decompress();
+    expect(Array.from(ldr.pixels().slice(60))).toEqual([255, 0, 0, 255]);
+  });
+
+  test("Async loading resolves to the same texture", async () => {
+    const file = dds("DXT1", 8, 8, [bc1Red(4)]);
+    const texture = await three_d.loadCompressedTextureAsync(file);
+    expect(texture.width).toBe(8);
+    expect(Array.from(texture.decompress().pixels())).toEqual(
+      Array.from(three_d.loadCompressedTexture(file).decompress().pixels()),
+    );
+  });
+
+  test("Rejects unsupported files", () => {
+    expect(() => three_d.loadCompressedTexture(Buffer.from("not a texture"))).toThrow();
+    expect(() => three_d.loadCompressedTexture(ktx2(0, 4, 4, [Buffer.alloc(16)]))).toThrow(/data format descriptor/);
+    expect(() => three_d.loadCompressedTexture(ktx2(131, 8, 8, [Buffer.alloc(8)]))).toThrow(/Mip level 0/);
+    expect(() => three_d.loadCompressedTexture("/nonexistent/texture.ktx2")).toThrow();
+  });
+
+  test("Rejects sizes that do not match the format", () => {
+    const lying = ktx2(131, 4, 4, [Buffer.alloc(8)], true);
+    lying.writeBigUInt64LE(2n ** 62n, 96);
+    expect(() => three_d.loadCompressedTexture(lying)).toThrow(/does not inflate to 8 bytes/);
+    const short = ktx2(131, 8, 8, [Buffer.alloc(8)], true);
+    short.writeBigUInt64LE(32n, 96);
+    expect(() => three_d.loadCompressedTexture(short)).toThrow(/inflates to 8 bytes/);
+    expect(() => three_d.loadCompressedTexture(dds("DXT1", 0xffffffff, 0xffffffff, [bc1Red(1)]))).toThrow(
+      /truncated image data|too large/,
+    );
+  });
+});
+
+describe("CompressedTexture2D", () => {
+  test("Native upload and CPU fallback read back the same texels", () => {
+    const ctx = new three_d.Context();
+    const data = Buffer.alloc(16 * 16);
+    for (let i = 0; i < data.length; i++) data[i] = (i * 97 + 13) & 0xff;
+    // BC7 unorm, 16x16 texels.
+    const cpu = three_d.loadCompressedTexture(ktx2(145, 16, 16, [data]));
+
+    const fallback = new three_d.CompressedTexture2D(ctx, cpu, true);
+    expect(fallback.isCompressed).toBe(false);
+    if (ctx.supportsCompression(three_d.CompressionTextureType.BPTC)) {
+      const native = new three_d.CompressedTexture2D(ctx, cpu);
+      expect(native.isCompressed).toBe(true);
+      expect(Array.from(native.readPixels())).toEqual(Array.from(fallback.readPixels()));
+    }
+
+    const material = new three_d.CustomMaterial(`
+      uniform sampler2D albedo;
+      in vec2 uvs;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = texture(albedo, uvs); }
+    `);
+    material.setCompressedTexture("albedo", fallback);
+    expect(ctx.isValid()).toBe(true);
+  });
+
+  test("CPU decoding matches the GPU for every block format", () => {
+    const ctx = new three_d.Context();
+    // KTX2 vkFormat and block size of BC1, BC3, BC4, BC5, ETC2 RGB8,
+    // ETC2 RGB8A1, ETC2 RGBA8, EAC R11 and EAC RG11, all unorm.
+    const formats = [
+      [133, 8],
+      [137, 16],
+      [139, 8],
+      [141, 16],
+      [147, 8],
+      [149, 8],
+      [151, 16],
+      [153, 8],
+      [155, 16],
+    ];
+    for (const [vkFormat, blockBytes] of formats) {
+      const data = Buffer.alloc(16 * blockBytes);
+      for (let i = 0; i < data.length; i++) data[i] = (i * 61 + vkFormat) & 0xff;
+      const cpu = three_d.loadCompressedTexture(ktx2(vkFormat, 16, 16, [data]));
+      if (!ctx.supportsCompression(cpu.compression)) continue;
+      const native = Array.from(new three_d.CompressedTexture2D(ctx, cpu).readPixels());
+      const fallback = Array.from(new three_d.CompressedTexture2D(ctx, cpu, true).readPixels());
+      expect(fallback.length).toBe(native.length);
+      // BC1-3 color interpolation is only specified to within a few
+      // percent, and EAC is decoded to 8 bits on the CPU.
+      const differences = native.filter((value, i) => Math.abs(value - fallback[i]) > 3);
+      expect(differences.length).toBe(0);
+    }
+  });
+});
@@ -0,0 +1,139 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+function gradient(width: number, height: number): three_d.CpuTexture {
+  const data = new Uint8Array(width * height * 3);
+  for (let y = 0; y < height; y++) {
+    for (let x = 0; x < width; x++) {
+      const i = (y * width + x) * 3;
+      data[i] = x * 8;
+      data[i + 1] = y * 10;
+      data[i + 2] = 128;
+    }
+  }
+  return new three_d.CpuTexture(width, height, three_d.TextureFormat.R8G8B8, data);
+}
+
+function halfToFloat(bits: number): number {
+  const exponent = (bits >> 10) & 0x1f;
+  const mantissa = bits & 0x3ff;
+  const sign = bits & 0x8000 ? -1 : 1;
+  if (exponent === 0) return sign * 2 ** -14 * (mantissa / 1024);
+  return sign * 2 ** (exponent - 15) * (1 + mantissa / 1024);
+}
+
+function maxError(a: ArrayLike<number>, b: ArrayLike<number>): number {
+  let max = 0;
+  for (let i = 0; i < a.length; i++) max = Math.max(max, Math.abs(a[i] - b[i]));
+  return max;
+}
+
+describe("encodeImage / loadTexture", () => {
+  test("PNG round trip is lossless", () => {
+    const texture = gradient(32, 24);
+    const png = three_d.encodeImage(texture, three_d.ImageFormat.Png);
+    expect(png.subarray(1, 4).toString()).toBe("PNG");
+    const loaded = three_d.loadTexture(png);
+    expect(loaded.width).toBe(32);
+    expect(loaded.height).toBe(24);
+    expect(loaded.format).toBe(three_d.TextureFormat.R8G8B8);
+    expect(Array.from(loaded.pixels())).toEqual(Array.from(texture.pixels()));
+  });
+
+  test("JPEG round trip stays close", () => {
+    const texture = gradient(32, 24);
+    const jpeg = three_d.encodeImage(texture, three_d.ImageFormat.Jpeg, 95);
+    expect(jpeg[0]).toBe(0xff);
+    expect(jpeg[1]).toBe(0xd8);
+    const loaded = three_d.loadTexture(jpeg);
+    expect(loaded.format).toBe(three_d.TextureFormat.R8G8B8);
+    expect(maxError(loaded.pixels(), texture.pixels())).toBeLessThan(8);
+  });
+
+  test("EXR round trip keeps float values", () => {
+    const values = new Float32Array([0.5, 1, 2, 1, 0, 0.25, 0.125, 0.5]);
+    const texture = new three_d.CpuTexture(
+      2,
+      1,
+      three_d.TextureFormat.R32G32B32A32F,
+      values,
+    );
+    const loaded = three_d.loadTexture(three_d.encodeImage(texture, three_d.ImageFormat.Exr));
+    expect(loaded.format).toBe(three_d.TextureFormat.R32G32B32A32F);
+    expect(Array.from(loaded.pixels())).toEqual(Array.from(values));
+  });
+
+  test("8-bit images decode to linear half floats on request", () => {
+    const texture = new three_d.CpuTexture(
+      1,
+      1,
+      three_d.TextureFormat.R8G8B8A8,
+      new Uint8Array([255, 188, 0, 128]),
+    );
+    const png = three_d.encodeImage(texture, three_d.ImageFormat.Png);
+    const linear = three_d.loadTexture(png, { linear: true });
+    expect(linear.format).toBe(three_d.TextureFormat.R16G16B16A16F);
+    const halves = Array.from(linear.pixels() as Uint16Array).map(halfToFloat);
+    expect(halves[0]).toBeCloseTo(1, 2);
+    expect(halves[1]).toBeCloseTo(0.5, 2);
+    expect(halves[2]).toBe(0);
+    expect(halves[3]).toBeCloseTo(128 / 255, 2);
+  });
+
+  test("Radiance HDR decodes to linear floats", () => {
+    const header = Buffer.from("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n");
+    const hdr = Buffer.concat([header, Buffer.from([128, 64, 32, 129, 128, 128, 128, 128])]);
+    const loaded = three_d.loadTexture(hdr);
+    expect(loaded.width).toBe(2);
+    expect(loaded.height).toBe(1);
+    expect(loaded.format).toBe(three_d.TextureFormat.R32G32B32F);
+    expect(Array.from(loaded.pixels())).toEqual([1, 0.5, 0.25, 0.5, 0.5, 0.5]);
+  });
+
+  test("Rejects corrupt and oversized JPEGs", () => {
+    const jpeg = three_d.encodeImage(gradient(32, 24), three_d.ImageFormat.Jpeg);
+    // A frame header claiming 65535x65535 pixels.
+    const huge = Buffer.from(jpeg);
+    const frame = huge.indexOf(Buffer.from([0xff, 0xc0]));
+    huge.writeUInt16BE(0xffff, frame + 5);
+    huge.writeUInt16BE(0xffff, frame + 7);
+    expect(() => three_d.loadTexture(huge)).toThrow();
+    // Garbage entropy coded data either decodes to something or fails cleanly.
+    const corrupt = Buffer.from(jpeg);
+    const scan = corrupt.indexOf(Buffer.from([0xff, 0xda]));
+    corrupt.fill(0xfe, scan + 20, corrupt.length - 2);
+    try {
+      expect(three_d.loadTexture(corrupt).width).toBe(32);
+    } catch (e) {
+      expect(String(e)).toContain("Jpeg");
+    }
+  });
+
+  test("Rejects unknown data", () => {
+    expect(() => three_d.loadTexture(Buffer.from("not an image"))).toThrow();
+    expect(() => three_d.loadTexture("/does/not/exist.png")).toThrow();
+  });
+});
+
+describe("loadTextureAsync", () => {
+  test("Decodes off the main thread", async () => {
+    const texture = gradient(64, 48);
+    const png = three_d.encodeImage(texture, three_d.ImageFormat.Png);
+    const pending = [1, 2, 3].map(() => three_d.loadTextureAsync(png));
+    expect(pending[0]).toBeInstanceOf(Promise);
+    for (const loaded of await Promise.all(pending)) {
+      expect(loaded.width).toBe(64);
+      expect(Array.from(loaded.pixels())).toEqual(Array.from(texture.pixels()));
+    }
+
+    const ctx = new three_d.Context();
+    const uploaded = new three_d.Texture2D(ctx, await three_d.loadTextureAsync(png, { linear: true }));
+    expect(uploaded.format).toBe(three_d.TextureFormat.R16G16B16F);
+  });
+
+  test("Rejects unknown data", async () => {
+    await expect(three_d.loadTextureAsync(Buffer.from("not an image"))).rejects.toThrow();
+    await expect(three_d.loadTextureAsync("/does/not/exist.png")).rejects.toThrow(/Failed to read/);
+    await expect(three_d.loadCompressedTextureAsync(Buffer.from("not a texture"))).rejects.toThrow(/KTX2 or DDS/);
+  });
+});
@@ -109,6 +109,27 @@ describe("Math Types (Quaternions/Units)", () => {
     expect(f.value).toBe(1.5);
   });
 
+  test("NF16 rounds to binary16", () => {
+    expect(new three_d.Nf16(1.0).toBits()).toBe(0x3c00);
+    expect(new three_d.Nf16(-2.0).toBits()).toBe(0xc000);
+    expect(new three_d.Nf16(65504).toBits()).toBe(0x7bff);
+    expect(new three_d.Nf16(0.1).value).toBe(0.0999755859375);
+    expect(new three_d.Nf16(1e6).value).toBe(Infinity);
+
+    const f = three_d.Nf16.fromBits(0x3555);
+    expect(f.value).toBeCloseTo(1 / 3, 3);
+    f.value = 2049;
+    expect(f.value).toBe(2048);
+    expect(() => three_d.Nf16.fromBits(0x10000)).toThrow();
+  });
+
+  test("NF16 bulk conversion", () => {
+    const bits = three_d.Nf16.fromFloat32Array(new Float32Array([0, 1, -0.5, 65504]));
+    expect(bits).toBeInstanceOf(Uint16Array);
+    expect(Array.from(bits)).toEqual([0x0000, 0x3c00, 0xb800, 0x7bff]);
+    expect(Array.from(three_d.Nf16.toFloat32Array(bits))).toEqual([0, 1, -0.5, 65504]);
+  });
+
   test("NSrgba", () => {
     const c = new three_d.NSrgba(1.0, 0.5, 0.0, 1.0);
     expect(c.r).toBe(1.0);
@@ -131,4 +152,25 @@ describe("AxisAlignedBoundingBox", () => {
     const merged = aabb1.merge(aabb2);
     expect(merged).toBeInstanceOf(three_d.AxisAlignedBoundingBox);
   });
+
+  test("Corners, center and size", () => {
+    const aabb = new three_d.AxisAlignedBoundingBox(4, -2, 0, -4, 2, 6);
+    expect(aabb.min()).toEqual([-4, -2, 0]);
+    expect(aabb.max()).toEqual([4, 2, 6]);
+    expect(aabb.center()).toEqual([0, 0, 3]);
+    expect(aabb.size()).toEqual([8, 4, 6]);
+
+    const merged = aabb.merge(new three_d.AxisAlignedBoundingBox(0, 0, 0, 10, 1, 1));
+    expect(merged.min()).toEqual([-4, -2, 0]);
+    expect(merged.max()).toEqual([10, 2, 6]);
+  });
+
+  test("Empty box", () => {
+    const empty = three_d.AxisAlignedBoundingBox.empty();
+    expect(empty.isEmpty()).toBe(true);
+    expect(empty.contains(0, 0, 0)).toBe(false);
+    const grown = empty.merge(new three_d.AxisAlignedBoundingBox(1, 1, 1, 2, 2, 2));
+    expect(grown.isEmpty()).toBe(false);
+    expect(grown.min()).toEqual([1, 1, 1]);
+  });
 });
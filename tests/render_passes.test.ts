@@ -0,0 +1,207 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+const SIZE = 8;
+
+function colorTexture(ctx: three_d.Context) {
+  return three_d.Texture2D.fromData(
+    ctx,
+    SIZE,
+    SIZE,
+    three_d.TextureFormat.R8G8B8A8,
+    new Uint8Array(SIZE * SIZE * 4),
+    { mipmap: false },
+  );
+}
+
+function pixel(pixels: Uint8Array, x: number, y: number) {
+  const i = (y * SIZE + x) * 4;
+  return Array.from(pixels.slice(i, i + 4));
+}
+
+const camera = () => new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+
+// A square covering the middle of the view, within pixels 2 to 5.
+function square(ctx: three_d.Context) {
+  const mesh = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+  mesh.setTransformation(
+    new three_d.Matrix4([1.2, 0, 0, 0, 0, 1.2, 0, 0, 0, 0, 1.2, 0, 0, 0, 0, 1]),
+  );
+  return mesh;
+}
+
+function setup() {
+  const ctx = new three_d.Context();
+  const color = colorTexture(ctx);
+  const depth = new three_d.DepthTexture2D(ctx, SIZE, SIZE);
+  const target = new three_d.RenderTarget(
+    ctx,
+    three_d.ColorTarget.fromTexture(color),
+    three_d.DepthTarget.fromTexture(depth),
+  );
+  target.clear();
+  return { ctx, color, depth, target };
+}
+
+describe("renderWithMaterial", () => {
+  test("Draws geometries with a custom material for ID buffers", () => {
+    const { ctx, target } = setup();
+    const id = new three_d.CustomMaterial(`
+      uniform float objectId;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = vec4(objectId / 255.0, 0.0, 0.0, 1.0); }
+    `);
+    id.setUniform("objectId", [7]);
+    three_d.renderWithMaterial(target, camera(), [square(ctx)], id);
+    const pixels = target.readColor();
+    expect(pixel(pixels, 4, 4)).toEqual([7, 0, 0, 255]);
+    expect(pixel(pixels, 0, 0)).toEqual([0, 0, 0, 0]);
+  });
+
+  test("Draws with built-in materials and lights", () => {
+    const { ctx, target } = setup();
+    const sun = new three_d.DirectionalLight(ctx, 1, new three_d.NSrgba(1, 1, 1, 1), [0, 0, -1]);
+    const material = new three_d.PhysicalMaterial(new three_d.NSrgba(1, 1, 1, 1));
+    three_d.renderWithMaterial(target, camera(), [square(ctx)], material, [sun]);
+    expect(pixel(target.readColor(), 4, 4)[0]).toBeGreaterThan(100);
+  });
+
+  test("Antialiases edges drawn into a multisampled target", () => {
+    const { ctx, target } = setup();
+    const samples = new three_d.ColorTargetMultisample(ctx, SIZE, SIZE, 4);
+    const multisample = new three_d.RenderTargetMultisample(
+      ctx,
+      samples,
+      new three_d.DepthTargetMultisample(ctx, SIZE, SIZE, samples.samples),
+    );
+    multisample.clear();
+    // The square turned by 30 degrees, so its edges cross pixels diagonally.
+    const mesh = square(ctx);
+    const [c, s] = [1.2 * Math.cos(Math.PI / 6), 1.2 * Math.sin(Math.PI / 6)];
+    mesh.setTransformation(new three_d.Matrix4([c, s, 0, 0, -s, c, 0, 0, 0, 0, 1.2, 0, 0, 0, 0, 1]));
+    const white = new three_d.CustomMaterial(`
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = vec4(1.0); }
+    `);
+    three_d.renderWithMaterial(multisample, camera(), [mesh], white);
+    multisample.resolve(target);
+    const reds = Array.from(target.readColor()).filter((_, i) => i % 4 === 0);
+    expect(reds).toContain(255);
+    expect(reds).toContain(0);
+    if (samples.samples > 1) {
+      expect(reds.some((red) => red > 0 && red < 255)).toBe(true);
+    }
+  });
+
+  test("Reports shader errors", () => {
+    const { ctx, target } = setup();
+    const broken = new three_d.CustomMaterial("void main() { nope; }");
+    expect(() => three_d.renderWithMaterial(target, camera(), [square(ctx)], broken)).toThrow(
+      "Invalid material shader",
+    );
+  });
+});
+
+describe("renderWithEffect", () => {
+  test("Samples the color input over geometries", () => {
+    const { ctx, target } = setup();
+    const input = colorTexture(ctx);
+    new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(input)).clear({
+      red: 0,
+      green: 1,
+      blue: 0,
+      alpha: 1,
+    });
+    const effect = new three_d.ScreenEffect(`
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = sample_color(gl_FragCoord.xy / 8.0); }
+    `);
+    three_d.renderWithEffect(target, camera(), [square(ctx)], effect, input);
+    const pixels = target.readColor();
+    expect(pixel(pixels, 4, 4)).toEqual([0, 255, 0, 255]);
+    expect(pixel(pixels, 0, 0)).toEqual([0, 0, 0, 0]);
+  });
+
+  test("Reports shader errors", () => {
+    const { ctx, target } = setup();
+    const effect = new three_d.ScreenEffect(`
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = sample_color(vec2(0.0)); }
+    `);
+    // Without a color input there is no sample_color function.
+    expect(() => three_d.renderWithEffect(target, camera(), [square(ctx)], effect)).toThrow(
+      "Invalid effect shader",
+    );
+  });
+});
+
+describe("RenderTarget.applyScreenEffect", () => {
+  test("Composites color and depth inputs", () => {
+    const { ctx, target } = setup();
+    const color = colorTexture(ctx);
+    const depth = new three_d.DepthTexture2D(ctx, SIZE, SIZE);
+    new three_d.RenderTarget(
+      ctx,
+      three_d.ColorTarget.fromTexture(color),
+      three_d.DepthTarget.fromTexture(depth),
+    ).clear({ red: 1, green: 0, blue: 0, alpha: 1, depth: 0.25 });
+
+    const effect = new three_d.ScreenEffect(`
+      uniform float blue;
+      in vec2 uvs;
+      layout (location = 0) out vec4 outColor;
+      void main() {
+        outColor = vec4(sample_color(uvs).r, sample_depth(uvs), blue, 1.0);
+      }
+    `);
+    effect.setUniform("blue", [0.5]);
+    target.applyScreenEffect(effect, color, depth);
+    expect(pixel(target.readColor(), 3, 3)).toEqual([255, 64, 128, 255]);
+    // The depth of the target is kept.
+    expect(target.readDepth()[0]).toBeCloseTo(1, 6);
+
+    // The same effect without inputs does not compile.
+    expect(() => target.applyScreenEffect(effect)).toThrow("Invalid effect shader");
+  });
+
+  test("Runs over multisampled targets", () => {
+    const { ctx, target } = setup();
+    const input = colorTexture(ctx);
+    new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(input)).clear({
+      red: 0,
+      green: 1,
+      blue: 0,
+      alpha: 1,
+    });
+    const multisample = new three_d.RenderTargetMultisample(
+      ctx,
+      new three_d.ColorTargetMultisample(ctx, SIZE, SIZE, 4),
+    );
+    const effect = new three_d.ScreenEffect(`
+      in vec2 uvs;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = sample_color(uvs); }
+    `);
+    multisample.applyScreenEffect(effect, input);
+    multisample.resolve(target);
+    expect(pixel(target.readColor(), 3, 3)).toEqual([0, 255, 0, 255]);
+  });
+
+  test("Rejects inputs attached to the target", () => {
+    const { ctx, target, color, depth } = setup();
+    const effect = new three_d.ScreenEffect(`
+      in vec2 uvs;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = sample_color(uvs); }
+    `);
+    expect(() => target.applyScreenEffect(effect, color)).toThrow(
+      "The color texture is attached to the render target",
+    );
+    expect(() => target.applyScreenEffect(effect, colorTexture(ctx), depth)).toThrow(
+      "The depth texture is attached to the render target",
+    );
+    expect(() => three_d.renderWithEffect(target, camera(), [square(ctx)], effect, color)).toThrow(
+      "The color texture is attached to the render target",
+    );
+  });
+});
@@ -0,0 +1,184 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+const gradient = `
+  in vec2 uvs;
+  layout (location = 0) out vec4 outColor;
+  void main() { outColor = vec4(uvs.y, 0.0, 0.0, 1.0); }
+`;
+
+function colorTexture(ctx: three_d.Context, width: number, height: number): three_d.Texture2D {
+  return three_d.Texture2D.fromData(
+    ctx,
+    width,
+    height,
+    three_d.TextureFormat.R8G8B8A8,
+    new Uint8Array(width * height * 4),
+    { mipmap: false },
+  );
+}
+
+describe("RenderTarget", () => {
+  test("Clears color and depth and reads them back", () => {
+    const ctx = new three_d.Context();
+    const color = colorTexture(ctx, 4, 4);
+    const depth = new three_d.DepthTexture2D(ctx, 4, 4);
+    const target = new three_d.RenderTarget(
+      ctx,
+      three_d.ColorTarget.fromTexture(color),
+      three_d.DepthTarget.fromTexture(depth),
+    );
+    expect(target.width).toBe(4);
+    expect(target.attachments.length).toBe(2);
+
+    target.clear({ red: 1, green: 0.5, blue: 0, alpha: 1, depth: 0.5 });
+    expect(Array.from(target.readColor().slice(0, 4))).toEqual([255, 128, 0, 255]);
+    expect(target.readDepth()[0]).toBeCloseTo(0.5, 6);
+    expect(depth.readDepth()[15]).toBeCloseTo(0.5, 6);
+
+    // Channels left out keep their content.
+    target.clear({ depth: 1 });
+    expect(target.readColor()[0]).toBe(255);
+    expect(target.readDepth()[0]).toBe(1);
+  });
+
+  test("Renders a material into a texture, top row first", () => {
+    const ctx = new three_d.Context();
+    const color = colorTexture(ctx, 2, 8);
+    const target = new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(color));
+    target.applyScreenMaterial(new three_d.CustomMaterial(gradient));
+    const pixels = target.readColor();
+    expect(pixels[0]).toBeGreaterThan(200);
+    expect(pixels[pixels.length - 4]).toBeLessThan(50);
+
+    // The rendered texture is usable as a material input.
+    const sampled = colorTexture(ctx, 2, 8);
+    const copy = new three_d.CustomMaterial(`
+      uniform sampler2D source;
+      in vec2 uvs;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = texture(source, uvs); }
+    `);
+    copy.setTexture("source", color);
+    const second = new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(sampled));
+    second.applyScreenMaterial(copy);
+    expect(Array.from(second.readColor())).toEqual(Array.from(pixels));
+  });
+
+  test("Texture array layers bound to separate color attachments", () => {
+    const ctx = new three_d.Context();
+    const layers = [0, 1, 2].map(
+      () => new three_d.CpuTexture(2, 2, three_d.TextureFormat.R32F, new Float32Array(4), { mipmap: false }),
+    );
+    const array = new three_d.Texture2DArray(ctx, layers);
+    const color = three_d.ColorTarget.fromTextureArray(array, [2, 0]);
+    expect(color.count).toBe(2);
+    const target = new three_d.RenderTarget(ctx, color);
+    target.applyScreenMaterial(
+      new three_d.CustomMaterial(`
+        layout (location = 0) out float first;
+        layout (location = 1) out float second;
+        void main() { first = 0.25; second = 0.75; }
+      `),
+    );
+    expect(target.readColor(0)[0]).toBe(0.25);
+    expect(target.readColor(1)[0]).toBe(0.75);
+    expect(() => target.readColor(2)).toThrow(/out of range/);
+    expect(() => three_d.ColorTarget.fromTextureArray(array, [3])).toThrow();
+  });
+
+  test("Depth-only targets and validation", () => {
+    const ctx = new three_d.Context();
+    const shadows = new three_d.DepthTextureCubeMap(ctx, 4, three_d.TextureFormat.Depth24);
+    const target = new three_d.RenderTarget(
+      ctx,
+      null,
+      three_d.DepthTarget.fromCubeMap(shadows, three_d.CubeMapSide.PositiveZ),
+    );
+    target.clear({ depth: 0.25 });
+    expect(shadows.readDepth(three_d.CubeMapSide.PositiveZ)[0]).toBeCloseTo(0.25, 5);
+    expect(() => target.readColor()).toThrow(/no color target/);
+
+    expect(() => new three_d.RenderTarget(ctx)).toThrow();
+    expect(
+      () =>
+        new three_d.RenderTarget(
+          ctx,
+          three_d.ColorTarget.fromTexture(colorTexture(ctx, 4, 4)),
+          three_d.DepthTarget.fromTexture(new three_d.DepthTexture2D(ctx, 2, 2)),
+        ),
+    ).toThrow(/4x4/);
+    const broken = new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(colorTexture(ctx, 2, 2)));
+    expect(() => broken.applyScreenMaterial(new three_d.CustomMaterial("void main() { nope; }"))).toThrow(
+      /Invalid material shader/,
+    );
+    expect(ctx.isValid()).toBe(true);
+  });
+});
+
+describe("RenderTargetMultisample", () => {
+  test("Clamps the sample count", () => {
+    const ctx = new three_d.Context();
+    const color = new three_d.ColorTargetMultisample(ctx, 4, 4, 4096);
+    expect(color.samples).toBeGreaterThanOrEqual(1);
+    expect(color.samples).toBeLessThan(4096);
+    expect(new three_d.DepthTargetMultisample(ctx, 4, 4, 0).samples).toBe(1);
+    expect(() => new three_d.DepthTargetMultisample(ctx, 4, 4, 4, three_d.TextureFormat.R8)).toThrow(/depth format/);
+  });
+
+  test("Resolves averaged samples into textures", () => {
+    const ctx = new three_d.Context();
+    const color = new three_d.ColorTargetMultisample(ctx, 4, 4, 4);
+    const multisample = new three_d.RenderTargetMultisample(
+      ctx,
+      color,
+      new three_d.DepthTargetMultisample(ctx, 4, 4, color.samples),
+    );
+    const texture = colorTexture(ctx, 4, 4);
+    const depth = new three_d.DepthTexture2D(ctx, 4, 4);
+    const target = new three_d.RenderTarget(
+      ctx,
+      three_d.ColorTarget.fromTexture(texture),
+      three_d.DepthTarget.fromTexture(depth),
+    );
+
+    multisample.clear({ red: 0, green: 0, blue: 1, alpha: 1, depth: 1 });
+    multisample.resolve(target);
+    expect(Array.from(target.readColor().slice(0, 4))).toEqual([0, 0, 255, 255]);
+    expect(depth.readDepth()[0]).toBe(1);
+
+    if (multisample.samples === 4) {
+      // Shading every sample, half of the samples lie right of the pixel center.
+      multisample.applyScreenMaterial(
+        new three_d.CustomMaterial(`#extension GL_ARB_sample_shading : require
+          layout (location = 0) out vec4 outColor;
+          void main() { outColor = vec4(gl_SamplePosition.x > 0.5 ? 1.0 : 0.0, 0.0, 0.0, 1.0); }
+        `),
+      );
+      multisample.resolve(target);
+      const red = target.readColor()[0];
+      expect(red).toBeGreaterThan(120);
+      expect(red).toBeLessThan(136);
+    }
+  });
+
+  test("Rejects mismatched resolves", () => {
+    const ctx = new three_d.Context();
+    const multisample = new three_d.RenderTargetMultisample(ctx, new three_d.ColorTargetMultisample(ctx, 4, 4, 4));
+    const small = new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(colorTexture(ctx, 2, 2)));
+    expect(() => multisample.resolve(small)).toThrow(/4x4/);
+    const float = new three_d.RenderTarget(
+      ctx,
+      three_d.ColorTarget.fromTexture(
+        three_d.Texture2D.fromData(ctx, 4, 4, three_d.TextureFormat.R32F, new Float32Array(16)),
+      ),
+    );
+    expect(() => multisample.resolve(float)).toThrow(/R32F/);
+    const depthOnly = new three_d.RenderTarget(
+      ctx,
+      null,
+      three_d.DepthTarget.fromTexture(new three_d.DepthTexture2D(ctx, 4, 4)),
+    );
+    expect(() => multisample.resolve(depthOnly)).toThrow(/in common/);
+  });
+});
@@ -0,0 +1,825 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+import { readFileSync } from "fs";
+
+const WIDTH = 32;
+const HEIGHT = 16;
+
+function pixel(pixels: Uint8Array, x: number, y: number) {
+  const i = (y * WIDTH + x) * 4;
+  return Array.from(pixels.slice(i, i + 4));
+}
+
+const camera = () => new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+
+function setup() {
+  const renderer = new three_d.Renderer(WIDTH, HEIGHT, "Test");
+  renderer.init();
+  return renderer;
+}
+
+// A quad covering the upper half of the view at z = 0.
+function upperQuad(ctx: three_d.Context, material: three_d.ColorMaterial) {
+  const positions = [-100, 0, 0, 100, 0, 0, 100, 100, 0, -100, 100, 0];
+  const mesh = new three_d.CpuMesh(positions, [0, 1, 2, 0, 2, 3]);
+  return new three_d.Gm(new three_d.Mesh(ctx, mesh), material);
+}
+
+describe("CpuMesh", () => {
+  test("Builds meshes from arrays and shapes", () => {
+    const mesh = new three_d.CpuMesh([0, 0, 0, 1, 0, 0, 0, 1, 0], undefined, undefined, [
+      0, 0, 1, 0, 0, 1,
+    ]);
+    expect(mesh.vertexCount).toBe(3);
+    expect(mesh.triangleCount).toBe(1);
+    expect(mesh.aabb().max()).toEqual([1, 1, 0]);
+
+    const cube = three_d.CpuMesh.cube();
+    expect(cube.triangleCount).toBe(12);
+    cube.transform(new three_d.Matrix4([2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 2, 0, 0, 0, 0, 1]));
+    expect(cube.aabb().max()).toEqual([2, 2, 2]);
+  });
+
+  test("Rejects malformed data", () => {
+    expect(() => new three_d.CpuMesh([0, 0])).toThrow("3 values per vertex");
+    expect(() => new three_d.CpuMesh([0, 0, 0, 1, 0, 0])).toThrow("multiple of 3 vertices");
+    expect(() => new three_d.CpuMesh([0, 0, 0, 1, 0, 0, 0, 1, 0], [0, 1, 3])).toThrow(
+      "Invalid mesh",
+    );
+    expect(() => new three_d.CpuMesh([0, 0, 0, 1, 0, 0, 0, 1, 0], undefined, [0, 0, 1])).toThrow(
+      "normal",
+    );
+  });
+});
+
+describe("Renderer", () => {
+  test("Describes itself", () => {
+    const renderer = new three_d.Renderer(WIDTH, HEIGHT, "Thumbnails");
+    expect(renderer.getInfo()).toContain("not initialized");
+    expect(() => renderer.readPixels()).toThrow("init()");
+    renderer.init();
+    expect(renderer.getInfo()).toContain("Thumbnails");
+    expect(renderer.getInfo()).toContain("Context");
+    expect([renderer.width, renderer.height]).toEqual([WIDTH, HEIGHT]);
+    expect(() => new three_d.Renderer(0, 10)).toThrow("positive");
+  });
+
+  test("Clears to the clear color", () => {
+    const renderer = setup();
+    renderer.setClearColor(new three_d.NSrgba(0, 0, 1, 1));
+    renderer.render([], camera());
+    const pixels = renderer.readPixels();
+    expect(pixels.length).toBe(WIDTH * HEIGHT * 4);
+    expect(pixel(pixels, 0, 0)).toEqual([0, 0, 255, 255]);
+  });
+
+  test("Draws objects with the top row first", () => {
+    const renderer = setup();
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1));
+    renderer.render([upperQuad(renderer.context, red)], camera());
+    const pixels = renderer.readPixels();
+    expect(pixel(pixels, 16, 0)).toEqual([255, 0, 0, 255]);
+    expect(pixel(pixels, 16, HEIGHT - 1)).toEqual([0, 0, 0, 255]);
+
+    const floats = new Float32Array(renderer.readPixels(three_d.DataType.Float).buffer);
+    expect(floats.length).toBe(WIDTH * HEIGHT * 4);
+    expect(floats[0]).toBeCloseTo(1, 2);
+    expect(() => renderer.readPixels(three_d.DataType.Short)).toThrow("UnsignedByte or Float");
+  });
+
+  test("Draws the nearest object in front", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const near = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    near.setTransformation(new three_d.Matrix4([1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1]));
+    const far = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    const green = new three_d.ColorMaterial(new three_d.NSrgba(0, 1, 0, 1));
+    const blue = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 1));
+    renderer.render([new three_d.Gm(far, blue), new three_d.Gm(near, green)], camera());
+    expect(pixel(renderer.readPixels(), 16, 8)).toEqual([0, 255, 0, 255]);
+  });
+
+  test("Lights physical materials", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const sphere = new three_d.Mesh(ctx, three_d.CpuMesh.sphere(16));
+    const white = new three_d.PhysicalMaterial(new three_d.NSrgba(1, 1, 1, 1));
+    const gm = new three_d.Gm(sphere, white);
+    renderer.render([gm], camera());
+    const dark = pixel(renderer.readPixels(), 16, 8);
+
+    const sun = new three_d.DirectionalLight(ctx, 2, new three_d.NSrgba(1, 1, 1, 1), [0, 0, -1]);
+    const ambient = new three_d.AmbientLight(ctx, 0.2, new three_d.NSrgba(1, 1, 1, 1));
+    renderer.render([gm], camera(), [sun, ambient]);
+    const lit = pixel(renderer.readPixels(), 16, 8);
+    expect(lit[0]).toBeGreaterThan(dark[0] + 50);
+
+    expect(() => new three_d.DirectionalLight(ctx, 1, new three_d.NSrgba(1, 1, 1, 1), [0, 0, 0])).toThrow(
+      "non-zero",
+    );
+    expect(() => new three_d.PhysicalMaterial(new three_d.NSrgba(1, 1, 1, 1), 2)).toThrow(
+      "Metallic",
+    );
+  });
+
+  test("Renders with frustum culling disabled", () => {
+    const renderer = setup();
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1));
+    const gm = upperQuad(renderer.context, red);
+    renderer.frustumCulling = false;
+    renderer.render([gm], camera());
+    expect(pixel(renderer.readPixels(), 16, 0)).toEqual([255, 0, 0, 255]);
+  });
+
+  test("Reports custom material shader errors", () => {
+    const renderer = setup();
+    const mesh = new three_d.Mesh(renderer.context, three_d.CpuMesh.square());
+    const broken = new three_d.CustomMaterial("void main() { nope; }");
+    expect(() => renderer.render([new three_d.Gm(mesh, broken)], camera())).toThrow(
+      "Invalid material shader",
+    );
+  });
+
+  test("Saves a PNG screenshot", () => {
+    const renderer = setup();
+    renderer.render([], camera());
+    const path = "/tmp/three_d_renderer_test.png";
+    renderer.saveScreenshot(path);
+    const bytes = readFileSync(path);
+    expect(Array.from(bytes.slice(1, 4))).toEqual([80, 78, 71]);
+    expect(() => renderer.saveScreenshot("/nonexistent/dir/shot.png")).toThrow(
+      "Failed to write screenshot",
+    );
+  });
+});
+
+describe("Multisampling", () => {
+  // A white square turned by 30 degrees, so its edges cross pixels diagonally.
+  const turned = (ctx: three_d.Context) => {
+    const mesh = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    const [c, s] = [Math.cos(Math.PI / 6), Math.sin(Math.PI / 6)];
+    mesh.setTransformation(new three_d.Matrix4([c, s, 0, 0, -s, c, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]));
+    return new three_d.Gm(mesh, new three_d.ColorMaterial(new three_d.NSrgba(1, 1, 1, 1)));
+  };
+  const reds = (renderer: three_d.Renderer) =>
+    Array.from(renderer.readPixels()).filter((_, i) => i % 4 === 0);
+
+  test("Antialiases edges with more than one sample", () => {
+    const renderer = setup();
+    const square = turned(renderer.context);
+    expect(renderer.samples).toBe(1);
+    renderer.render([square], camera());
+    expect(reds(renderer).every((red) => red === 0 || red === 255)).toBe(true);
+
+    renderer.samples = 4;
+    renderer.render([square], camera());
+    expect(reds(renderer)).toContain(255);
+    expect(reds(renderer).some((red) => red > 0 && red < 255)).toBe(true);
+    expect(renderer.lastFrameStats()?.passes.map((pass) => pass.name)).toEqual([
+      "forward",
+      "resolve",
+    ]);
+
+    expect(() => {
+      renderer.samples = 0;
+    }).toThrow("at least 1");
+  });
+
+  test("Resolves only the viewport drawn", () => {
+    const renderer = setup();
+    renderer.samples = 4;
+    const square = turned(renderer.context);
+    const left = new three_d.Viewport(0, 0, 16, 16);
+    const right = new three_d.Viewport(16, 0, 16, 16);
+    const blue = new three_d.NSrgba(0, 0, 1, 1);
+    renderer.render([square], camera(), [], { viewport: left });
+    renderer.render([], camera(), [], { viewport: right, background: blue });
+    let pixels = renderer.readPixels();
+    expect(pixel(pixels, 8, 8)).toEqual([255, 255, 255, 255]);
+    expect(pixel(pixels, 24, 8)).toEqual([0, 0, 255, 255]);
+
+    // Without clearing, the view is drawn over a copy of the frame.
+    renderer.render([square], camera(), [], { viewport: right, clear: false });
+    pixels = renderer.readPixels();
+    expect(pixel(pixels, 24, 8)).toEqual([255, 255, 255, 255]);
+    expect(pixel(pixels, 31, 0)).toEqual([0, 0, 255, 255]);
+    expect(pixel(pixels, 8, 8)).toEqual([255, 255, 255, 255]);
+  });
+});
+
+describe("Transparency", () => {
+  // A square at depth `z` covering the whole view.
+  const panel = (renderer: three_d.Renderer, z: number, material: three_d.ColorMaterial) => {
+    const mesh = new three_d.Mesh(renderer.context, three_d.CpuMesh.square());
+    mesh.setTransformation(new three_d.Matrix4([5, 0, 0, 0, 0, 5, 0, 0, 0, 0, 1, 0, 0, 0, z, 1]));
+    return new three_d.Gm(mesh, material);
+  };
+
+  test("Defaults to alpha blending for translucent colors", () => {
+    const material = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 0.5));
+    expect(material.transparency).toBe(three_d.Transparency.Alpha);
+    material.setColor(new three_d.NSrgba(1, 0, 0, 1));
+    expect(material.transparency).toBe(three_d.Transparency.Opaque);
+    material.transparency = three_d.Transparency.Additive;
+    material.setColor(new three_d.NSrgba(1, 0, 0, 0.5));
+    expect(material.transparency).toBe(three_d.Transparency.Additive);
+
+    const physical = new three_d.PhysicalMaterial(new three_d.NSrgba(1, 1, 1, 0.2));
+    expect(physical.transparency).toBe(three_d.Transparency.Alpha);
+    const custom = new three_d.CustomMaterial("void main() {}");
+    expect(custom.transparency).toBe(three_d.Transparency.Opaque);
+  });
+
+  test("Blends overlapping panels back to front in any order", () => {
+    const renderer = setup();
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 0.5));
+    const blue = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 0.5));
+    const near = panel(renderer, 1, red);
+    const far = panel(renderer, 0, blue);
+    renderer.render([near, far], camera());
+    const first = pixel(renderer.readPixels(), 16, 8);
+    renderer.render([far, near], camera());
+    expect(pixel(renderer.readPixels(), 16, 8)).toEqual(first);
+    expect(first[0]).toBeGreaterThan(first[2] + 50);
+    expect(first[2]).toBeGreaterThan(0);
+  });
+
+  test("Adds and multiplies colors", () => {
+    const renderer = setup();
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1));
+    const green = new three_d.ColorMaterial(new three_d.NSrgba(0, 1, 0, 1));
+    red.transparency = three_d.Transparency.Additive;
+    green.transparency = three_d.Transparency.Additive;
+    renderer.render([panel(renderer, 0, red), panel(renderer, 1, green)], camera());
+    expect(pixel(renderer.readPixels(), 16, 8).slice(0, 3)).toEqual([255, 255, 0]);
+
+    renderer.setClearColor(new three_d.NSrgba(1, 1, 0, 1));
+    const cyan = new three_d.ColorMaterial(new three_d.NSrgba(0, 1, 1, 1));
+    cyan.transparency = three_d.Transparency.Multiply;
+    renderer.render([panel(renderer, 0, cyan)], camera());
+    expect(pixel(renderer.readPixels(), 16, 8)).toEqual([0, 255, 0, 255]);
+  });
+
+  test("Blends intersecting panels independently of order", () => {
+    const renderer = setup();
+    renderer.orderIndependentTransparency = true;
+    expect(renderer.orderIndependentTransparency).toBe(true);
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 0.5));
+    const blue = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 0.5));
+    // Both panels at the same depth, where sorting cannot decide.
+    const a = panel(renderer, 0, red);
+    const b = panel(renderer, 0, blue);
+    renderer.render([a, b], camera());
+    const first = pixel(renderer.readPixels(), 16, 8);
+    renderer.render([b, a], camera());
+    expect(pixel(renderer.readPixels(), 16, 8)).toEqual(first);
+    expect(Math.abs(first[0] - first[2])).toBeLessThan(2);
+    expect(first[0]).toBeGreaterThan(50);
+
+    renderer.render([panel(renderer, 0, red)], camera());
+    const single = pixel(renderer.readPixels(), 16, 8);
+    expect(single[0]).toBeGreaterThan(120);
+    expect(single[0]).toBeLessThan(136);
+  });
+
+  test("Hides order-independent layers behind opaque objects", () => {
+    const renderer = setup();
+    renderer.orderIndependentTransparency = true;
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 0.5));
+    const green = new three_d.ColorMaterial(new three_d.NSrgba(0, 1, 0, 1));
+    renderer.render([panel(renderer, 0, red), panel(renderer, 1, green)], camera());
+    expect(pixel(renderer.readPixels(), 16, 8)).toEqual([0, 255, 0, 255]);
+  });
+
+  test("Draws translucent colors opaque when asked", () => {
+    const renderer = setup();
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 0.5));
+    red.transparency = three_d.Transparency.Opaque;
+    const blue = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 1));
+    renderer.render([panel(renderer, 0, blue), panel(renderer, 1, red)], camera());
+    expect(pixel(renderer.readPixels(), 16, 8).slice(0, 3)).toEqual([255, 0, 0]);
+  });
+});
+
+describe("Deferred shading", () => {
+  const floats = (buffer: Uint8Array) =>
+    new Float32Array(buffer.buffer, buffer.byteOffset, buffer.length / 4);
+  const floatPixel = (values: Float32Array, x: number, y: number) =>
+    Array.from(values.slice((y * WIDTH + x) * 4, (y * WIDTH + x) * 4 + 4));
+
+  const sun = (ctx: three_d.Context) =>
+    new three_d.DirectionalLight(ctx, 2, new three_d.NSrgba(1, 1, 1, 1), [0, 0, -1]);
+
+  test("Lights deferred objects", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const sphere = new three_d.Mesh(ctx, three_d.CpuMesh.sphere(32));
+    const deferred = new three_d.DeferredPhysicalMaterial(new three_d.NSrgba(0.8, 0.4, 0.2, 1));
+    deferred.roughness = 0.6;
+    expect(deferred.roughness).toBeCloseTo(0.6, 5);
+    expect(() => {
+      deferred.metallic = 2;
+    }).toThrow("Metallic");
+    const gm = new three_d.Gm(sphere, deferred);
+    renderer.render([gm], camera());
+    const dark = pixel(renderer.readPixels(), 16, 8);
+    renderer.render([gm], camera(), [sun(ctx)]);
+    const lit = pixel(renderer.readPixels(), 16, 8);
+    expect(lit[0]).toBeGreaterThan(dark[0] + 100);
+    expect(lit[0]).toBeGreaterThan(lit[2]);
+
+    // Emissive colors need no lighting and match forward shading, up to the
+    // 8-bit precision of the G-buffer.
+    const glow = new three_d.PhysicalMaterial(
+      new three_d.NSrgba(0, 0, 0, 1),
+      0,
+      1,
+      new three_d.NSrgba(0.2, 0.6, 0.4, 1),
+    );
+    renderer.render([new three_d.Gm(sphere, glow)], camera());
+    const forward = pixel(renderer.readPixels(), 16, 8);
+    const fromForward = three_d.DeferredPhysicalMaterial.fromPhysicalMaterial(glow);
+    renderer.render([new three_d.Gm(sphere, fromForward)], camera());
+    const actual = pixel(renderer.readPixels(), 16, 8);
+    actual.forEach((v, i) => expect(Math.abs(v - forward[i])).toBeLessThan(4));
+  });
+
+  test("Lights with many lights", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const sphere = new three_d.Mesh(ctx, three_d.CpuMesh.sphere(16));
+    const material = new three_d.DeferredPhysicalMaterial(new three_d.NSrgba(1, 1, 1, 1));
+    const lights = [];
+    for (let i = 0; i < 64; i++) {
+      const angle = (i / 64) * Math.PI * 2;
+      lights.push(
+        new three_d.PointLight(ctx, 0.05, new three_d.NSrgba(1, 1, 1, 1), [
+          3 * Math.cos(angle),
+          3 * Math.sin(angle),
+          3,
+        ]),
+      );
+    }
+    renderer.render([new three_d.Gm(sphere, material)], camera(), lights);
+    expect(pixel(renderer.readPixels(), 16, 8)[0]).toBeGreaterThan(20);
+  });
+
+  test("Reads the G-buffer", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    expect(() => renderer.readGBuffer(three_d.GBufferTexture.Albedo)).toThrow("no deferred");
+    const square = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    const material = new three_d.DeferredPhysicalMaterial(
+      new three_d.NSrgba(1, 0, 0, 1),
+      0.25,
+      0.75,
+    );
+    renderer.render([new three_d.Gm(square, material)], camera(), [sun(ctx)]);
+
+    const albedo = floats(renderer.readGBuffer(three_d.GBufferTexture.Albedo));
+    expect(albedo.length).toBe(WIDTH * HEIGHT * 4);
+    floatPixel(albedo, 16, 8).forEach((v, i) => expect(v).toBeCloseTo([1, 0, 0, 1][i], 2));
+    expect(floatPixel(albedo, 0, 0)).toEqual([0, 0, 0, 0]);
+
+    const normal = floats(renderer.readGBuffer(three_d.GBufferTexture.Normal));
+    floatPixel(normal, 16, 8).forEach((v, i) => expect(v).toBeCloseTo([0, 0, 1, 0][i], 1));
+
+    const orm = floats(renderer.readGBuffer(three_d.GBufferTexture.ORM));
+    floatPixel(orm, 16, 8).forEach((v, i) => expect(v).toBeCloseTo([1, 0.75, 0.25, 1][i], 1));
+
+    const depth = floats(renderer.readGBuffer(three_d.GBufferTexture.Depth));
+    expect(floatPixel(depth, 16, 8)[0]).toBeLessThan(1);
+    expect(floatPixel(depth, 0, 0)[0]).toBeCloseTo(1, 5);
+
+    // A frame without deferred objects has no G-buffer.
+    renderer.render([], camera());
+    expect(() => renderer.readGBuffer(three_d.GBufferTexture.Depth)).toThrow("no deferred");
+  });
+
+  test("Draws forward and transparent objects over deferred ones", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const far = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    far.setTransformation(new three_d.Matrix4([5, 0, 0, 0, 0, 5, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]));
+    const near = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    near.setTransformation(new three_d.Matrix4([1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1]));
+    const hidden = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    hidden.setTransformation(new three_d.Matrix4([1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 3, 0, -1, 1]));
+
+    const white = new three_d.DeferredPhysicalMaterial(
+      new three_d.NSrgba(1, 1, 1, 1),
+      0,
+      1,
+      new three_d.NSrgba(1, 1, 1, 1),
+    );
+    const glass = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 0.5));
+    const blue = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 1));
+    const cam = camera();
+    cam.toneMapping = three_d.ToneMapping.None;
+    renderer.render(
+      [new three_d.Gm(far, white), new three_d.Gm(near, glass), new three_d.Gm(hidden, blue)],
+      cam,
+    );
+    const pixels = renderer.readPixels();
+    const center = pixel(pixels, 16, 8);
+    expect(center[0]).toBe(255);
+    expect(center[1]).toBeGreaterThan(100);
+    expect(center[1]).toBeLessThan(220);
+    // The forward object behind the deferred one is hidden by its depth.
+    expect(pixel(pixels, 25, 8)).toEqual([255, 255, 255, 255]);
+
+    const texture = three_d.Texture2D.fromData(
+      ctx,
+      1,
+      1,
+      three_d.TextureFormat.R8G8B8A8,
+      new Uint8Array(4),
+      { mipmap: false },
+    );
+    const target = new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(texture));
+    expect(() => three_d.renderWithMaterial(target, camera(), [far], white)).toThrow(
+      "only be drawn by Renderer.render",
+    );
+  });
+});
+
+describe("Cube maps", () => {
+  // One color per side, in CubeMapSide order.
+  const COLORS = [
+    [255, 0, 0],
+    [0, 255, 0],
+    [0, 0, 255],
+    [255, 255, 0],
+    [255, 0, 255],
+    [0, 255, 255],
+  ];
+  const cubeMap = (ctx: three_d.Context) =>
+    new three_d.TextureCubeMap(
+      ctx,
+      COLORS.map(
+        (color) =>
+          new three_d.CpuTexture(
+            4,
+            4,
+            three_d.TextureFormat.R8G8B8A8,
+            new Uint8Array(Array.from({ length: 16 }, () => [...color, 255]).flat()),
+          ),
+      ),
+    );
+
+  test("Samples a cube map through a material", () => {
+    const renderer = setup();
+    const material = new three_d.CustomMaterial(`
+      uniform samplerCube environment;
+      uniform vec3 direction;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = texture(environment, direction); }
+    `);
+    material.setTextureCubeMap("environment", cubeMap(renderer.context));
+    const square = new three_d.Gm(new three_d.Mesh(renderer.context, three_d.CpuMesh.square()), material);
+    for (const [direction, side] of [
+      [[1, 0, 0], 0],
+      [[0, -1, 0], 3],
+      [[0, 0, 1], 4],
+    ] as const) {
+      material.setUniform("direction", [...direction]);
+      renderer.render([square], camera());
+      expect(pixel(renderer.readPixels(), 16, 8)).toEqual([...COLORS[side], 255]);
+    }
+  });
+
+  test("Draws a skybox behind the objects", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const skybox = new three_d.Skybox(ctx, cubeMap(ctx));
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1));
+    renderer.render([upperQuad(ctx, red)], camera(), [], { skybox });
+    const pixels = renderer.readPixels();
+    expect(pixel(pixels, 16, 0)).toEqual([255, 0, 0, 255]);
+    // The camera looks down -Z, at the cyan side, tone mapped like other
+    // lit colors.
+    const [r, g, b] = pixel(pixels, 16, HEIGHT - 1);
+    expect(r).toBe(0);
+    expect(g).toBeGreaterThan(200);
+    expect(b).toBe(g);
+    expect(renderer.lastFrameStats()?.drawCalls).toBe(2);
+  });
+});
+
+describe("Picking", () => {
+  const scene = (renderer: three_d.Renderer) => {
+    const ctx = renderer.context;
+    const material = new three_d.ColorMaterial(new three_d.NSrgba(1, 1, 1, 1));
+    const far = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    const near = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    near.setTransformation(
+      new three_d.Matrix4([0.5, 0, 0, 0, 0, 0.5, 0, 0, 0, 0, 0.5, 0, 2, 0, 1, 1]),
+    );
+    return [new three_d.Gm(far, material), new three_d.Gm(near, material)];
+  };
+
+  test("Picks the object under a pixel", () => {
+    const renderer = setup();
+    const objects = scene(renderer);
+    const hit = renderer.pick(camera(), 16, 8, objects);
+    expect(hit).not.toBeNull();
+    if (hit) {
+      expect(hit.objectIndex).toBe(0);
+      expect(hit.instanceId).toBe(0);
+      hit.position.forEach((v, i) => expect(v).toBeCloseTo([0, 0, 0][i], 1));
+    }
+    expect(renderer.pick(camera(), 0, 0, objects)).toBeNull();
+  });
+
+  test("Picks inside a viewport", () => {
+    const renderer = setup();
+    const objects = scene(renderer);
+    // The center of the right half is off-center in the full frame.
+    expect(renderer.pick(camera(), 24, 8, objects)?.objectIndex).toBe(1);
+    const hit = renderer.pick(camera(), 24, 8, objects, new three_d.Viewport(16, 0, 16, 16));
+    expect(hit).not.toBeNull();
+    if (hit) {
+      expect(hit.objectIndex).toBe(0);
+      hit.position.forEach((v, i) => expect(v).toBeCloseTo([0, 0, 0][i], 1));
+    }
+    expect(() => renderer.pick(camera(), 0, 0, objects, new three_d.Viewport(20, 0, 16, 16))).toThrow(
+      "must lie inside the 32x16 frame",
+    );
+  });
+
+  test("Intersects a ray with the closest object", () => {
+    const renderer = setup();
+    const objects = scene(renderer);
+    const hit = renderer.rayIntersect([2, 0, 10], [0, 0, -2], 100, objects);
+    expect(hit).not.toBeNull();
+    if (hit) {
+      expect(hit.objectIndex).toBe(1);
+      hit.position.forEach((v, i) => expect(v).toBeCloseTo([2, 0, 1][i], 1));
+    }
+    expect(renderer.rayIntersect([2, 0, 10], [0, 0, -1], 5, objects)).toBeNull();
+    expect(() => renderer.rayIntersect([0, 0, 0], [0, 0, 0], 1, objects)).toThrow("non-zero");
+    expect(() => renderer.rayIntersect([0, 0, 0], [0, 0, 1], 0, objects)).toThrow("positive");
+  });
+});
+
+describe("Viewports", () => {
+  const left = () => new three_d.Viewport(0, 0, 16, 16);
+  const right = () => new three_d.Viewport(16, 0, 16, 16);
+
+  test("Draws several cameras into one frame", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const sphere = new three_d.Gm(
+      new three_d.Mesh(ctx, three_d.CpuMesh.sphere(16)),
+      new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1)),
+    );
+    const blue = new three_d.NSrgba(0, 0, 1, 1);
+    const green = new three_d.NSrgba(0, 1, 0, 1);
+    renderer.render([sphere], camera(), [], { viewport: left(), background: blue });
+    renderer.render([sphere], camera(), [], { viewport: right(), background: green, layers: 2 });
+    let pixels = renderer.readPixels();
+    expect(pixel(pixels, 8, 8)).toEqual([255, 0, 0, 255]);
+    expect(pixel(pixels, 0, 0)).toEqual([0, 0, 255, 255]);
+    expect(pixel(pixels, 24, 8)).toEqual([0, 255, 0, 255]);
+
+    // Without clearing, the view is drawn over what is there.
+    sphere.layers = 3;
+    renderer.render([sphere], camera(), [], { viewport: right(), clear: false, layers: 2 });
+    pixels = renderer.readPixels();
+    expect(pixel(pixels, 24, 8)).toEqual([255, 0, 0, 255]);
+    expect(pixel(pixels, 31, 0)).toEqual([0, 255, 0, 255]);
+    expect(pixel(pixels, 0, 0)).toEqual([0, 0, 255, 255]);
+
+    expect(() =>
+      renderer.render([sphere], camera(), [], { viewport: new three_d.Viewport(20, 0, 16, 16) }),
+    ).toThrow("must lie inside the 32x16 frame");
+  });
+
+  test("Keeps frame passes inside the viewport", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    const sphere = new three_d.Mesh(ctx, three_d.CpuMesh.sphere(16));
+    const glow = new three_d.PhysicalMaterial(
+      new three_d.NSrgba(0, 0, 0, 1),
+      0,
+      1,
+      new three_d.NSrgba(0.2, 0.6, 0.4, 1),
+    );
+    renderer.render([new three_d.Gm(sphere, glow)], camera(), [], { viewport: left() });
+    const forward = pixel(renderer.readPixels(), 8, 8);
+
+    // The G-buffer covers the viewport and is lit in place.
+    const deferred = three_d.DeferredPhysicalMaterial.fromPhysicalMaterial(glow);
+    const green = new three_d.NSrgba(0, 1, 0, 1);
+    renderer.render([], camera(), [], { viewport: right(), background: green });
+    renderer.render([new three_d.Gm(sphere, deferred)], camera(), [], { viewport: left() });
+    let pixels = renderer.readPixels();
+    pixel(pixels, 8, 8).forEach((v, i) => expect(Math.abs(v - forward[i])).toBeLessThan(4));
+    expect(pixel(pixels, 24, 8)).toEqual([0, 255, 0, 255]);
+    expect(renderer.readGBuffer(three_d.GBufferTexture.Albedo).length).toBe(16 * 16 * 16);
+
+    // Exposure is applied by a pass over the viewport only.
+    const gray = new three_d.Gm(
+      sphere,
+      new three_d.ColorMaterial(new three_d.NSrgba(0.25, 0.25, 0.25, 1)),
+    );
+    renderer.render([gray], camera(), [], { viewport: right() });
+    const plain = pixel(renderer.readPixels(), 24, 8);
+    const bright = camera();
+    bright.exposure = 2;
+    renderer.render([gray], bright, [], { viewport: right() });
+    pixels = renderer.readPixels();
+    expect(pixel(pixels, 24, 8)[0]).toBeGreaterThan(plain[0] + 20);
+    pixel(pixels, 8, 8).forEach((v, i) => expect(Math.abs(v - forward[i])).toBeLessThan(4));
+  });
+});
+
+describe("Frame statistics", () => {
+  function sum(values: number[]) {
+    return values.reduce((total, value) => total + value, 0);
+  }
+
+  test("Counts the work of a frame", () => {
+    const renderer = setup();
+    const ctx = renderer.context;
+    expect(renderer.lastFrameStats()).toBeNull();
+    const sphere = three_d.CpuMesh.sphere(16);
+    const red = new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1));
+    const blue = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 1));
+    const behind = new three_d.Mesh(ctx, sphere);
+    behind.setTransformation(new three_d.Matrix4([1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 10, 1]));
+    const objects = [
+      new three_d.Gm(new three_d.Mesh(ctx, sphere), red),
+      new three_d.Gm(new three_d.Mesh(ctx, sphere), red),
+      new three_d.Gm(behind, red),
+    ];
+    renderer.render(objects, camera());
+    let stats = renderer.lastFrameStats();
+    expect(stats?.drawCalls).toBe(2);
+    expect(stats?.instances).toBe(2);
+    expect(stats?.triangles).toBe(2 * sphere.triangleCount);
+    expect(stats?.vertices).toBe(2 * sphere.vertexCount);
+    expect(stats?.culledObjects).toBe(1);
+    expect(stats?.stateChanges).toBe(1);
+    expect(stats?.textureBinds).toBe(0);
+    expect(stats?.passes.map((pass) => pass.name)).toEqual(["forward"]);
+    expect(stats?.cpuTime).toBeGreaterThanOrEqual(0);
+    expect(stats?.passes[0].cpuTime).toBeLessThanOrEqual(stats?.cpuTime ?? 0);
+    expect(renderer.gpuTiming).toBe(false);
+    expect(stats?.gpuTime).toBeUndefined();
+    expect(stats?.passes[0].gpuTime).toBeUndefined();
+
+    // A textured material is another program with a texture bound. Drawn
+    // first, as it is nearest, followed by both spheres with one program.
+    const texture = three_d.Texture2D.fromData(
+      ctx,
+      1,
+      1,
+      three_d.TextureFormat.R32G32B32A32F,
+      new Float32Array([1, 1, 1, 1]),
+    );
+    const square = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    square.setTransformation(new three_d.Matrix4([1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1]));
+    const textured = new three_d.Gm(
+      square,
+      new three_d.ColorMaterial(new three_d.NSrgba(1, 1, 1, 1), texture),
+    );
+    const other = new three_d.Gm(new three_d.Mesh(ctx, sphere), blue);
+    renderer.render([objects[0], other, textured], camera());
+    stats = renderer.lastFrameStats();
+    expect(stats?.drawCalls).toBe(3);
+    expect(stats?.stateChanges).toBe(2);
+    expect(stats?.textureBinds).toBe(1);
+
+    renderer.gpuTiming = true;
+    renderer.render(objects, camera());
+    stats = renderer.lastFrameStats();
+    expect(stats?.gpuTime).toBeGreaterThan(0);
+    expect(stats?.gpuTime).toBe(stats?.passes[0].gpuTime);
+  });
+
+  test("Reports every pass", () => {
+    const renderer = setup();
+    renderer.orderIndependentTransparency = true;
+    const ctx = renderer.context;
+    const sphere = new three_d.Mesh(ctx, three_d.CpuMesh.sphere(8));
+    const square = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+    const deferred = new three_d.DeferredPhysicalMaterial(new three_d.NSrgba(1, 1, 1, 1));
+    const glass = new three_d.ColorMaterial(new three_d.NSrgba(0, 0, 1, 0.5));
+    const bright = camera();
+    bright.exposure = 2;
+    renderer.render([new three_d.Gm(sphere, deferred), new three_d.Gm(square, glass)], bright);
+    const stats = renderer.lastFrameStats();
+    expect(stats?.passes.map((pass) => pass.name)).toEqual([
+      "deferred geometry",
+      "deferred lighting",
+      "forward",
+      "transparency",
+      "tone mapping",
+    ]);
+    // The transparent square is drawn into both OIT buffers, and the
+    // lighting, composite and tone mapping passes draw one triangle each.
+    expect(stats?.drawCalls).toBe(1 + 1 + 2 + 1 + 1);
+    const passes = stats?.passes ?? [];
+    expect(sum(passes.map((pass) => pass.drawCalls))).toBe(stats?.drawCalls);
+    expect(sum(passes.map((pass) => pass.triangles))).toBe(stats?.triangles);
+    expect(stats?.textureBinds).toBe(2 + 2 + 1);
+  });
+
+  test("Calls the stats callback for every frame", () => {
+    const renderer = setup();
+    const frames: three_d.FrameStats[] = [];
+    renderer.setStatsCallback((stats) => {
+      frames.push(stats);
+    });
+    renderer.render([], camera());
+    renderer.renderScene(new three_d.Scene(), camera());
+    expect(frames.length).toBe(2);
+    expect(frames[1].drawCalls).toBe(0);
+
+    renderer.setStatsCallback(null);
+    renderer.render([], camera());
+    expect(frames.length).toBe(2);
+
+    renderer.setStatsCallback(() => {
+      throw new Error("over budget");
+    });
+    expect(() => renderer.render([], camera())).toThrow("over budget");
+    expect(renderer.lastFrameStats()?.drawCalls).toBe(0);
+  });
+});
+
+// A binary glTF holding one red triangle facing the camera.
+// A red triangle, optionally without a scene or with a base color texture
+// stored in a strided buffer view.
+function glb(scenes = true, stridedImage = false): Buffer {
+  const bin = Buffer.from(
+    new Float32Array([-2, -2, 0, 2, -2, 0, 0, 2, 0, 0, 0, 1, 0, 0, 1, 0, 0, 1]).buffer,
+  );
+  const gltf: Record<string, unknown> = {
+    asset: { version: "2.0" },
+    nodes: [{ mesh: 0 }],
+    meshes: [{ primitives: [{ attributes: { POSITION: 0, NORMAL: 1 }, material: 0 }] }],
+    materials: [{ pbrMetallicRoughness: { baseColorFactor: [1, 0, 0, 1], metallicFactor: 0 } }],
+    buffers: [{ byteLength: bin.length }],
+    bufferViews: [{ buffer: 0, byteLength: bin.length }],
+    accessors: [
+      { bufferView: 0, componentType: 5126, count: 3, type: "VEC3", min: [-2, -2, 0], max: [2, 2, 0] },
+      { bufferView: 0, byteOffset: 36, componentType: 5126, count: 3, type: "VEC3" },
+    ],
+  };
+  if (scenes) {
+    gltf.scene = 0;
+    gltf.scenes = [{ nodes: [0] }];
+  }
+  if (stridedImage) {
+    gltf.bufferViews = [
+      { buffer: 0, byteLength: bin.length },
+      { buffer: 0, byteLength: 36, byteStride: 12 },
+    ];
+    gltf.images = [{ bufferView: 1, mimeType: "image/png" }];
+    gltf.textures = [{ source: 0 }];
+    gltf.materials = [{ pbrMetallicRoughness: { baseColorTexture: { index: 0 } } }];
+  }
+  let json = Buffer.from(JSON.stringify(gltf));
+  json = Buffer.concat([json, Buffer.alloc((4 - (json.length % 4)) % 4, " ")]);
+  const chunk = (type: number, data: Buffer) => {
+    const header = Buffer.alloc(8);
+    header.writeUInt32LE(data.length, 0);
+    header.writeUInt32LE(type, 4);
+    return Buffer.concat([header, data]);
+  };
+  const body = Buffer.concat([chunk(0x4e4f534a, json), chunk(0x004e4942, bin)]);
+  const header = Buffer.alloc(12);
+  header.write("glTF", 0);
+  header.writeUInt32LE(2, 4);
+  header.writeUInt32LE(12 + body.length, 8);
+  return Buffer.concat([header, body]);
+}
+
+describe("loadModelAsync", () => {
+  test("Loads binary glTF models off the main thread", async () => {
+    const cpu = await three_d.loadModelAsync(glb());
+    expect(cpu.meshCount).toBe(1);
+    expect(cpu.materialCount).toBe(1);
+    expect(three_d.loadModel(glb()).meshCount).toBe(1);
+
+    const renderer = setup();
+    const ctx = renderer.context;
+    const objects = new three_d.Model(ctx, cpu).objects();
+    expect(objects.length).toBe(1);
+    const ambient = new three_d.AmbientLight(ctx, 1, new three_d.NSrgba(1, 1, 1, 1));
+    renderer.render(objects, camera(), [ambient]);
+    const [r, g, b] = pixel(renderer.readPixels(), 16, 8);
+    expect(r).toBeGreaterThan(100);
+    expect(g).toBe(0);
+    expect(b).toBe(0);
+  });
+
+  test("Rejects models it cannot load", async () => {
+    await expect(three_d.loadModelAsync(glb(false))).rejects.toThrow("no scene");
+    expect(() => three_d.loadModel(glb(true, true))).toThrow("strided buffer views");
+    await expect(three_d.loadModelAsync(Buffer.from("not a model"))).rejects.toThrow("binary glTF");
+    expect(() => three_d.loadModel("/nonexistent/model.gltf")).toThrow("Failed to read");
+  });
+});
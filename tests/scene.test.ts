@@ -0,0 +1,165 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+const WIDTH = 32;
+const HEIGHT = 16;
+
+function expectClose(actual: number[], expected: number[], digits = 4) {
+  expect(actual.length).toBe(expected.length);
+  expected.forEach((e, i) => expect(actual[i]).toBeCloseTo(e, digits));
+}
+
+function pixel(pixels: Uint8Array, x: number, y: number) {
+  const i = (y * WIDTH + x) * 4;
+  return Array.from(pixels.slice(i, i + 4));
+}
+
+// Rotation by `degrees` about the z axis.
+function rotationZ(degrees: number) {
+  const half = (degrees * Math.PI) / 360;
+  return new three_d.NQuaternion(0, 0, Math.sin(half), Math.cos(half));
+}
+
+function translation(matrix: three_d.Matrix4) {
+  return matrix.data.slice(12, 15);
+}
+
+describe("Node", () => {
+  test("Composes local transforms into world matrices", () => {
+    const parent = new three_d.Node("parent");
+    parent.setTranslation(new three_d.Vector3(1, 0, 0));
+    parent.setRotation(rotationZ(90));
+    const child = new three_d.Node("child");
+    child.setTranslation(new three_d.Vector3(0, 1, 0));
+    child.setScale(new three_d.Vector3(2, 2, 2));
+    parent.addChild(child);
+
+    expectClose(translation(child.localMatrix()), [0, 1, 0]);
+    expectClose(translation(child.worldMatrix()), [0, 0, 0]);
+    // The child's x axis is rotated onto y and scaled by 2.
+    expectClose(child.worldMatrix().data.slice(0, 3), [0, 2, 0]);
+
+    parent.setTranslation(new three_d.Vector3(0, 0, 5));
+    expectClose(translation(child.worldMatrix()), [-1, 0, 5]);
+    expect(child.parent?.name).toBe("parent");
+  });
+
+  test("Normalizes rotations and rejects invalid values", () => {
+    const node = new three_d.Node();
+    expect(node.name).toBe("");
+    node.setRotation(new three_d.NQuaternion(0, 0, 2, 0));
+    const q = node.getRotation();
+    expectClose([q.x, q.y, q.z, q.w], [0, 0, 1, 0]);
+    expect(() => node.setRotation(new three_d.NQuaternion(0, 0, 0, 0))).toThrow("non-zero");
+    expect(() => node.setTranslation(new three_d.Vector3(NaN, 0, 0))).toThrow("finite");
+  });
+
+  test("Finds and traverses nodes depth first", () => {
+    const scene = new three_d.Scene();
+    const arm = new three_d.Node("arm");
+    const hand = new three_d.Node("hand");
+    const leg = new three_d.Node("leg");
+    arm.addChild(hand);
+    scene.add(arm);
+    scene.add(leg);
+
+    expect(scene.traverse().map((n) => n.name)).toEqual(["root", "arm", "hand", "leg"]);
+    expect(scene.find("hand")?.parent?.name).toBe("arm");
+    expect(arm.find("leg")).toBeNull();
+    expect(scene.root.children().map((n) => n.name)).toEqual(["arm", "leg"]);
+
+    leg.addChild(hand);
+    expect(arm.children()).toEqual([]);
+    expect(leg.traverse().map((n) => n.name)).toEqual(["leg", "hand"]);
+    expect(() => hand.addChild(leg)).toThrow("own subtree");
+    expect(() => hand.addChild(hand)).toThrow("own subtree");
+  });
+
+  test("Keeps the world transform when reparenting", () => {
+    const a = new three_d.Node("a");
+    a.setTranslation(new three_d.Vector3(3, 0, 0));
+    a.setRotation(rotationZ(45));
+    a.setScale(new three_d.Vector3(2, 2, 2));
+    const b = new three_d.Node("b");
+    b.setTranslation(new three_d.Vector3(0, -2, 1));
+    b.setRotation(rotationZ(-90));
+    const child = new three_d.Node("child");
+    child.setTranslation(new three_d.Vector3(1, 1, 0));
+    a.addChild(child);
+
+    const world = child.worldMatrix().data;
+    child.setParent(b);
+    expect(child.parent?.name).toBe("b");
+    expectClose(child.worldMatrix().data, world);
+    expectClose([child.getScale().x], [2]);
+
+    child.setParent(null);
+    expect(child.parent).toBeNull();
+    expectClose(child.localMatrix().data, world);
+    expect(b.children()).toEqual([]);
+  });
+});
+
+describe("Scene", () => {
+  test("Moves attached objects and lights", () => {
+    const renderer = new three_d.Renderer(WIDTH, HEIGHT, "Test");
+    renderer.init();
+    const ctx = renderer.context;
+    const white = new three_d.NSrgba(1, 1, 1, 1);
+    const cube = new three_d.Gm(
+      new three_d.Mesh(ctx, three_d.CpuMesh.cube()),
+      new three_d.ColorMaterial(white),
+    );
+    const lamp = new three_d.PointLight(ctx, 1, white, [0, 1, 0]);
+    const sun = new three_d.DirectionalLight(ctx, 1, white, [1, 0, 0]);
+
+    const node = new three_d.Node("node");
+    node.addObject(cube);
+    node.addLight(lamp);
+    node.addLight(sun);
+    node.setTranslation(new three_d.Vector3(10, 0, 0));
+    node.setRotation(rotationZ(90));
+
+    expectClose(cube.aabb().center(), [10, 0, 0]);
+    expectClose(lamp.getPosition(), [9, 0, 0]);
+    expectClose(sun.getDirection(), [0, 1, 0]);
+    expect(node.objects().length).toBe(1);
+
+    expect(node.removeObject(cube)).toBe(true);
+    expect(node.removeObject(cube)).toBe(false);
+    expect(node.removeLight(lamp)).toBe(true);
+    node.setTranslation(new three_d.Vector3(0, 0, 0));
+    expectClose(cube.aabb().center(), [10, 0, 0]);
+    expectClose(lamp.getPosition(), [9, 0, 0]);
+    expectClose(sun.getDirection(), [0, 1, 0]);
+  });
+
+  test("Renders the objects of a scene", () => {
+    const renderer = new three_d.Renderer(WIDTH, HEIGHT, "Test");
+    renderer.init();
+    const ctx = renderer.context;
+    const camera = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+    const positions = [-100, 0, 0, 100, 0, 0, 100, 100, 0, -100, 100, 0];
+    const quad = new three_d.Gm(
+      new three_d.Mesh(ctx, new three_d.CpuMesh(positions, [0, 1, 2, 0, 2, 3])),
+      new three_d.ColorMaterial(new three_d.NSrgba(1, 0, 0, 1)),
+    );
+    const scene = new three_d.Scene();
+    const group = new three_d.Node("group");
+    const part = new three_d.Node("part");
+    part.addObject(quad);
+    group.addChild(part);
+    scene.add(group);
+    expect(scene.objects().length).toBe(1);
+
+    renderer.renderScene(scene, camera);
+    expect(pixel(renderer.readPixels(), 16, 0)).toEqual([255, 0, 0, 255]);
+    expect(pixel(renderer.readPixels(), 16, HEIGHT - 1)).toEqual([0, 0, 0, 255]);
+
+    // Flipping the group upside down moves the quad to the lower half.
+    group.setRotation(rotationZ(180));
+    renderer.renderScene(scene, camera);
+    expect(pixel(renderer.readPixels(), 16, 0)).toEqual([0, 0, 0, 255]);
+    expect(pixel(renderer.readPixels(), 16, HEIGHT - 1)).toEqual([255, 0, 0, 255]);
+  });
+});
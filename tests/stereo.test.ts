@@ -0,0 +1,127 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+const LEFT = [200, 100, 50, 255];
+const RIGHT = [20, 150, 250, 255];
+
+function solidTexture(ctx: three_d.Context, width: number, height: number, rgba: number[]) {
+  const data = new Uint8Array(width * height * 4);
+  for (let i = 0; i < data.length; i++) data[i] = rgba[i % 4];
+  return three_d.Texture2D.fromData(ctx, width, height, three_d.TextureFormat.R8G8B8A8, data, {
+    mipmap: false,
+  });
+}
+
+function pixel(pixels: Uint8Array, width: number, x: number, y: number) {
+  const i = (y * width + x) * 4;
+  return Array.from(pixels.slice(i, i + 4));
+}
+
+const center = () => new three_d.Camera(0, 0, 10, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+
+const close = (actual: number[], expected: number[]) =>
+  expected.forEach((e, i) => expect(actual[i]).toBeCloseTo(e, 4));
+
+describe("StereoCamera", () => {
+  test("Offsets the eyes and converges on the target", () => {
+    const stereo = new three_d.StereoCamera(center(), 0.2);
+    expect(stereo.ipd).toBeCloseTo(0.2, 6);
+    expect(stereo.convergence).toBeCloseTo(10, 4);
+
+    const left = stereo.leftCamera(three_d.StereoMode.Anaglyph, 64, 32);
+    const right = stereo.rightCamera(three_d.StereoMode.Anaglyph, 64, 32);
+    close(left.getPosition(), [-0.1, 0, 10]);
+    close(right.getPosition(), [0.1, 0, 10]);
+    close(left.getTarget(), [0, 0, 0]);
+    close(right.getTarget(), [0, 0, 0]);
+    expect(left.fov).toBeCloseTo(60, 3);
+
+    stereo.convergence = 5;
+    close(stereo.leftCamera(three_d.StereoMode.Left, 64, 32).getTarget(), [0, 0, 5]);
+
+    const mono = stereo.rightCamera(three_d.StereoMode.Mono, 64, 32);
+    close(mono.getPosition(), [0, 0, 10]);
+  });
+
+  test("Sizes the eye images for each layout", () => {
+    const stereo = new three_d.StereoCamera(center());
+    const size = (mode: three_d.StereoMode) => {
+      const vp = stereo.eyeViewport(mode, 64, 32);
+      return [vp.width, vp.height];
+    };
+    expect(size(three_d.StereoMode.Mono)).toEqual([64, 32]);
+    expect(size(three_d.StereoMode.SideBySide)).toEqual([32, 32]);
+    expect(size(three_d.StereoMode.TopBottom)).toEqual([64, 16]);
+    expect(size(three_d.StereoMode.Anaglyph)).toEqual([64, 32]);
+
+    const vp = stereo.leftCamera(three_d.StereoMode.SideBySide, 64, 32).getViewport();
+    expect([vp.x, vp.y, vp.width, vp.height]).toEqual([0, 0, 32, 32]);
+  });
+
+  test("Rejects invalid parameters", () => {
+    expect(() => new three_d.StereoCamera(center(), -1)).toThrow("Interpupillary");
+    const stereo = new three_d.StereoCamera(center());
+    expect(() => {
+      stereo.convergence = 0;
+    }).toThrow("Convergence");
+  });
+});
+
+describe("StereoCamera.composite", () => {
+  const setup = (mode: three_d.StereoMode) => {
+    const ctx = new three_d.Context();
+    const stereo = new three_d.StereoCamera(center());
+    const eye = stereo.eyeViewport(mode, 8, 4);
+    const left = solidTexture(ctx, eye.width, eye.height, LEFT);
+    const right = solidTexture(ctx, eye.width, eye.height, RIGHT);
+    const output = solidTexture(ctx, 8, 4, [0, 0, 0, 0]);
+    const target = new three_d.RenderTarget(ctx, three_d.ColorTarget.fromTexture(output));
+    stereo.composite(target, mode, left, right);
+    return target.readColor();
+  };
+
+  test("Side by side", () => {
+    const pixels = setup(three_d.StereoMode.SideBySide);
+    for (let y = 0; y < 4; y++) {
+      expect(pixel(pixels, 8, 0, y)).toEqual(LEFT);
+      expect(pixel(pixels, 8, 3, y)).toEqual(LEFT);
+      expect(pixel(pixels, 8, 4, y)).toEqual(RIGHT);
+      expect(pixel(pixels, 8, 7, y)).toEqual(RIGHT);
+    }
+  });
+
+  test("Top and bottom, left eye on top", () => {
+    const pixels = setup(three_d.StereoMode.TopBottom);
+    for (let x = 0; x < 8; x++) {
+      expect(pixel(pixels, 8, x, 0)).toEqual(LEFT);
+      expect(pixel(pixels, 8, x, 1)).toEqual(LEFT);
+      expect(pixel(pixels, 8, x, 2)).toEqual(RIGHT);
+      expect(pixel(pixels, 8, x, 3)).toEqual(RIGHT);
+    }
+  });
+
+  test("Single eyes", () => {
+    expect(pixel(setup(three_d.StereoMode.Left), 8, 5, 2)).toEqual(LEFT);
+    expect(pixel(setup(three_d.StereoMode.Mono), 8, 5, 2)).toEqual(LEFT);
+    expect(pixel(setup(three_d.StereoMode.Right), 8, 5, 2)).toEqual(RIGHT);
+  });
+
+  test("Red/cyan anaglyph", () => {
+    const pixels = setup(three_d.StereoMode.Anaglyph);
+    expect(pixel(pixels, 8, 0, 0)).toEqual([200, 150, 250, 255]);
+    expect(pixel(pixels, 8, 7, 3)).toEqual([200, 150, 250, 255]);
+  });
+
+  test("Rejects eye textures of the wrong size", () => {
+    const ctx = new three_d.Context();
+    const stereo = new three_d.StereoCamera(center());
+    const eye = solidTexture(ctx, 8, 4, LEFT);
+    const target = new three_d.RenderTarget(
+      ctx,
+      three_d.ColorTarget.fromTexture(solidTexture(ctx, 8, 4, [0, 0, 0, 0])),
+    );
+    expect(() => stereo.composite(target, three_d.StereoMode.SideBySide, eye, eye)).toThrow(
+      "needs 4x4",
+    );
+  });
+});
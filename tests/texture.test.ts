@@ -0,0 +1,231 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+describe("CpuTexture", () => {
+  test("Constructor from Uint8Array", () => {
+    const cpu = new three_d.CpuTexture(
+      2,
+      2,
+      three_d.TextureFormat.R8G8B8A8,
+      new Uint8Array(16),
+      { minFilter: three_d.TextureMinFilter.Nearest, mipmap: false },
+    );
+    expect(cpu).toBeInstanceOf(three_d.CpuTexture);
+    expect(cpu.width).toBe(2);
+    expect(cpu.height).toBe(2);
+    expect(cpu.format).toBe(three_d.TextureFormat.R8G8B8A8);
+  });
+
+  test("Constructor from NF16 values", () => {
+    const cpu = new three_d.CpuTexture(2, 1, three_d.TextureFormat.R16F, [
+      new three_d.Nf16(0.5),
+      new three_d.Nf16(1.5),
+    ]);
+    expect(cpu.format).toBe(three_d.TextureFormat.R16F);
+  });
+
+  test("Rejects mismatched data", () => {
+    expect(
+      () => new three_d.CpuTexture(2, 2, three_d.TextureFormat.R8G8B8A8, new Uint8Array(3)),
+    ).toThrow();
+    expect(
+      () => new three_d.CpuTexture(1, 1, three_d.TextureFormat.R32F, new Uint8Array(1)),
+    ).toThrow();
+  });
+});
+
+describe("Texture2D", () => {
+  test("Upload and fill", () => {
+    const ctx = new three_d.Context();
+    const texture = three_d.Texture2D.fromData(
+      ctx,
+      4,
+      4,
+      three_d.TextureFormat.R32G32B32A32F,
+      new Float32Array(64),
+    );
+    expect(texture).toBeInstanceOf(three_d.Texture2D);
+    expect(texture.width).toBe(4);
+    texture.fill(new Float32Array(64).fill(1));
+    expect(ctx.isValid()).toBe(true);
+  });
+
+  test("Generates mipmaps only for mipmap filters", () => {
+    const ctx = new three_d.Context();
+    const make = (options: three_d.TextureOptions) =>
+      three_d.Texture2D.fromData(
+        ctx,
+        4,
+        4,
+        three_d.TextureFormat.R8G8B8A8,
+        new Uint8Array(64),
+        options,
+      );
+    expect(make({}).numberOfMipMaps()).toBeGreaterThan(1);
+    expect(make({ minFilter: three_d.TextureMinFilter.Nearest }).numberOfMipMaps()).toBe(1);
+    expect(
+      make({ minFilter: three_d.TextureMinFilter.NearestMipmapNearest }).numberOfMipMaps(),
+    ).toBeGreaterThan(1);
+    expect(() =>
+      make({ minFilter: three_d.TextureMinFilter.LinearMipmapLinear, mipmap: false }),
+    ).toThrow("mipmap: false");
+    expect(() => make({ minFilter: three_d.TextureMinFilter.Linear, mipmap: true })).toThrow(
+      "mipmap: true",
+    );
+  });
+});
+
+describe("Texture2DArray", () => {
+  test("Layer uploads and region updates", () => {
+    const ctx = new three_d.Context();
+    const layers = [0, 1, 2].map(
+      (i) =>
+        new three_d.CpuTexture(2, 2, three_d.TextureFormat.R8G8B8A8, new Uint8Array(16).fill(i)),
+    );
+    const array = new three_d.Texture2DArray(ctx, layers);
+    expect(array.layers).toBe(3);
+    array.fillLayer(1, new Uint8Array(16).fill(255));
+    array.updateRegion(2, 1, 0, 1, 2, new Uint8Array(8));
+    expect(() => array.updateRegion(3, 0, 0, 1, 1, new Uint8Array(4))).toThrow();
+    expect(() => array.updateRegion(0, 0xffffffff, 0, 2, 1, new Uint8Array(8))).toThrow("outside");
+    expect(ctx.isValid()).toBe(true);
+  });
+});
+
+describe("Texture3D", () => {
+  test("Slice uploads and sampling from a custom material", () => {
+    const ctx = new three_d.Context();
+    const cpu = new three_d.CpuTexture3D(4, 4, 4, three_d.TextureFormat.R32F, new Float32Array(64), {
+      wrapR: three_d.TextureWrap.ClampToEdge,
+    });
+    expect(cpu.depth).toBe(4);
+    const volume = new three_d.Texture3D(ctx, cpu);
+    volume.fillSlice(2, new Float32Array(16).fill(1));
+    volume.updateRegion(1, 1, 1, 2, 2, 2, new Float32Array(8));
+
+    // Samples the center of slice 0.
+    const material = new three_d.CustomMaterial(`
+      uniform sampler3D volume;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = vec4(texture(volume, vec3(0.5, 0.5, 0.125)).rrr, 1.0); }
+    `);
+    material.setTexture3D("volume", volume);
+    const renderer = new three_d.Renderer(4, 4);
+    renderer.init(ctx);
+    const camera = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+    const square = new three_d.Gm(new three_d.Mesh(ctx, three_d.CpuMesh.square()), material);
+    // The center pixel, drawn over a blue background.
+    const center = () => Array.from(renderer.readPixels().slice(40, 43));
+    renderer.setClearColor(new three_d.NSrgba(0, 0, 1, 1));
+    renderer.render([square], camera);
+    expect(center()).toEqual([0, 0, 0]);
+
+    // Slices can be streamed in while a material samples the texture.
+    volume.fillSlice(0, new Float32Array(16).fill(1));
+    renderer.render([square], camera);
+    expect(center()).toEqual([255, 255, 255]);
+    expect(ctx.isValid()).toBe(true);
+  });
+});
+
+describe("TextureCubeMap", () => {
+  test("Six faces addressed by CubeMapSide", () => {
+    const ctx = new three_d.Context();
+    const sides = [...new three_d.CubeMapSideIterator()];
+    expect(sides.length).toBe(6);
+    expect(sides[0]).toBe(three_d.CubeMapSide.PositiveX);
+
+    const faces = sides.map(
+      (_, i) => new three_d.CpuTexture(2, 2, three_d.TextureFormat.R8G8B8A8, new Uint8Array(16).fill(i)),
+    );
+    const cube = new three_d.TextureCubeMap(ctx, faces);
+    sides.forEach((side, i) => {
+      expect(cube.readFace(side)[0]).toBe(i);
+    });
+
+    cube.fillFace(three_d.CubeMapSide.NegativeZ, new Uint8Array(16).fill(9));
+    expect(cube.readFace(three_d.CubeMapSide.NegativeZ)[0]).toBe(9);
+  });
+
+  test("Reads faces with unaligned rows after reading depth", () => {
+    const ctx = new three_d.Context();
+    new three_d.DepthTexture2D(ctx, 3, 3).readDepth();
+    // 3 RGB texels make 9 byte rows, which GL would pad to 12 by default.
+    const texels = Uint8Array.from({ length: 27 }, (_, i) => i);
+    const faces = Array.from({ length: 6 }, () => new three_d.CpuTexture(3, 3, three_d.TextureFormat.R8G8B8, texels));
+    const cube = new three_d.TextureCubeMap(ctx, faces);
+    const face = cube.readFace(three_d.CubeMapSide.PositiveX);
+    expect(face.length).toBe(27);
+    expect(Array.from(face)).toEqual(Array.from(texels));
+  });
+
+  test("Equirectangular panorama", () => {
+    const ctx = new three_d.Context();
+    const panorama = new three_d.CpuTexture(
+      16,
+      8,
+      three_d.TextureFormat.R32G32B32F,
+      new Float32Array(16 * 8 * 3).fill(0.5),
+    );
+    const cube = three_d.TextureCubeMap.fromEquirectangular(ctx, panorama);
+    expect(cube.width).toBe(4);
+    expect(cube.format).toBe(three_d.TextureFormat.R32G32B32A32F);
+    expect(cube.readFace(three_d.CubeMapSide.PositiveY)[0]).toBeCloseTo(0.5, 5);
+  });
+});
+
+describe("Depth textures", () => {
+  test("Clear and read raw depth in every format", () => {
+    const ctx = new three_d.Context();
+    for (const format of [
+      three_d.TextureFormat.Depth16,
+      three_d.TextureFormat.Depth24,
+      three_d.TextureFormat.Depth32F,
+    ]) {
+      const depth = new three_d.DepthTexture2D(ctx, 4, 2, format);
+      expect(depth.format).toBe(format);
+      depth.clear(0.25);
+      const values = depth.readDepth();
+      expect(values.length).toBe(8);
+      expect(values[7]).toBeCloseTo(0.25, 4);
+    }
+    expect(() => new three_d.DepthTexture2D(ctx, 4, 4, three_d.TextureFormat.R8G8B8A8)).toThrow(/depth format/);
+  });
+
+  test("Linearizes depth for a camera", () => {
+    const ctx = new three_d.Context();
+    const camera = new three_d.Camera(0, 0, 1, 0, 0, 0, 0, 1, 0, 45, 0.1, 1000);
+    const [near, far, distance] = [0.1, 1000, 10];
+    const ndc = (far + near) / (far - near) - (2 * far * near) / ((far - near) * distance);
+    const depth = new three_d.DepthTexture2D(ctx, 2, 2);
+    depth.clear((ndc + 1) / 2);
+    expect(depth.readDepth(camera)[0]).toBeCloseTo(distance, 2);
+    depth.clear();
+    expect(depth.readDepth(camera)[3]).toBeCloseTo(far, 0);
+  });
+
+  test("Array layers and cube map faces", () => {
+    const ctx = new three_d.Context();
+    const array = new three_d.DepthTexture2DArray(ctx, 2, 2, 3, three_d.TextureFormat.Depth24);
+    expect(array.layers).toBe(3);
+    array.clear(1, 0.5);
+    array.clear(2, 0.75);
+    expect(array.readDepth(1)[0]).toBeCloseTo(0.5, 5);
+    expect(array.readDepth(2)[0]).toBeCloseTo(0.75, 5);
+    expect(() => array.readDepth(3)).toThrow(/out of range/);
+
+    const cube = new three_d.DepthTextureCubeMap(ctx, 4);
+    cube.clear(three_d.CubeMapSide.NegativeY, 0.125);
+    expect(cube.readDepth(three_d.CubeMapSide.NegativeY)[15]).toBe(0.125);
+
+    const material = new three_d.CustomMaterial(`
+      uniform sampler2DArray shadows;
+      in vec2 uvs;
+      layout (location = 0) out vec4 outColor;
+      void main() { outColor = vec4(texture(shadows, vec3(uvs, 0.0)).r); }
+    `);
+    material.setDepthTextureArray("shadows", array);
+    material.setDepthTextureCubeMap("cube", cube);
+    expect(ctx.isValid()).toBe(true);
+  });
+});
@@ -0,0 +1,92 @@
+import { expect, test, describe } from "bun:test";
+import * as three_d from "../index";
+
+const SIZE = 16;
+
+const camera = () => new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.1, 100);
+
+// The red channel at the center of a full-screen square glowing with a
+// linear intensity of 1.
+function center(renderer: three_d.Renderer, camera: three_d.Camera) {
+  const ctx = renderer.context;
+  const mesh = new three_d.Mesh(ctx, three_d.CpuMesh.square());
+  mesh.setTransformation(new three_d.Matrix4([5, 0, 0, 0, 0, 5, 0, 0, 0, 0, 5, 0, 0, 0, 0, 1]));
+  const glow = new three_d.PhysicalMaterial(
+    new three_d.NSrgba(0, 0, 0, 1),
+    0,
+    1,
+    new three_d.NSrgba(1, 1, 1, 1),
+  );
+  const light = new three_d.AmbientLight(ctx, 0, new three_d.NSrgba(1, 1, 1, 1));
+  renderer.render([new three_d.Gm(mesh, glow)], camera, [light]);
+  const floats = new Float32Array(renderer.readPixels(three_d.DataType.Float).buffer);
+  const i = ((SIZE / 2) * SIZE + SIZE / 2) * 4;
+  return floats[i];
+}
+
+function setup() {
+  const renderer = new three_d.Renderer(SIZE, SIZE);
+  renderer.init();
+  return renderer;
+}
+
+describe("Camera output", () => {
+  test("Defaults to ACES filmic in sRGB", () => {
+    const cam = camera();
+    expect(cam.toneMapping).toBe(three_d.ToneMapping.ACESFilmic);
+    expect(cam.colorMapping).toBe(three_d.ColorMapping.Srgb);
+    expect(cam.exposure).toBe(1);
+    expect(() => {
+      cam.exposure = 0;
+    }).toThrow("Exposure must be positive");
+  });
+
+  test("Applies each operator", () => {
+    const renderer = setup();
+    const srgb = (linear: number) => 1.055 * Math.pow(linear, 1 / 2.4) - 0.055;
+    const cam = camera();
+
+    cam.toneMapping = three_d.ToneMapping.None;
+    expect(center(renderer, cam)).toBeCloseTo(1, 2);
+
+    cam.toneMapping = three_d.ToneMapping.Reinhard;
+    expect(center(renderer, cam)).toBeCloseTo(srgb(0.5), 2);
+    cam.colorMapping = three_d.ColorMapping.Linear;
+    expect(center(renderer, cam)).toBeCloseTo(0.5, 2);
+
+    // Exposure goes through the separate pass and matches the native operator.
+    cam.exposure = 3;
+    expect(center(renderer, cam)).toBeCloseTo(0.75, 2);
+    cam.colorMapping = three_d.ColorMapping.Srgb;
+    expect(center(renderer, cam)).toBeCloseTo(srgb(0.75), 2);
+
+    const operators = [
+      three_d.ToneMapping.ACESFilmic,
+      three_d.ToneMapping.Filmic,
+      three_d.ToneMapping.Lottes,
+      three_d.ToneMapping.Uchimura,
+      three_d.ToneMapping.Unreal,
+    ];
+    for (const operator of operators) {
+      cam.toneMapping = operator;
+      cam.exposure = 1;
+      const normal = center(renderer, cam);
+      cam.exposure = 0.25;
+      const dark = center(renderer, cam);
+      expect(normal).toBeGreaterThan(0.3);
+      expect(normal).toBeLessThan(1);
+      expect(dark).toBeLessThan(normal);
+    }
+  });
+
+  test("Maps the clear color with the frame in the linear pass", () => {
+    const renderer = setup();
+    renderer.setClearColor(new three_d.NSrgba(0, 0, 1, 1));
+    const cam = camera();
+    cam.toneMapping = three_d.ToneMapping.None;
+    cam.exposure = 0.5;
+    renderer.render([], cam);
+    const pixels = renderer.readPixels();
+    expect(Array.from(pixels.slice(0, 4))).toEqual([0, 0, 188, 255]);
+  });
+});
@@ -28,6 +28,218 @@ describe("Camera", () => {
     const cam = new three_d.Camera(10, 5, -2, 0, 0, 0, 0, 0, 1, 45, 0.5, 100);
     expect(cam).toBeDefined();
   });
+
+  test("Camera rejects invalid parameters", () => {
+    expect(() => new three_d.Camera(0, 0, 0, 0, 0, 0, 0, 1, 0, 45, 0.1, 100)).toThrow(
+      "position and target",
+    );
+    expect(() => new three_d.Camera(0, 0, 1, 0, 0, 0, 0, 1, 0, 180, 0.1, 100)).toThrow(
+      "Field of view",
+    );
+    expect(() => new three_d.Camera(0, 0, 1, 0, 0, 0, 0, 1, 0, 45, 0, 100)).toThrow("Near plane");
+    expect(() => new three_d.Camera(0, 0, 1, 0, 0, 0, 0, 1, 0, 45, 10, 1)).toThrow("Far plane");
+  });
+
+  test("Camera setters update the view", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 45, 0.1, 100);
+    cam.setPosition(0, 0, 10);
+    expect(cam.getPosition()).toEqual([0, 0, 10]);
+    cam.setTarget(0, 0, 20);
+    expect(cam.getTarget()).toEqual([0, 0, 20]);
+    expect(cam.getViewDirection()).toEqual([0, 0, 1]);
+    cam.setUp(1, 0, 0);
+    expect(cam.getUp()).toEqual([1, 0, 0]);
+    expect(() => cam.setUp(0, 0, 1)).toThrow("parallel");
+  });
+
+  test("Camera projection parameters", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 60, 0.5, 50);
+    expect(cam.fov).toBeCloseTo(60, 4);
+    expect(cam.zNear).toBeCloseTo(0.5, 6);
+    expect(cam.zFar).toBeCloseTo(50, 6);
+    expect(cam.isOrthographic).toBe(false);
+
+    cam.setFov(90);
+    cam.setClipPlanes(1, 10);
+    expect(cam.fov).toBeCloseTo(90, 4);
+    expect(cam.zNear).toBeCloseTo(1, 6);
+    expect(cam.zFar).toBeCloseTo(10, 6);
+
+    cam.setOrthographicProjection(4, 0, 10);
+    expect(cam.isOrthographic).toBe(true);
+    expect(cam.fov).toBeNull();
+    expect(() => cam.setFov(45)).toThrow("orthographic");
+  });
+
+  test("Camera viewport", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 45, 0.1, 100);
+    cam.setViewport(new three_d.Viewport(10, 20, 800, 600));
+    const vp = cam.getViewport();
+    expect([vp.x, vp.y, vp.width, vp.height]).toEqual([10, 20, 800, 600]);
+    expect(() => cam.setViewport(three_d.Viewport.atOrigin(0, 600))).toThrow("empty");
+  });
+
+  test("Perspective camera matrices", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 90, 1, 100);
+    cam.setViewport(three_d.Viewport.atOrigin(100, 100));
+
+    // The view matrix moves the camera to the origin looking down -Z.
+    const view = cam.viewMatrix().data;
+    expect(view[14]).toBeCloseTo(-5, 5);
+
+    // With a 90° field of view and a square viewport, the focal lengths are 1.
+    const proj = cam.projectionMatrix().data;
+    expect(proj[0]).toBeCloseTo(1, 5);
+    expect(proj[5]).toBeCloseTo(1, 5);
+    expect(proj[11]).toBeCloseTo(-1, 5);
+
+    // The origin is 5 units in front of the camera and projects to the center.
+    const vp = cam.viewProjectionMatrix().data;
+    const w = vp[15];
+    expect(vp[12] / w).toBeCloseTo(0, 5);
+    expect(vp[13] / w).toBeCloseTo(0, 5);
+    expect(w).toBeCloseTo(5, 5);
+  });
+
+  test("Orthographic camera matrices", () => {
+    const cam = three_d.Camera.orthographic(0, 0, 5, 0, 0, 0, 0, 1, 0, 4, 0.1, 100);
+    cam.setViewport(three_d.Viewport.atOrigin(200, 100));
+    expect(cam.isOrthographic).toBe(true);
+
+    // The height scales with the distance to the target, so the view volume
+    // is 20 units tall and 40 units wide.
+    const proj = cam.projectionMatrix().data;
+    expect(proj[0]).toBeCloseTo(0.05, 5);
+    expect(proj[5]).toBeCloseTo(0.1, 5);
+    expect(proj[11]).toBeCloseTo(0, 5);
+    expect(proj[15]).toBeCloseTo(1, 5);
+  });
+});
+
+describe("Camera picking", () => {
+  const close = (actual: number[], expected: number[]) => {
+    expect(actual).toHaveLength(expected.length);
+    expected.forEach((e, i) => expect(actual[i]).toBeCloseTo(e, 4));
+  };
+
+  test("Perspective rays start at the camera", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 90, 0.1, 100);
+    cam.setViewport(three_d.Viewport.atOrigin(100, 100));
+
+    const center = cam.rayFromPixel(50, 50);
+    close(center.origin, [0, 0, 5]);
+    close(center.direction, [0, 0, -1]);
+
+    // Pixel coordinates start in the bottom left corner.
+    const corner = cam.rayFromPixel(100, 100);
+    close(corner.origin, [0, 0, 5]);
+    const s = Math.sqrt(1 / 3);
+    close(corner.direction, [s, s, -s]);
+  });
+
+  test("Orthographic rays are parallel", () => {
+    const cam = three_d.Camera.orthographic(0, 0, 5, 0, 0, 0, 0, 1, 0, 0.4, 0.1, 100);
+    cam.setViewport(three_d.Viewport.atOrigin(100, 100));
+
+    // The view volume is 2 units tall at a distance of 5.
+    const ray = cam.rayFromPixel(100, 0);
+    close(ray.origin, [1, -1, 5]);
+    close(ray.direction, [0, 0, -1]);
+  });
+
+  test("Project and unproject round trip", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 90, 0.1, 100);
+    cam.setViewport(new three_d.Viewport(10, 20, 100, 100));
+
+    close(cam.project([0, 0, 0]), [60, 70, 5]);
+    close(cam.project([1, 1, 0]), [70, 80, 5]);
+    close(cam.unproject([70, 80], 5), [1, 1, 0]);
+    close(cam.unproject(cam.project([-2, 3, -4]).slice(0, 2), 9), [-2, 3, -4]);
+
+    const ortho = three_d.Camera.orthographic(0, 0, 5, 0, 0, 0, 0, 1, 0, 0.4, 0.1, 100);
+    ortho.setViewport(three_d.Viewport.atOrigin(100, 100));
+    close(ortho.project([0.5, 0.5, 1]), [75, 75, 4]);
+    close(ortho.unproject([75, 75], 4), [0.5, 0.5, 1]);
+  });
+
+  test("Takes the viewport of the frame it is rendered into", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 90, 0.1, 100);
+    // Without setViewport or rendering, pixels refer to a 1x1 viewport.
+    let vp = cam.getViewport();
+    expect([vp.x, vp.y, vp.width, vp.height]).toEqual([0, 0, 1, 1]);
+    close(cam.project([0, 0, 0]), [0.5, 0.5, 5]);
+
+    const renderer = new three_d.Renderer(64, 32);
+    renderer.init();
+    renderer.render([], cam);
+    vp = cam.getViewport();
+    expect([vp.x, vp.y, vp.width, vp.height]).toEqual([0, 0, 64, 32]);
+    close(cam.project([0, 0, 0]), [32, 16, 5]);
+    close(cam.rayFromPixel(32, 16).direction, [0, 0, -1]);
+    close(cam.unproject([32, 16], 5), [0, 0, 0]);
+
+    renderer.render([], cam, [], { viewport: new three_d.Viewport(32, 0, 32, 32) });
+    close(cam.project([0, 0, 0]), [48, 16, 5]);
+  });
+
+  test("Rejects malformed points", () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 90, 0.1, 100);
+    expect(() => cam.project([1, 2])).toThrow("3 components");
+    expect(() => cam.unproject([1, 2, 3], 1)).toThrow("2 components");
+  });
+});
+
+describe("Frustum", () => {
+  const camera = () => {
+    const cam = new three_d.Camera(0, 0, 5, 0, 0, 0, 0, 1, 0, 90, 1, 100);
+    cam.setViewport(three_d.Viewport.atOrigin(100, 100));
+    return cam;
+  };
+
+  test("Planes point into the frustum", () => {
+    const planes = camera().frustum().planes();
+    expect(planes).toHaveLength(6);
+    for (const [a, b, c] of planes) {
+      expect(Math.hypot(a, b, c)).toBeCloseTo(1, 5);
+    }
+    // Near plane at z = 4 and far plane at z = -95, both facing the inside.
+    const [near, far] = [planes[4], planes[5]];
+    expect(near[2]).toBeCloseTo(-1, 5);
+    expect(near[3]).toBeCloseTo(4, 4);
+    expect(far[2]).toBeCloseTo(1, 5);
+    expect(far[3]).toBeCloseTo(95, 3);
+  });
+
+  test("Contains points", () => {
+    const frustum = camera().frustum();
+    expect(frustum.containsPoint(0, 0, 0)).toBe(true);
+    expect(frustum.containsPoint(4, 4, 0)).toBe(true);
+    expect(frustum.containsPoint(6, 0, 0)).toBe(false);
+    expect(frustum.containsPoint(0, 0, 4.5)).toBe(false);
+    expect(frustum.containsPoint(0, 0, -96)).toBe(false);
+  });
+
+  test("Intersects bounding boxes", () => {
+    const frustum = camera().frustum();
+    const box = (x: number, y: number, z: number) =>
+      new three_d.AxisAlignedBoundingBox(x - 1, y - 1, z - 1, x + 1, y + 1, z + 1);
+    expect(frustum.intersectsAabb(box(0, 0, 0))).toBe(true);
+    // Partially inside still counts.
+    expect(frustum.intersectsAabb(box(5.5, 0, 0))).toBe(true);
+    expect(frustum.intersectsAabb(box(10, 0, 0))).toBe(false);
+    expect(frustum.intersectsAabb(box(0, 0, 10))).toBe(false);
+    expect(frustum.intersectsAabb(box(0, 0, -200))).toBe(false);
+    expect(frustum.intersectsAabb(three_d.AxisAlignedBoundingBox.empty())).toBe(false);
+  });
+});
+
+describe("Renderer", () => {
+  test("Frustum culling is enabled by default", () => {
+    const renderer = new three_d.Renderer(64, 64);
+    expect(renderer.frustumCulling).toBe(true);
+    renderer.frustumCulling = false;
+    expect(renderer.frustumCulling).toBe(false);
+  });
 });
 
 describe("Viewport", () => {